@@ -6,22 +6,10 @@ fn main() {
     let toml_contents = std::fs::read("Cargo.toml").expect("Failed to find Cargo.toml");
     let manifest =
         Manifest::from_slice(&toml_contents).expect("Failed to parse Cargo.toml as Manifest");
-    let curl_version = {
-        let curl = manifest
-            .dependencies
-            .get("curl")
-            .expect("Couldn't find curl in parsed manifest");
-        match curl {
-            cargo_toml::Dependency::Simple(ver) => ver,
-            cargo_toml::Dependency::Detailed(detail) => &detail.version.as_ref().unwrap().clone(),
-            // This case should not happen since we are reading the root Cargo.toml
-            cargo_toml::Dependency::Inherited(_) => {
-                panic!("Couldn't determine curl version since it appears as inherited.")
-            },
-        }
-    };
-    // Pass the $CURL_VERSION to the crate at compile time.
-    println!("cargo:rustc-env=CURL_VERSION={}", curl_version);
+    // Pass the versions of the dependencies used by the generated install script to the crate at
+    // compile time.
+    println!("cargo:rustc-env=CURL_VERSION={}", dependency_version(&manifest, "curl"));
+    println!("cargo:rustc-env=SHA2_VERSION={}", dependency_version(&manifest, "sha2"));
 
     // Pass the $TARGET to the crate at compile time.
     println!(
@@ -40,6 +28,22 @@ fn main() {
     );
 }
 
+/// Returns the version requirement of `dependency`, as written in the root Cargo.toml.
+fn dependency_version(manifest: &Manifest, dependency: &str) -> String {
+    let dep = manifest
+        .dependencies
+        .get(dependency)
+        .unwrap_or_else(|| panic!("Couldn't find {dependency} in parsed manifest"));
+    match dep {
+        cargo_toml::Dependency::Simple(ver) => ver.clone(),
+        cargo_toml::Dependency::Detailed(detail) => detail.version.as_ref().unwrap().clone(),
+        // This case should not happen since we are reading the root Cargo.toml
+        cargo_toml::Dependency::Inherited(_) => {
+            panic!("Couldn't determine {dependency} version since it appears as inherited.")
+        },
+    }
+}
+
 fn write_command_to_file(command: &[&str], file: &str) {
     let full_command =
         command.iter().fold(String::new(), |acc, argument| format!("{acc} {argument}"));
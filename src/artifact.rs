@@ -2,7 +2,7 @@ use semver::Version;
 use serde::{Deserialize, Serialize};
 
 /// All the artifacts that the [Component] contains.
-#[derive(Serialize, Deserialize, Debug, Clone, Hash)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Artifacts {
     artifacts: Vec<Artifact>,
 }
@@ -13,24 +13,51 @@ impl Artifacts {
         self.artifacts.iter().find_map(|artifact| artifact.get_uri_for(target))
     }
 
+    /// Get the expected SHA-256 digest of the artifact that's valid for `target`, if the manifest
+    /// specifies one.
+    pub fn get_sha256_for(&self, target: &TargetTriple) -> Option<String> {
+        self.artifacts
+            .iter()
+            .find(|artifact| artifact.get_uri_for(target).is_some())
+            .and_then(|artifact| artifact.sha256().map(String::from))
+    }
+
     /// Replace all occurrances of version string `prev` with `replacement` in all artifact URIs
-    pub fn replace_version(&mut self, prev: &Version, replacement: &Version) {
+    ///
+    /// The SHA-256 digests of the rewritten artifacts are dropped, since they are the digests of
+    /// the previous version's artifacts. Returns whether any digest was dropped.
+    pub fn replace_version(&mut self, prev: &Version, replacement: &Version) -> bool {
         let prev = prev.to_string();
         let replacement = replacement.to_string();
+        let mut dropped_checksums = false;
         for artifact in self.artifacts.iter_mut() {
-            if artifact.0.contains(&prev) {
-                let modified = artifact.0.replace(&prev, &replacement);
-                artifact.0 = modified;
+            let uri = artifact.uri_mut();
+            if uri.contains(&prev) {
+                let modified = uri.replace(&prev, &replacement);
+                dropped_checksums |= artifact.sha256().is_some();
+                *artifact = Artifact::Uri(modified);
             }
         }
+        dropped_checksums
     }
 }
 
 /// Holds a URI used to fetch an artifact.
 ///
 /// These URIs have the following format: `(https://|file://)<path>/<component name>(-<triplet>|.masp)`
-#[derive(Serialize, Deserialize, Debug, Clone, Hash)]
-struct Artifact(String);
+///
+/// An artifact can optionally be paired with the SHA-256 digest of its contents, in which case the
+/// manifest entry is an object instead of a plain URI:
+///
+/// ```json
+/// { "uri": "https://<path>/midenc-x86_64-unknown-linux-gnu", "sha256": "<hex digest>" }
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[serde(untagged)]
+enum Artifact {
+    Uri(String),
+    Checksummed { uri: String, sha256: String },
+}
 
 #[derive(Debug, PartialEq)]
 pub enum TargetTriple {
@@ -44,6 +71,15 @@ pub enum TargetTriple {
     MidenVM,
 }
 
+impl core::fmt::Display for TargetTriple {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Custom(triplet) => f.write_str(triplet),
+            Self::MidenVM => f.write_str("miden-vm"),
+        }
+    }
+}
+
 impl TargetTriple {
     fn get_uri_extension(&self) -> String {
         match &self {
@@ -54,15 +90,35 @@ impl TargetTriple {
 }
 
 impl Artifact {
+    fn uri(&self) -> &str {
+        match self {
+            Self::Uri(uri) | Self::Checksummed { uri, .. } => uri,
+        }
+    }
+
+    fn uri_mut(&mut self) -> &mut String {
+        match self {
+            Self::Uri(uri) | Self::Checksummed { uri, .. } => uri,
+        }
+    }
+
+    fn sha256(&self) -> Option<&str> {
+        match self {
+            Self::Uri(_) => None,
+            Self::Checksummed { sha256, .. } => Some(sha256),
+        }
+    }
+
     /// Returns the URI for the specified component + triplet if it has it.
     ///
     /// NOTE: The component name is required to separate the triplet from the filename in the URI.
     fn get_uri_for(&self, target: &TargetTriple) -> Option<String> {
+        let uri = self.uri();
         #[allow(clippy::question_mark)]
-        let path = if let Some(file_path) = self.0.strip_prefix("file://") {
+        let path = if let Some(file_path) = uri.strip_prefix("file://") {
             file_path
         } else {
-            self.0.strip_prefix("https://")?
+            uri.strip_prefix("https://")?
         };
 
         // <component name>(-<triplet>|.masp)
@@ -71,9 +127,68 @@ impl Artifact {
         let wanted_uri_extension = target.get_uri_extension();
 
         if uri_extension.contains(&wanted_uri_extension) {
-            Some(self.0.clone())
+            Some(uri.to_string())
         } else {
             None
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Artifacts, TargetTriple};
+
+    fn artifacts() -> Artifacts {
+        serde_json::from_str(
+            r#"{
+                "artifacts": [
+                    "https://github.com/0xMiden/compiler/releases/download/v0.8.1/midenc-aarch64-apple-darwin",
+                    {
+                        "uri": "https://github.com/0xMiden/compiler/releases/download/v0.8.1/midenc-x86_64-unknown-linux-gnu",
+                        "sha256": "2f0e3b1c"
+                    },
+                    "file:///tmp/artifacts/core.masp"
+                ]
+            }"#,
+        )
+        .expect("invalid artifacts")
+    }
+
+    /// The install script fetches the artifact matching the host's triple, if there is one.
+    #[test]
+    fn artifact_uri_matches_target() {
+        let artifacts = artifacts();
+
+        let linux = TargetTriple::Custom(String::from("x86_64-unknown-linux-gnu"));
+        assert_eq!(
+            artifacts.get_uri_for(&linux).as_deref(),
+            Some(
+                "https://github.com/0xMiden/compiler/releases/download/v0.8.1/\
+                 midenc-x86_64-unknown-linux-gnu"
+            )
+        );
+        assert_eq!(
+            artifacts.get_uri_for(&TargetTriple::MidenVM).as_deref(),
+            Some("file:///tmp/artifacts/core.masp")
+        );
+    }
+
+    /// Checksums are optional, artifacts listed as plain URIs have none.
+    #[test]
+    fn artifact_sha256() {
+        let artifacts = artifacts();
+
+        let linux = TargetTriple::Custom(String::from("x86_64-unknown-linux-gnu"));
+        assert_eq!(artifacts.get_sha256_for(&linux).as_deref(), Some("2f0e3b1c"));
+        let darwin = TargetTriple::Custom(String::from("aarch64-apple-darwin"));
+        assert!(artifacts.get_uri_for(&darwin).is_some());
+        assert!(artifacts.get_sha256_for(&darwin).is_none());
+    }
+
+    /// When no artifact matches the host's triple, the component is built from source instead.
+    #[test]
+    fn artifact_uri_missing_target() {
+        let windows = TargetTriple::Custom(String::from("x86_64-pc-windows-msvc"));
+        assert!(artifacts().get_uri_for(&windows).is_none());
+    }
+}
@@ -1,6 +1,6 @@
 use std::{
     borrow::Cow,
-    collections::{BTreeMap, HashMap},
+    collections::{BTreeMap, HashMap, HashSet, VecDeque},
     ffi::OsString,
     fmt::{self, Display},
     hash::Hash,
@@ -15,6 +15,7 @@ use sha2::Digest;
 use crate::{
     artifact::{Artifacts, TargetTriple},
     config::Config,
+    manifest::{ManifestError, fetch_published_versions},
     profile::Profile,
     toolchain::{Toolchain, ToolchainJustification},
     utils,
@@ -30,14 +31,14 @@ impl Display for ChannelHash {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, Hash)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
 #[serde(untagged)]
 pub enum MigrationStrategy {
     NameChange { old_channel: semver::Version },
 }
 
 /// Tags used to identify special qualities of a specific channel.
-#[derive(Serialize, Deserialize, Debug, Clone, Hash)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
 #[serde(rename_all = "snake_case")]
 pub enum Tags {
     /// The channel is partially installed, i.e. only a subset of components
@@ -54,7 +55,7 @@ pub enum Tags {
 ///
 /// Different channels have different stability guarantees. See the specific details for the
 /// channel you are interested in to learn more.
-#[derive(Serialize, Deserialize, Debug, Clone, Hash)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Channel {
     /// Channels are identified by their name. The name corresponds to the channel's version.
     /// The version can contain suffixes such as "-custom", "-beta".
@@ -71,6 +72,15 @@ pub struct Channel {
     pub tags: Vec<Tags>,
     /// The set of toolchain components available in this channel
     pub components: Vec<Component>,
+    /// Commands run once every component of the channel got installed, in order, e.g. to
+    /// generate a configuration file shared by several components.
+    ///
+    /// Each hook is resolved like an alias (see [resolve_command]), except that a hook starting
+    /// with the name of a component runs that component's executable. Any other hook runs a
+    /// program from `PATH`. See [Channel::resolve_post_install_hooks].
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub post_install: Vec<CliCommands>,
 }
 
 enum InstallationMotive {
@@ -113,6 +123,68 @@ impl UpstreamChannel {
     }
 }
 
+/// How the components of an installed [Channel] differ from those of a newer version of it, as
+/// returned by [Channel::components_to_update].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChannelDiff {
+    /// Components only present in the newer channel.
+    pub added: Vec<Component>,
+    /// Components only present in the installed channel.
+    pub removed: Vec<Component>,
+    /// Components that need to be reinstalled, e.g. because their version, their source or their
+    /// features changed.
+    pub version_changed: Vec<ComponentChange>,
+    /// Components installed from a git branch which got new commits. Nothing else about them
+    /// changed.
+    pub revision_changed: Vec<ComponentChange>,
+    /// Components that are up to date, as present in the newer channel.
+    pub up_to_date: Vec<Component>,
+}
+
+/// A component present in both channels of a [ChannelDiff], which needs to be reinstalled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ComponentChange {
+    pub installed: Component,
+    pub upstream: Component,
+}
+
+impl ChannelDiff {
+    /// Whether the installed channel is up to date, i.e. no component has to be installed,
+    /// reinstalled or removed.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.version_changed.is_empty()
+            && self.revision_changed.is_empty()
+    }
+
+    /// The components that need to be reinstalled, whatever the reason.
+    pub fn changed(&self) -> impl Iterator<Item = &ComponentChange> {
+        self.version_changed.iter().chain(self.revision_changed.iter())
+    }
+}
+
+/// Lists every added, changed and removed component, one per line.
+impl Display for ChannelDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let added = self.added.iter().map(|component| {
+            format!("- {} {} (added)", component.name, component.version.describe())
+        });
+        let changed = self.changed().map(|ComponentChange { installed, upstream }| {
+            format!(
+                "- {}: {} -> {}",
+                installed.name,
+                installed.version.describe(),
+                upstream.version.describe()
+            )
+        });
+        let removed =
+            self.removed.iter().map(|component| format!("- {} (removed)", component.name));
+
+        write!(f, "{}", added.chain(changed).chain(removed).collect::<Vec<_>>().join("\n"))
+    }
+}
+
 impl Channel {
     pub fn new(
         name: semver::Version,
@@ -120,7 +192,13 @@ impl Channel {
         components: Vec<Component>,
         tags: Vec<Tags>,
     ) -> Self {
-        Self { name, alias, components, tags }
+        Self {
+            name,
+            alias,
+            components,
+            tags,
+            post_install: Vec::new(),
+        }
     }
 
     pub fn get_component(&self, name: impl AsRef<str>) -> Option<&Component> {
@@ -133,11 +211,292 @@ impl Channel {
         self.components.iter_mut().find(|c| c.name == name)
     }
 
+    /// Overrides the version of the components installed from crates.io that appear in `pins`,
+    /// see `midenup pin`. Pins of components installed from other sources are ignored.
+    ///
+    /// The pre-built artifacts of a pinned component are dropped, since they were built from the
+    /// version in the channel.
+    pub fn apply_pins(&mut self, pins: &BTreeMap<String, semver::Version>) {
+        for component in self.components.iter_mut() {
+            let Some(pinned_version) = pins.get(component.name.as_ref()) else {
+                continue;
+            };
+            match &mut component.version {
+                Authority::Cargo { version, .. } if version != pinned_version => {
+                    *version = pinned_version.clone();
+                    component.artifacts = None;
+                },
+                Authority::CargoRequirement { package, .. } => {
+                    component.version = Authority::Cargo {
+                        package: package.take(),
+                        version: pinned_version.clone(),
+                    };
+                    component.artifacts = None;
+                },
+                _ => {},
+            }
+        }
+    }
+
+    /// Resolves the requirement of every component given as an [Authority::CargoRequirement] to
+    /// the latest version of its package published on crates.io that matches it, turning them
+    /// into [Authority::Cargo] components.
+    ///
+    /// The pre-built artifacts of these components are dropped, since there is no telling which
+    /// version they were built from.
+    pub fn resolve_versions(&mut self, offline: bool) -> anyhow::Result<()> {
+        self.resolve_versions_with(|package| {
+            if offline {
+                bail!("cannot look up the published versions of '{package}' in offline mode");
+            }
+            fetch_published_versions(package)
+                .with_context(|| format!("failed to look up the published versions of '{package}'"))
+        })
+    }
+
+    /// Like [Channel::resolve_versions], but obtains the published versions of each package from
+    /// `published_versions` rather than from crates.io.
+    fn resolve_versions_with(
+        &mut self,
+        mut published_versions: impl FnMut(&str) -> anyhow::Result<Vec<semver::Version>>,
+    ) -> anyhow::Result<()> {
+        for component in self.components.iter_mut() {
+            let Authority::CargoRequirement { package, requirement } = &component.version else {
+                continue;
+            };
+            let (package, requirement) = (package.clone(), requirement.clone());
+            let package_name = package.as_deref().unwrap_or(component.name.as_ref());
+
+            let Some(version) = published_versions(package_name)?
+                .into_iter()
+                .filter(|version| requirement.matches(version))
+                .max()
+            else {
+                bail!(
+                    "no published version of '{package_name}' matches the requirement \
+                     '{requirement}' of component '{}'",
+                    component.name
+                );
+            };
+
+            component.version = Authority::Cargo { package, version };
+            component.artifacts = None;
+        }
+
+        Ok(())
+    }
+
+    /// Makes `component` be installed from the crate at `path` instead of its channel's source,
+    /// see `midenup install --override`.
+    pub fn override_with_path(&mut self, component: &str, path: PathBuf) -> anyhow::Result<()> {
+        let Some(component) = self.get_component_mut(component) else {
+            bail!("component '{component}' is not part of channel '{}'", self.name);
+        };
+
+        let crate_name = component.get_cargo_package().to_string();
+        component.version = Authority::Path {
+            path,
+            crate_name,
+            last_modification: None,
+        };
+        // Artifacts are built from the channel's source, not from the local crate.
+        component.artifacts = None;
+
+        Ok(())
+    }
+
+    /// Enables the cargo `feature` of `component`, on top of the features enabled by the channel,
+    /// see `midenup install --features`.
+    pub fn enable_feature(&mut self, component: &str, feature: &str) -> anyhow::Result<()> {
+        let Some(component) = self.get_component_mut(component) else {
+            bail!("component '{component}' is not part of channel '{}'", self.name);
+        };
+
+        if !component.features.iter().any(|enabled| enabled == feature) {
+            component.features.push(feature.to_string());
+        }
+        // Artifacts are built with the channel's features.
+        component.artifacts = None;
+
+        Ok(())
+    }
+
+    /// Disables the default cargo features of `component`, see `midenup install
+    /// --no-default-features`.
+    pub fn disable_default_features(&mut self, component: &str) -> anyhow::Result<()> {
+        let Some(component) = self.get_component_mut(component) else {
+            bail!("component '{component}' is not part of channel '{}'", self.name);
+        };
+
+        component.default_features = false;
+        // Artifacts are built with the channel's features.
+        component.artifacts = None;
+
+        Ok(())
+    }
+
+    /// Describes every reference between the components of this channel that can't be resolved:
+    /// required components that are not part of the channel, and aliases that call the executable
+    /// of a component which installs a library.
+    pub fn dangling_references(&self) -> Vec<String> {
+        let mut dangling_references = Vec::new();
+        for component in self.components.iter() {
+            for required in component.requires.iter() {
+                if self.get_component(required).is_none() {
+                    dangling_references.push(format!(
+                        "{}: component '{}' requires '{required}', which is not part of the \
+                         channel",
+                        self.name, component.name
+                    ));
+                }
+            }
+
+            if !matches!(component.get_installed_file(), InstalledFile::Library { .. }) {
+                continue;
+            }
+            for (alias, commands) in component.aliases.iter() {
+                if commands.contains(&CliCommand::Executable) {
+                    dangling_references.push(format!(
+                        "{}: alias '{alias}' calls the executable of component '{}', which \
+                         installs a library",
+                        self.name, component.name
+                    ));
+                }
+            }
+        }
+        for (index, hook) in self.post_install.iter().enumerate() {
+            if hook.is_empty() || hook.contains(&CliCommand::Executable) {
+                dangling_references.push(format!(
+                    "{}: post-install hook {} doesn't name the program it runs; hooks must start \
+                     with a component or a program name",
+                    self.name,
+                    index + 1
+                ));
+            }
+        }
+        dangling_references
+    }
+
+    /// Describes every alias that has the same name as a component of this channel. Since `miden`
+    /// resolves aliases before components, such a component can only be called with `midenup run`.
+    pub fn alias_collisions(&self) -> Vec<String> {
+        let mut collisions = Vec::new();
+        for component in self.components.iter() {
+            for alias in component.aliases.keys() {
+                if self.get_component(alias).is_some() {
+                    collisions.push(format!(
+                        "{}: alias '{alias}' of component '{}' shadows component '{alias}'",
+                        self.name, component.name
+                    ));
+                }
+            }
+        }
+        collisions
+    }
+
+    /// Describes every symlink in `opt/` (see [Component::get_symlink_name]) shared by several
+    /// executable components of this channel. Only one of them could be called that way.
+    pub fn symlink_collisions(&self) -> Vec<String> {
+        let mut symlinks: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for component in self.components.iter() {
+            if matches!(component.get_installed_file(), InstalledFile::Executable { .. }) {
+                symlinks
+                    .entry(component.get_symlink_name())
+                    .or_default()
+                    .push(format!("'{}'", component.name));
+            }
+        }
+
+        symlinks
+            .into_iter()
+            .filter(|(_, components)| components.len() > 1)
+            .map(|(symlink, components)| {
+                format!(
+                    "{}: components {} are all installed as '{symlink}'",
+                    self.name,
+                    components.join(", ")
+                )
+            })
+            .collect()
+    }
+
+    /// Describes every package which several executable components of this channel are installed
+    /// from, unless each of them installs a different binary of the package (see
+    /// [Component::bin]). `cargo install` keeps a single install of each binary in a toolchain, so
+    /// installing one of them would replace the other, and uninstalling one would remove both.
+    pub fn package_collisions(&self) -> Vec<String> {
+        let mut packages: BTreeMap<&str, Vec<&Component>> = BTreeMap::new();
+        for component in self.components.iter() {
+            if matches!(component.get_installed_file(), InstalledFile::Executable { .. }) {
+                packages.entry(component.get_cargo_package()).or_default().push(component);
+            }
+        }
+
+        packages
+            .into_iter()
+            .filter_map(|(package, components)| {
+                // A component which doesn't select a binary installs all of them.
+                let collides = |a: &Component, b: &Component| {
+                    a.bin.is_none() || b.bin.is_none() || a.bin == b.bin
+                };
+                let colliding = components
+                    .iter()
+                    .filter(|component| {
+                        components
+                            .iter()
+                            .any(|other| other.name != component.name && collides(component, other))
+                    })
+                    .map(|component| format!("'{}'", component.name))
+                    .collect::<Vec<_>>();
+                (!colliding.is_empty()).then(|| {
+                    format!(
+                        "{}: components {} are all installed from package '{package}'",
+                        self.name,
+                        colliding.join(", ")
+                    )
+                })
+            })
+            .collect()
+    }
+
+    /// Describes every problem with this channel: references between its components that can't be
+    /// resolved, aliases that shadow a component, and components sharing a symlink or a package.
+    /// See [Channel::dangling_references], [Channel::alias_collisions],
+    /// [Channel::symlink_collisions] and [Channel::package_collisions].
+    pub fn problems(&self) -> Vec<String> {
+        [
+            self.dangling_references(),
+            self.alias_collisions(),
+            self.symlink_collisions(),
+            self.package_collisions(),
+        ]
+        .concat()
+    }
+
+    /// Checks that this channel has none of the [Channel::problems]. All the problems found are
+    /// listed in the error.
+    pub fn validate(&self) -> Result<(), ManifestError> {
+        let problems = self.problems();
+        if problems.is_empty() {
+            return Ok(());
+        }
+
+        Err(ManifestError::Invalid(format!(
+            "found invalid references between components:\n{}",
+            problems.join("\n")
+        )))
+    }
+
     /// Is this channel a stable release? Does not imply that it has the `stable` alias.
     ///
+    /// Pre-releases (e.g. `0.16.0-custom-build`) are only stable if they have the `stable` alias.
+    ///
     /// To find out the latest stable [Channel], use [crate::manifest::Manifest::get_latest_stable].
     pub fn is_stable(&self) -> bool {
-        self.alias.as_ref().is_none_or(|alias| matches!(alias, ChannelAlias::Stable))
+        match &self.alias {
+            Some(alias) => matches!(alias, ChannelAlias::Stable),
+            None => self.name.pre.is_empty(),
+        }
     }
 
     pub fn is_nightly(&self) -> bool {
@@ -204,8 +563,6 @@ impl Channel {
     ) -> Option<Channel> {
         let profile = current_toolchain.profile.unwrap_or_default();
         let mut requested_components = Vec::new();
-        let mut components_to_install: Vec<Component> = Vec::new();
-        let mut components_not_found: HashMap<String, Vec<InstallationMotive>> = HashMap::new();
 
         match profile {
             Profile::Minimal => {
@@ -234,36 +591,56 @@ impl Channel {
             },
         }
 
-        for component_name in requested_components {
-            let Some(component) = self.get_component(component_name) else {
-                // NOTE: In order to provide more helpful error messages, we collect all the missing
-                // components and return a single error message at the end.
-                components_not_found
-                    .entry(component_name.to_string())
-                    .or_default()
-                    .push(InstallationMotive::ExplicitelySelected);
+        Some(self.select_components(requested_components, Some(toolchain_justification)))
+    }
 
-                continue;
-            };
-            components_to_install.push(component.clone());
+    /// Creates a "partial channel" containing only the `requested_components`, plus the components
+    /// they require, directly or transitively.
+    ///
+    /// Requested components that are missing from this channel are reported and then ignored.
+    /// `toolchain_justification` is used to point the user to where the components were requested
+    /// from, if they were not requested from the command line.
+    pub fn select_components<'a>(
+        &self,
+        requested_components: impl IntoIterator<Item = &'a str>,
+        toolchain_justification: Option<&ToolchainJustification>,
+    ) -> Channel {
+        let mut components_to_install: Vec<Component> = Vec::new();
+        let mut components_not_found: HashMap<String, Vec<InstallationMotive>> = HashMap::new();
+        let mut selected_components: HashSet<&str> = HashSet::new();
+
+        for component_name in requested_components {
+            // Requirements are followed transitively. Since every component is only selected once,
+            // cyclic requirements are followed just once as well.
+            let mut pending =
+                VecDeque::from([(component_name, InstallationMotive::ExplicitelySelected)]);
+            while let Some((component_name, motive)) = pending.pop_front() {
+                let Some(component) = self.get_component(component_name) else {
+                    // NOTE: In order to provide more helpful error messages, we collect all the
+                    // missing components and return a single error message at the end.
+                    components_not_found
+                        .entry(component_name.to_string())
+                        .or_default()
+                        .push(motive);
 
-            for depenency_name in &component.requires {
-                let Some(dependency) = self.get_component(depenency_name) else {
-                    components_not_found.entry(depenency_name.to_string()).or_default().push(
-                        InstallationMotive::Dependency { comp_name: component_name.to_string() },
-                    );
                     continue;
                 };
+                if !selected_components.insert(component.name.as_ref()) {
+                    continue;
+                }
+                components_to_install.push(component.clone());
 
-                if !components_to_install.iter().any(|c| c.name == dependency.name) {
-                    components_to_install.push(dependency.clone());
+                for dependency_name in &component.requires {
+                    pending.push_back((
+                        dependency_name.as_str(),
+                        InstallationMotive::Dependency { comp_name: component_name.to_string() },
+                    ));
                 }
             }
         }
         if !components_not_found.is_empty() {
             println!(
-                "{}: Some elements present in the current Toolchain are not present in the \
-                 upstream channel: {}",
+                "{}: Some of the requested components are not present in the upstream channel: {}",
                 "WARNING".yellow().bold(),
                 self.name
             );
@@ -287,7 +664,7 @@ impl Channel {
             // TODO: Add messages for the other justifications
             #[allow(clippy::single_match)]
             match toolchain_justification {
-                ToolchainJustification::MidenToolchainFile { path } => println!(
+                Some(ToolchainJustification::MidenToolchainFile { path }) => println!(
                     "Check the `miden_toolchain.toml` file in {} to see if any component is \
                      misspelled or got removed from upstream",
                     path.display()
@@ -296,14 +673,53 @@ impl Channel {
             }
         }
 
-        let partial_channel = Channel {
+        // Hooks that run the executable of a component which is left out can't run.
+        let post_install = self
+            .post_install
+            .iter()
+            .filter(|hook| match hook.first() {
+                Some(CliCommand::Verbatim(name)) => {
+                    self.get_component(name).is_none()
+                        || selected_components.contains(name.as_str())
+                },
+                _ => true,
+            })
+            .cloned()
+            .collect();
+
+        Channel {
             name: self.name.clone(),
             alias: self.alias.clone(),
             tags: vec![Tags::Partial],
             components: components_to_install,
-        };
+            post_install,
+        }
+    }
 
-        Some(partial_channel)
+    /// Resolves the [Channel::post_install] hooks of this channel into the commands to run, with
+    /// the channel installed in `channel_dir`.
+    pub fn resolve_post_install_hooks(
+        &self,
+        channel_dir: &Path,
+    ) -> anyhow::Result<Vec<Vec<OsString>>> {
+        self.post_install
+            .iter()
+            .map(|hook| match hook.split_first() {
+                Some((CliCommand::Verbatim(name), rest))
+                    if let Some(component) = self.get_component(name)
+                        && matches!(
+                            component.get_installed_file(),
+                            InstalledFile::Executable { .. }
+                        ) =>
+                {
+                    let hook = std::iter::once(CliCommand::Executable)
+                        .chain(rest.iter().cloned())
+                        .collect::<Vec<_>>();
+                    resolve_command_in(&hook, self, Some(component), channel_dir)
+                },
+                _ => resolve_command_in(hook, self, None, channel_dir),
+            })
+            .collect()
     }
 
     /// Checks wheter the channel [other] is Self's upstream counterpart.
@@ -350,6 +766,44 @@ impl Channel {
         upstream_counterpart
     }
 
+    /// Compares the components of this installed channel with those of `upstream`, a newer
+    /// version of it, e.g. its upstream counterpart (see [Channel::find_upstream_counterpart]).
+    ///
+    /// Components are matched by name, and compared with [Component::is_up_to_date]. Added,
+    /// changed and up to date components are listed in the order of `upstream`, removed ones in
+    /// the order of this channel.
+    pub fn components_to_update(&self, upstream: &Channel) -> ChannelDiff {
+        let mut diff = ChannelDiff::default();
+        for upstream_component in upstream.components.iter() {
+            let Some(installed) = self.get_component(&upstream_component.name) else {
+                diff.added.push(upstream_component.clone());
+                continue;
+            };
+            if installed.is_up_to_date(upstream_component) {
+                diff.up_to_date.push(upstream_component.clone());
+                continue;
+            }
+
+            let change = ComponentChange {
+                installed: installed.clone(),
+                upstream: upstream_component.clone(),
+            };
+            if installed.differs_only_in_revision(upstream_component) {
+                diff.revision_changed.push(change);
+            } else {
+                diff.version_changed.push(change);
+            }
+        }
+        diff.removed = self
+            .components
+            .iter()
+            .filter(|component| upstream.get_component(&component.name).is_none())
+            .cloned()
+            .collect();
+
+        diff
+    }
+
     // Syncs the channel to the latest changes
     fn sync(&mut self, config: &Config) {
         for comp in self.components.iter_mut() {
@@ -375,8 +829,9 @@ impl Display for Channel {
 
 /// A special alias/tag that a channel can posses. For more information see [`Channel::alias`].
 /// These are only used for locally installed [`Channel`]s.
-#[derive(Serialize, Debug, PartialEq, Eq, Clone, Hash)]
-#[serde(rename_all = "snake_case")]
+///
+/// Aliases are (de)serialized as strings, such as `stable`, `nightly` or `nightly-$SUFFIX`.
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
 pub enum ChannelAlias {
     /// Represents `stable`. Only one [Channel] can be marked as `stable` at a time.
     Stable,
@@ -384,10 +839,29 @@ pub enum ChannelAlias {
     Nightly(Option<Cow<'static, str>>),
     /// An ad-hoc named alias for a channel. This can be used to tag custom channels with names such
     /// as `0.15.0-stable`.
-    #[serde(untagged)]
     Tag(Cow<'static, str>),
 }
 
+impl fmt::Display for ChannelAlias {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Stable => f.write_str("stable"),
+            Self::Nightly(None) => f.write_str("nightly"),
+            Self::Nightly(Some(suffix)) => write!(f, "nightly-{suffix}"),
+            Self::Tag(tag) => f.write_str(tag),
+        }
+    }
+}
+
+impl Serialize for ChannelAlias {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
 impl<'de> serde::de::Deserialize<'de> for ChannelAlias {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -485,7 +959,7 @@ impl Display for InstalledFile {
 /// Represents each possible "word" variant that is passed to the command line.
 ///
 /// These are used to resolve an [Alias] to its associated command.
-#[derive(Serialize, Deserialize, Debug, Clone, Hash)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
 #[serde(rename_all = "snake_case")]
 pub enum CliCommand {
     /// Resolve the command to a [Component]'s corresponding executable.
@@ -495,8 +969,8 @@ pub enum CliCommand {
     LibPath,
     /// Resolve the command to a toolchain var directory (`<toolchain>/var`).
     ///
-    /// Optionally, it can contain a file name, which represents a file in `<toolchain>/var/<file>`.
-    // NOTE: Potentially in the future, we might want this to be an Optional field
+    /// Optionally, it can be followed by a [CliCommand::Verbatim] file name, in which case it
+    /// resolves to `<toolchain>/var/<file>` instead.
     #[serde(rename = "var_path")]
     VarPath,
     /// An argument that is passed verbatim, as is.
@@ -520,13 +994,30 @@ pub fn resolve_command(
     channel: &Channel,
     component: &Component,
     config: &Config,
+) -> anyhow::Result<Vec<OsString>> {
+    resolve_command_in(commands, channel, Some(component), &channel.get_channel_dir(config))
+}
+
+/// Like [resolve_command], but with `channel` installed in `channel_dir`. Without a `component`,
+/// [CliCommand::Executable] can't be resolved.
+fn resolve_command_in(
+    commands: &[CliCommand],
+    channel: &Channel,
+    component: Option<&Component>,
+    channel_dir: &Path,
 ) -> anyhow::Result<Vec<OsString>> {
     let mut resolution = Vec::with_capacity(commands.len());
-    let mut commands = commands.iter();
+    let mut commands = commands.iter().peekable();
 
     while let Some(command) = commands.next() {
         match command {
             CliCommand::Executable => {
+                let Some(component) = component else {
+                    bail!(
+                        "'executable' doesn't refer to any component of toolchain {}",
+                        channel.name
+                    );
+                };
                 let name = &component.name;
                 let component = channel.get_component(name).with_context(|| {
                     format!(
@@ -535,29 +1026,26 @@ pub fn resolve_command(
                     )
                 })?;
 
-                resolution.push(OsString::from(component.get_cli_display()));
+                // Executables are run straight from the channel's `bin` directory, which doesn't
+                // change when a different toolchain is activated elsewhere. See
+                // [Config::execute_command].
+                let executable = component.get_installed_file().get_path_from(channel_dir);
+                resolution.push(executable.into_os_string());
             },
             CliCommand::LibPath => {
-                let channel_dir = channel.get_channel_dir(config);
-
                 let toolchain_path = channel_dir.join("lib");
 
                 resolution.push(toolchain_path.into_os_string())
             },
-            // The VarPath must be followed by a file name.
+            // If the VarPath is followed by a file name, it resolves to that file.
             CliCommand::VarPath => {
-                let channel_dir = channel.get_channel_dir(config);
-
                 let toolchain_path = channel_dir.join("var");
 
-                let next_command =
-                    commands.next().context("var_path needs to be followed by a path name")?;
-
-                let CliCommand::Verbatim(directory_name) = next_command else {
-                    bail!(format!("After var_path a file is required. Got: {}", next_command))
-                };
-
-                let full_path = toolchain_path.join(directory_name);
+                let full_path =
+                    match commands.next_if(|next| matches!(next, CliCommand::Verbatim(_))) {
+                        Some(CliCommand::Verbatim(file_name)) => toolchain_path.join(file_name),
+                        _ => toolchain_path,
+                    };
 
                 resolution.push(full_path.into_os_string())
             },
@@ -573,7 +1061,7 @@ pub type Alias = String;
 pub type CliCommands = Vec<CliCommand>;
 
 /// An installable component of a toolchain
-#[derive(Serialize, Deserialize, Debug, Clone, Hash)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Component {
     /// The canonical name of this toolchain component.
     pub name: Cow<'static, str>,
@@ -588,6 +1076,19 @@ pub struct Component {
     #[serde(default)]
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub features: Vec<String>,
+    /// Whether the default features of the component's crate are enabled. Can be disabled by the
+    /// manifest, or via `midenup install --no-default-features`.
+    #[serde(default = "default_features")]
+    #[serde(skip_serializing_if = "is_true")]
+    pub default_features: bool,
+    /// The binary target of the component's crate to install, i.e. `cargo install --bin <bin>`.
+    /// If not set, every binary of the crate is installed.
+    ///
+    /// Unless the manifest sets `installed_executable`, the component's executable is named after
+    /// this binary.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bin: Option<String>,
     /// Other components that are required if this component is installed.
     #[serde(default)]
     #[serde(skip_serializing_if = "Vec::is_empty")]
@@ -648,6 +1149,21 @@ pub struct Component {
     #[serde(default)]
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub initialization: Vec<String>,
+    /// Environment variables set when the component's executable is run, either directly or
+    /// through an alias.
+    ///
+    /// Each value is resolved like an alias (see [resolve_command]), and the resolved words are
+    /// concatenated. For example, the following sets `MIDEN_CLIENT_CONFIG` to
+    /// `<toolchain>/var/miden-client.toml`:
+    ///
+    /// ```json
+    /// "env": {
+    ///   "MIDEN_CLIENT_CONFIG": ["var_path", "miden-client.toml"]
+    /// }
+    /// ```
+    #[serde(default)]
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, CliCommands>,
     /// Pre-built artifact.
     #[serde(flatten)]
     pub artifacts: Option<Artifacts>,
@@ -657,6 +1173,14 @@ const fn is_false(value: &bool) -> bool {
     !*value
 }
 
+const fn is_true(value: &bool) -> bool {
+    *value
+}
+
+const fn default_features() -> bool {
+    true
+}
+
 impl Component {
     pub fn new(name: impl Into<Cow<'static, str>>, version: Authority) -> Self {
         Self {
@@ -664,6 +1188,8 @@ impl Component {
             version,
             optional: false,
             features: vec![],
+            default_features: true,
+            bin: None,
             requires: vec![],
             call_format: vec![],
             rustup_channel: None,
@@ -671,6 +1197,7 @@ impl Component {
             aliases: BTreeMap::new(),
             symlink_name: None,
             initialization: Vec::new(),
+            env: BTreeMap::new(),
             artifacts: None,
         }
     }
@@ -678,9 +1205,9 @@ impl Component {
     /// This method is used to check if the current [Component] is up to date with its  upstream
     /// equivalent.
     ///
-    /// This is used to check if they different in fields _besides_ the name. The [`Component::eq`]
-    /// implementation only tests name equality and is only used to check for components that got
-    /// added/removed.
+    /// This is used to check if they different in fields _besides_ the name. Unlike
+    /// [`Component::eq`], which compares every field, this only considers the fields that affect
+    /// what gets installed.
     ///
     /// WARNING: The idea behind this function is to early return when a
     /// difference is found, and fallback to "UpToDate" if none are
@@ -787,7 +1314,8 @@ impl Component {
             },
         };
 
-        if self.features != upstream.features {
+        if self.features != upstream.features || self.default_features != upstream.default_features
+        {
             return false;
         }
 
@@ -806,16 +1334,41 @@ impl Component {
         true
     }
 
+    /// Whether this component and `upstream` are installed from the same git branch, and would be
+    /// up to date with each other if it weren't for the branch's latest revision.
+    fn differs_only_in_revision(&self, upstream: &Self) -> bool {
+        let Authority::Git {
+            target:
+                GitTarget::Branch {
+                    latest_revision: Some(upstream_revision), ..
+                },
+            ..
+        } = &upstream.version
+        else {
+            return false;
+        };
+        let mut at_upstream_revision = self.clone();
+        match &mut at_upstream_revision.version {
+            Authority::Git {
+                target: GitTarget::Branch { latest_revision, .. },
+                ..
+            } => *latest_revision = Some(upstream_revision.clone()),
+            _ => return false,
+        }
+
+        at_upstream_revision.is_up_to_date(upstream)
+    }
+
     /// Returns the name of the executable corresponding to this component.
     ///
     /// If the component does not specify the installed file name, that means that it installs and
-    /// executable named exactly like the crate.
+    /// executable named exactly like the crate, or like its [Component::bin] if set.
     pub fn get_installed_file(&self) -> InstalledFile {
         if let Some(installed_file) = &self.installed_file {
             installed_file.clone()
         } else {
             InstalledFile::Executable {
-                binary_name: self.name.to_string(),
+                binary_name: self.bin.clone().unwrap_or_else(|| self.name.to_string()),
                 // If not specified, all executable components are *not* alias_only
                 alias_only: false,
             }
@@ -831,6 +1384,16 @@ impl Component {
         format!("miden {}", self.name)
     }
 
+    /// Returns the name of the package this component is installed from with `cargo install`.
+    pub fn get_cargo_package(&self) -> &str {
+        match &self.version {
+            Authority::Cargo { package, .. } | Authority::CargoRequirement { package, .. } => {
+                package.as_deref().unwrap_or(self.name.as_ref())
+            },
+            Authority::Git { crate_name, .. } | Authority::Path { crate_name, .. } => crate_name,
+        }
+    }
+
     /// Returns the name of symlink associated with a component.
     pub fn get_symlink_name(&self) -> String {
         if let Some(symlink_name) = &self.symlink_name {
@@ -840,6 +1403,27 @@ impl Component {
         }
     }
 
+    /// Resolves the environment variables this component's executable is run with, see
+    /// [Component::env].
+    pub fn resolve_env(
+        &self,
+        channel: &Channel,
+        config: &Config,
+    ) -> anyhow::Result<Vec<(String, OsString)>> {
+        self.env
+            .iter()
+            .map(|(var, commands)| {
+                let value = resolve_command(commands, channel, self, config)
+                    .with_context(|| {
+                        format!("invalid value for '{var}' in the environment of '{}'", self.name)
+                    })?
+                    .into_iter()
+                    .collect::<OsString>();
+                Ok((var.clone(), value))
+            })
+            .collect()
+    }
+
     /// Returns the string representation under which midenup calls a component.
     pub fn get_call_format(&self) -> Vec<CliCommand> {
         if self.call_format.is_empty() {
@@ -854,6 +1438,11 @@ impl Component {
         self.artifacts.as_ref().and_then(|artifacts| artifacts.get_uri_for(target))
     }
 
+    /// Returns the expected SHA-256 digest of the artifact for a given `target` (if available).
+    pub fn get_artifact_sha256(&self, target: &TargetTriple) -> Option<String> {
+        self.artifacts.as_ref().and_then(|artifacts| artifacts.get_sha256_for(target))
+    }
+
     // Sync to the latest changes.
     pub fn sync(&mut self, config: &Config) {
         match &mut self.version {
@@ -872,7 +1461,9 @@ impl Component {
                     Cow::Owned(config.working_directory.join(&*path))
                 };
                 let latest_registered_modification =
-                    utils::fs::latest_modification(&path).ok().map(|modification| modification.0);
+                    utils::fs::latest_modification(&path, utils::fs::IGNORED_DIRECTORIES)
+                        .ok()
+                        .map(|modification| modification.0);
                 *last_modification = latest_registered_modification;
             },
             // NOTE: Components that are installed via git BRANCHES are a special case because we
@@ -899,7 +1490,7 @@ impl Component {
                     GitTarget::Tag { name: _name } => {},
                 }
             },
-            Authority::Cargo { package: _package, version: _version } => {},
+            Authority::Cargo { .. } | Authority::CargoRequirement { .. } => {},
         }
     }
 }
@@ -960,9 +1551,624 @@ impl core::str::FromStr for UserChannel {
         match s {
             "stable" => Ok(Self::Stable),
             "nightly" => Ok(Self::Nightly),
-            version => semver::Version::parse(version)
-                .map(Self::Version)
-                .map_err(|err| anyhow!("invalid channel version: {err}")),
+            // Named nightlies, e.g. `nightly-2024-06-01`, are resolved through their alias. See
+            // [crate::manifest::Manifest::get_named_nightly].
+            named_nightly if named_nightly.starts_with("nightly-") => {
+                Ok(Self::Other(Cow::Owned(named_nightly.to_string())))
+            },
+            // Anything that starts like a version must be a valid one, so that typos (e.g. `0.16`)
+            // aren't mistaken for the name of an unknown channel.
+            version if version.starts_with(|c: char| c.is_ascii_digit()) => {
+                semver::Version::parse(version)
+                    .map(Self::Version)
+                    .map_err(|err| anyhow!("'{version}' is not a valid channel version: {err}"))
+            },
+            // Custom channels are referred to by their tag, e.g. `custom-dev-build`.
+            tag if !tag.is_empty()
+                && tag
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')) =>
+            {
+                Ok(Self::Other(Cow::Owned(tag.to_string())))
+            },
+            invalid => Err(anyhow!(
+                "invalid channel '{invalid}': expected 'stable', 'nightly', a version or the tag \
+                 of a channel"
+            )),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{borrow::Cow, path::PathBuf};
+
+    use super::{
+        Channel, ChannelAlias, CliCommand, Component, ComponentChange, UserChannel, resolve_command,
+    };
+    use crate::{
+        config::test_config,
+        version::{Authority, GitTarget},
+    };
+
+    /// Two channels sharing a name, but whose components differ in their [Authority], must not be
+    /// considered equal.
+    #[test]
+    fn channel_eq_compares_components() {
+        let name = semver::Version::new(0, 15, 0);
+        let from_cargo = Component::new(
+            "vm",
+            Authority::Cargo {
+                package: Some(String::from("miden-vm")),
+                version: semver::Version::new(0, 15, 0),
+            },
+        );
+        let from_git = Component::new(
+            "vm",
+            Authority::Git {
+                repository_url: String::from("https://github.com/0xMiden/miden-vm.git"),
+                crate_name: String::from("miden-vm"),
+                target: GitTarget::Tag { name: String::from("v0.15.0") },
+            },
+        );
+        let std = Component::new(
+            "std",
+            Authority::Cargo {
+                package: Some(String::from("miden-stdlib")),
+                version: semver::Version::new(0, 15, 0),
+            },
+        );
+
+        let a = Channel::new(name.clone(), None, vec![from_cargo.clone(), std.clone()], vec![]);
+        let b = Channel::new(name.clone(), None, vec![from_git, std.clone()], vec![]);
+        assert_ne!(a, b);
+
+        let c = Channel::new(name, None, vec![from_cargo, std], vec![]);
+        assert_eq!(a, c);
+    }
+
+    /// Selecting components pulls in the components they require, exactly once, and ignores
+    /// components which are not part of the channel.
+    #[test]
+    fn select_components_includes_requirements() {
+        let cargo = |name: &'static str, package: &str| {
+            Component::new(
+                name,
+                Authority::Cargo {
+                    package: Some(String::from(package)),
+                    version: semver::Version::new(0, 15, 0),
+                },
+            )
+        };
+        let mut midenc = cargo("midenc", "midenc");
+        midenc.requires = vec![String::from("std"), String::from("base")];
+        let channel = Channel::new(
+            semver::Version::new(0, 15, 0),
+            None,
+            vec![
+                cargo("vm", "miden-vm"),
+                cargo("std", "miden-stdlib"),
+                cargo("base", "miden-lib"),
+                midenc,
+                cargo("client", "miden-client-cli"),
+            ],
+            vec![],
+        );
+
+        let subset = channel.select_components(["midenc", "std", "missing"], None);
+        assert!(subset.is_partially_installed());
+        let names = subset.components.iter().map(|c| c.name.as_ref()).collect::<Vec<_>>();
+        assert_eq!(names, ["midenc", "std", "base"]);
+    }
+
+    /// Pins override the version of crates.io components, and drop their pre-built artifacts.
+    /// Components from other sources are left untouched.
+    #[test]
+    fn apply_pins_overrides_cargo_versions() {
+        let vm: Component = serde_json::from_str(
+            r#"{
+                "name": "vm",
+                "package": "miden-vm",
+                "version": "0.15.0",
+                "artifacts": ["file:///nonexistent/miden-x86_64-unknown-linux-gnu"]
+            }"#,
+        )
+        .unwrap();
+        let std = Component::new(
+            "std",
+            Authority::Cargo {
+                package: Some(String::from("miden-stdlib")),
+                version: semver::Version::new(0, 15, 0),
+            },
+        );
+        let midenc = Component::new(
+            "midenc",
+            Authority::Git {
+                repository_url: String::from("https://github.com/0xMiden/compiler.git"),
+                crate_name: String::from("midenc"),
+                target: GitTarget::default(),
+            },
+        );
+        let mut channel = Channel::new(
+            semver::Version::new(0, 15, 0),
+            None,
+            vec![vm, std.clone(), midenc.clone()],
+            vec![],
+        );
+
+        let pins = [
+            (String::from("vm"), semver::Version::new(0, 15, 2)),
+            (String::from("midenc"), semver::Version::new(0, 1, 0)),
+        ]
+        .into_iter()
+        .collect();
+        channel.apply_pins(&pins);
+
+        let vm = channel.get_component("vm").unwrap();
+        assert!(matches!(
+            &vm.version,
+            Authority::Cargo { version, .. } if *version == semver::Version::new(0, 15, 2)
+        ));
+        assert!(vm.artifacts.is_none());
+        assert_eq!(channel.get_component("std"), Some(&std));
+        assert_eq!(channel.get_component("midenc"), Some(&midenc));
+    }
+
+    /// Version requirements resolve to the latest matching published version, and the resolved
+    /// components lose their pre-built artifacts.
+    #[test]
+    fn resolve_versions_picks_latest_matching_version() {
+        let vm: Component = serde_json::from_str(
+            r#"{
+                "name": "vm",
+                "package": "miden-vm",
+                "version": "^0.15",
+                "artifacts": ["file:///nonexistent/miden-x86_64-unknown-linux-gnu"]
+            }"#,
+        )
+        .unwrap();
+        let std = Component::new(
+            "std",
+            Authority::Cargo {
+                package: Some(String::from("miden-stdlib")),
+                version: semver::Version::new(0, 15, 0),
+            },
+        );
+        let mut channel =
+            Channel::new(semver::Version::new(0, 15, 0), None, vec![vm, std.clone()], vec![]);
+
+        channel
+            .resolve_versions_with(|package| {
+                assert_eq!(package, "miden-vm");
+                Ok(["0.14.9", "0.15.0", "0.15.4", "0.16.0", "0.15.5-rc.1"]
+                    .into_iter()
+                    .map(|version| semver::Version::parse(version).unwrap())
+                    .collect())
+            })
+            .unwrap();
+
+        let vm = channel.get_component("vm").unwrap();
+        assert_eq!(
+            vm.version,
+            Authority::Cargo {
+                package: Some(String::from("miden-vm")),
+                version: semver::Version::new(0, 15, 4),
+            }
+        );
+        assert!(vm.artifacts.is_none());
+        assert_eq!(channel.get_component("std"), Some(&std));
+    }
+
+    /// A requirement that no published version matches can't be installed.
+    #[test]
+    fn resolve_versions_fails_without_matching_version() {
+        let midenc = Component::new(
+            "midenc",
+            Authority::CargoRequirement {
+                package: None,
+                requirement: semver::VersionReq::parse("^0.2").unwrap(),
+            },
+        );
+        let mut channel = Channel::new(semver::Version::new(0, 15, 0), None, vec![midenc], vec![]);
+
+        let err = channel
+            .resolve_versions_with(|_| Ok(vec![semver::Version::new(0, 1, 0)]))
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "no published version of 'midenc' matches the requirement '^0.2' of component 'midenc'"
+        );
+    }
+
+    /// Pinning a component given by a requirement installs the pinned version, without looking it
+    /// up.
+    #[test]
+    fn apply_pins_overrides_cargo_requirements() {
+        let midenc = Component::new(
+            "midenc",
+            Authority::CargoRequirement {
+                package: None,
+                requirement: semver::VersionReq::parse("^0.1").unwrap(),
+            },
+        );
+        let mut channel = Channel::new(semver::Version::new(0, 15, 0), None, vec![midenc], vec![]);
+
+        let pins = [(String::from("midenc"), semver::Version::new(0, 1, 3))].into_iter().collect();
+        channel.apply_pins(&pins);
+        channel
+            .resolve_versions_with(|package| panic!("unexpected lookup of {package}"))
+            .unwrap();
+
+        assert_eq!(
+            channel.get_component("midenc").unwrap().version,
+            Authority::Cargo {
+                package: None,
+                version: semver::Version::new(0, 1, 3),
+            }
+        );
+    }
+
+    /// Requirements are followed transitively, and cyclic requirements select every component of
+    /// the cycle exactly once.
+    #[test]
+    fn select_components_follows_requirements_transitively() {
+        let component = |name: &'static str, requires: &[&str]| {
+            let mut component = Component::new(
+                name,
+                Authority::Cargo {
+                    package: None,
+                    version: semver::Version::new(0, 1, 0),
+                },
+            );
+            component.requires = requires.iter().map(|name| name.to_string()).collect();
+            component
+        };
+        let channel = Channel::new(
+            semver::Version::new(0, 15, 0),
+            None,
+            vec![
+                component("a", &["b"]),
+                component("b", &["c"]),
+                component("c", &[]),
+                component("x", &["y"]),
+                component("y", &["x"]),
+                component("unrelated", &[]),
+            ],
+            vec![],
+        );
+
+        let subset = channel.select_components(["a"], None);
+        let names = subset.components.iter().map(|c| c.name.as_ref()).collect::<Vec<_>>();
+        assert_eq!(names, ["a", "b", "c"]);
+
+        let subset = channel.select_components(["x", "y"], None);
+        let names = subset.components.iter().map(|c| c.name.as_ref()).collect::<Vec<_>>();
+        assert_eq!(names, ["x", "y"]);
+    }
+
+    /// `--override COMPONENT=path:DIR` makes the component be installed from the local crate,
+    /// keeping the name of the crate it was installed from.
+    #[test]
+    fn override_with_path_installs_from_local_crate() {
+        let path = PathBuf::from("/home/me/miden-vm");
+
+        let vm: Component = serde_json::from_str(
+            r#"{
+                "name": "vm",
+                "package": "miden-vm",
+                "version": "0.15.0",
+                "artifacts": ["file:///nonexistent/miden-x86_64-unknown-linux-gnu"]
+            }"#,
+        )
+        .unwrap();
+        let mut channel = Channel::new(semver::Version::new(0, 15, 0), None, vec![vm], vec![]);
+
+        channel.override_with_path("vm", path.clone()).unwrap();
+        let vm = channel.get_component("vm").unwrap();
+        assert_eq!(
+            vm.version,
+            Authority::Path {
+                path,
+                crate_name: String::from("miden-vm"),
+                last_modification: None,
+            }
+        );
+        assert!(vm.artifacts.is_none());
+
+        assert!(channel.override_with_path("missing", PathBuf::from("/tmp")).is_err());
+    }
+
+    /// `--features COMPONENT/FEATURE` adds to the features enabled by the channel, and
+    /// `--no-default-features` is recorded along with them.
+    #[test]
+    fn cli_features_are_added_to_the_channels() {
+        let vm: Component = serde_json::from_str(
+            r#"{
+                "name": "vm",
+                "package": "miden-vm",
+                "version": "0.15.0",
+                "features": ["executable"],
+                "artifacts": ["file:///nonexistent/miden-x86_64-unknown-linux-gnu"]
+            }"#,
+        )
+        .unwrap();
+        assert!(vm.default_features);
+        let upstream = vm.clone();
+        let mut channel = Channel::new(semver::Version::new(0, 15, 0), None, vec![vm], vec![]);
+
+        channel.enable_feature("vm", "concurrent").unwrap();
+        channel.enable_feature("vm", "executable").unwrap();
+        channel.disable_default_features("vm").unwrap();
+        let vm = channel.get_component("vm").unwrap();
+        assert_eq!(vm.features, ["executable", "concurrent"]);
+        assert!(!vm.default_features);
+        assert!(vm.artifacts.is_none());
+        assert!(!vm.is_up_to_date(&upstream));
+
+        let serialized = serde_json::to_string(vm).unwrap();
+        assert!(serialized.contains(r#""default_features":false"#), "{serialized}");
+        assert_eq!(&serde_json::from_str::<Component>(&serialized).unwrap(), vm);
+
+        assert!(channel.enable_feature("missing", "concurrent").is_err());
+        assert!(channel.disable_default_features("missing").is_err());
+    }
+
+    /// Aliases are written to the local manifest as the strings they are parsed from, so that
+    /// they can be read back.
+    #[test]
+    fn channel_alias_roundtrips() {
+        for alias in ["stable", "nightly", "nightly-2024-06-01", "0.15.0-stable"] {
+            let parsed: ChannelAlias = alias.parse().unwrap();
+            let serialized = serde_json::to_string(&parsed).unwrap();
+            assert_eq!(serialized, format!("\"{alias}\""));
+            assert_eq!(serde_json::from_str::<ChannelAlias>(&serialized).unwrap(), parsed);
+        }
+    }
+
+    /// `nightly-<suffix>` must keep its suffix, so that it resolves to the named nightly rather
+    /// than to the latest one.
+    #[test]
+    fn user_channel_parses_named_nightly() {
+        assert!(matches!("nightly".parse::<UserChannel>().unwrap(), UserChannel::Nightly));
+        assert!(matches!(
+            "nightly-2024-06-01".parse::<UserChannel>().unwrap(),
+            UserChannel::Other(Cow::Owned(name)) if name == "nightly-2024-06-01"
+        ));
+    }
+
+    /// Channels can be referred to by their tag, but strings that look like versions must be
+    /// valid versions.
+    #[test]
+    fn user_channel_parses_tags() {
+        assert!(matches!(
+            "custom-dev-build".parse::<UserChannel>().unwrap(),
+            UserChannel::Other(Cow::Owned(name)) if name == "custom-dev-build"
+        ));
+        assert!(matches!(
+            "0.16.0".parse::<UserChannel>().unwrap(),
+            UserChannel::Version(version) if version == semver::Version::new(0, 16, 0)
+        ));
+
+        let err = "1.2.x".parse::<UserChannel>().unwrap_err();
+        assert!(err.to_string().starts_with("'1.2.x' is not a valid channel version"), "{err}");
+        assert!("".parse::<UserChannel>().is_err());
+        assert!("custom build".parse::<UserChannel>().is_err());
+    }
+
+    /// Executables are resolved to the channel's own `bin` directory rather than to `opt/`, so
+    /// that they keep working regardless of which toolchain `opt/` points to.
+    #[test]
+    fn executables_resolve_to_channel_bin_dir() {
+        let home = std::env::temp_dir();
+        let config = test_config(home.clone());
+        let vm = Component::new(
+            "vm",
+            Authority::Cargo {
+                package: Some(String::from("miden-vm")),
+                version: semver::Version::new(0, 15, 0),
+            },
+        );
+        let channel = Channel::new(semver::Version::new(0, 15, 0), None, vec![vm.clone()], vec![]);
+
+        let resolution = resolve_command(&[CliCommand::Executable], &channel, &vm, &config)
+            .expect("failed to resolve executable");
+        assert_eq!(resolution, vec![home.join("toolchains").join("0.15.0").join("bin").join("vm")]);
+    }
+
+    /// The words of each environment variable's value are resolved and concatenated.
+    #[test]
+    fn component_env_is_resolved() {
+        let home = std::env::temp_dir();
+        let config = test_config(home.clone());
+        let client: Component = serde_json::from_str(
+            r#"{
+                "name": "client",
+                "package": "miden-client-cli",
+                "version": "0.10.0",
+                "env": {
+                    "MIDEN_CLIENT_CONFIG": ["var_path", "miden-client.toml"],
+                    "MIDEN_LIBS": ["lib_path", "/std.masp"],
+                    "MIDEN_DEBUG": ["1"]
+                }
+            }"#,
+        )
+        .expect("invalid component");
+        let channel =
+            Channel::new(semver::Version::new(0, 10, 0), None, vec![client.clone()], vec![]);
+
+        let toolchain_dir = home.join("toolchains").join("0.10.0");
+        let env = client.resolve_env(&channel, &config).expect("failed to resolve environment");
+        assert_eq!(
+            env,
+            vec![
+                (
+                    String::from("MIDEN_CLIENT_CONFIG"),
+                    toolchain_dir.join("var/miden-client.toml").into()
+                ),
+                (String::from("MIDEN_DEBUG"), "1".into()),
+                (String::from("MIDEN_LIBS"), toolchain_dir.join("lib/std.masp").into()),
+            ]
+        );
+    }
+
+    /// `var_path` resolves to the `var` directory itself, unless it is followed by a file name.
+    #[test]
+    fn var_path_file_name_is_optional() {
+        let home = std::env::temp_dir();
+        let config = test_config(home.clone());
+        let node = Component::new(
+            "node",
+            Authority::Cargo {
+                package: Some(String::from("miden-node")),
+                version: semver::Version::new(0, 10, 0),
+            },
+        );
+        let channel =
+            Channel::new(semver::Version::new(0, 10, 0), None, vec![node.clone()], vec![]);
+        let var_dir = home.join("toolchains").join("0.10.0").join("var");
+
+        let bare = resolve_command(&[CliCommand::VarPath], &channel, &node, &config).unwrap();
+        assert_eq!(bare, vec![var_dir.clone()]);
+
+        let with_file = resolve_command(
+            &[CliCommand::VarPath, CliCommand::Verbatim(String::from("data"))],
+            &channel,
+            &node,
+            &config,
+        )
+        .unwrap();
+        assert_eq!(with_file, vec![var_dir.join("data")]);
+
+        let followed_by_lib =
+            resolve_command(&[CliCommand::VarPath, CliCommand::LibPath], &channel, &node, &config)
+                .unwrap();
+        assert_eq!(followed_by_lib, vec![var_dir, home.join("toolchains/0.10.0/lib")]);
+    }
+
+    /// Post-install hooks starting with a component's name run its executable, and are dropped
+    /// from partial channels that leave the component out.
+    #[test]
+    fn resolve_post_install_hooks() {
+        let mut channel = Channel::new(
+            semver::Version::new(0, 15, 0),
+            None,
+            vec![Component::new(
+                "client",
+                Authority::Cargo {
+                    package: Some(String::from("miden-client-cli")),
+                    version: semver::Version::new(0, 9, 0),
+                },
+            )],
+            vec![],
+        );
+        channel.post_install = vec![
+            vec![
+                CliCommand::Verbatim(String::from("client")),
+                CliCommand::Verbatim(String::from("init")),
+            ],
+            vec![CliCommand::Verbatim(String::from("touch")), CliCommand::VarPath],
+        ];
+
+        let channel_dir = PathBuf::from("/toolchain");
+        let hooks = channel.resolve_post_install_hooks(&channel_dir).unwrap();
+        assert_eq!(
+            hooks,
+            vec![
+                vec![channel_dir.join("bin").join("client").into_os_string(), "init".into()],
+                vec!["touch".into(), channel_dir.join("var").into_os_string()],
+            ]
+        );
+
+        let partial_channel = channel.select_components(std::iter::empty(), None);
+        assert_eq!(partial_channel.post_install, channel.post_install[1..]);
+
+        channel.post_install.push(vec![CliCommand::Executable]);
+        assert_eq!(channel.dangling_references().len(), 1);
+    }
+
+    /// Every kind of change between an installed channel and a newer version of it is reported
+    /// separately.
+    #[test]
+    fn components_to_update_reports_each_change_kind() {
+        let cargo = |name: &'static str, minor| {
+            Component::new(
+                name,
+                Authority::Cargo {
+                    package: None,
+                    version: semver::Version::new(0, minor, 0),
+                },
+            )
+        };
+        let git = |name: &'static str, revision: &str| {
+            Component::new(
+                name,
+                Authority::Git {
+                    repository_url: String::from("https://github.com/0xMiden/compiler.git"),
+                    crate_name: String::from("midenc"),
+                    target: GitTarget::Branch {
+                        name: String::from("next"),
+                        latest_revision: Some(String::from(revision)),
+                    },
+                },
+            )
+        };
+        let name = semver::Version::new(0, 15, 0);
+        let installed = Channel::new(
+            name.clone(),
+            None,
+            vec![cargo("vm", 15), cargo("std", 15), git("midenc", "abc1234"), cargo("old", 1)],
+            vec![],
+        );
+
+        let upstream = Channel::new(
+            name.clone(),
+            None,
+            vec![cargo("vm", 16), cargo("std", 15), git("midenc", "def5678"), cargo("new", 1)],
+            vec![],
+        );
+        let diff = installed.components_to_update(&upstream);
+        assert_eq!(diff.added, [cargo("new", 1)]);
+        assert_eq!(diff.removed, [cargo("old", 1)]);
+        assert_eq!(
+            diff.version_changed,
+            [ComponentChange {
+                installed: cargo("vm", 15),
+                upstream: cargo("vm", 16)
+            }]
+        );
+        assert_eq!(
+            diff.revision_changed,
+            [ComponentChange {
+                installed: git("midenc", "abc1234"),
+                upstream: git("midenc", "def5678"),
+            }]
+        );
+        assert_eq!(diff.up_to_date, [cargo("std", 15)]);
+        assert_eq!(
+            diff.to_string(),
+            "- new 0.1.0 (added)\n- vm: 0.15.0 -> 0.16.0\n- midenc: next (abc1234) -> next \
+             (def5678)\n- old (removed)"
+        );
+
+        // A new revision of the same branch, along with a feature, is more than a new revision.
+        let mut midenc_with_feature = git("midenc", "def5678");
+        midenc_with_feature.features = vec![String::from("std")];
+        let upstream = Channel::new(
+            name,
+            None,
+            vec![cargo("vm", 15), cargo("std", 15), midenc_with_feature.clone(), cargo("old", 1)],
+            vec![],
+        );
+        let diff = installed.components_to_update(&upstream);
+        assert!(diff.revision_changed.is_empty());
+        assert_eq!(
+            diff.version_changed,
+            [ComponentChange {
+                installed: git("midenc", "abc1234"),
+                upstream: midenc_with_feature
+            }]
+        );
+
+        assert!(installed.components_to_update(&installed).is_empty());
+    }
+}
@@ -0,0 +1,204 @@
+use std::{collections::HashSet, path::PathBuf};
+
+use anyhow::Context;
+use colored::Colorize;
+
+use crate::{commands, config::Config, manifest::Manifest, utils};
+
+/// Leftovers of interrupted installs, updates and uninstalls.
+#[derive(Default)]
+struct StaleEntries {
+    /// Directories in `installed_toolchains/` which no toolchain symlink points to.
+    install_dirs: Vec<PathBuf>,
+    /// Symlinks which point to something that does not exist anymore, plus the temporary
+    /// `installed_toolchains/<channel>.new` symlinks used to publish an install.
+    symlinks: Vec<PathBuf>,
+    /// Channels in the local manifest which are not installed.
+    channels: Vec<semver::Version>,
+}
+
+impl StaleEntries {
+    fn is_empty(&self) -> bool {
+        self.install_dirs.is_empty() && self.symlinks.is_empty() && self.channels.is_empty()
+    }
+}
+
+/// Removes the leftovers of interrupted installs, updates and uninstalls from `MIDENUP_HOME`.
+///
+/// Since installs are published by atomically swapping the `toolchains/<channel>` symlink (see
+/// [crate::commands::install]), an interrupted install leaves behind an unreferenced directory in
+/// `installed_toolchains/`, and possibly a `<channel>.new` symlink. Updates leave behind the
+/// directory of the previous install in the same way.
+///
+/// Unless `yes` is set, the user is asked for confirmation before anything gets removed.
+pub fn clean(config: &Config, local_manifest: &mut Manifest, yes: bool) -> anyhow::Result<()> {
+    let stale = find_stale_entries(config, local_manifest)?;
+    if stale.is_empty() {
+        println!("{}: nothing to clean", "info".white().bold());
+        return Ok(());
+    }
+
+    println!("The following entries will be removed:");
+    for install_dir in stale.install_dirs.iter() {
+        println!("  {} (unused install directory)", install_dir.display());
+    }
+    for symlink in stale.symlinks.iter() {
+        println!("  {} (stale symlink)", symlink.display());
+    }
+    for channel in stale.channels.iter() {
+        println!("  {channel} (local manifest entry of a toolchain that is not installed)");
+    }
+
+    if !yes {
+        println!("Proceed? (y/N)");
+        let mut input = String::new();
+        std::io::stdin().read_line(&mut input).context("Failed to read input")?;
+        if !input.trim().eq_ignore_ascii_case("y") {
+            println!("Cancelling, no changes will be applied.");
+            return Ok(());
+        }
+    }
+
+    for symlink in stale.symlinks.iter() {
+        std::fs::remove_file(symlink)
+            .with_context(|| format!("failed to remove symlink '{}'", symlink.display()))?;
+    }
+    for install_dir in stale.install_dirs.iter() {
+        std::fs::remove_dir_all(install_dir).with_context(|| {
+            format!("failed to remove install directory '{}'", install_dir.display())
+        })?;
+    }
+
+    if !stale.channels.is_empty() {
+        for channel in stale.channels {
+            local_manifest.remove_channel(channel);
+        }
+
+        commands::save_local_manifest(config, local_manifest)?;
+    }
+
+    println!("{}: cleaned up midenup's home directory", "info".white().bold());
+
+    Ok(())
+}
+
+fn find_stale_entries(config: &Config, local_manifest: &Manifest) -> anyhow::Result<StaleEntries> {
+    let mut stale = StaleEntries::default();
+
+    // Every install directory that is reachable from `toolchains/` is in use. This includes the
+    // `stable` and `default` symlinks, which point to other symlinks in that same directory.
+    let mut referenced = HashSet::new();
+    let toolchains_dir = config.midenup_home.join("toolchains");
+    if toolchains_dir.exists() {
+        let entries = std::fs::read_dir(&toolchains_dir).with_context(|| {
+            format!("failed to read toolchains directory '{}'", toolchains_dir.display())
+        })?;
+        for entry in entries {
+            let path = entry.context("failed to read toolchains directory entry")?.path();
+            if !path.is_symlink() {
+                continue;
+            }
+            match path.canonicalize() {
+                Ok(install_dir) => {
+                    referenced.insert(install_dir);
+                },
+                Err(_) => stale.symlinks.push(path),
+            }
+        }
+    }
+
+    let installed_toolchains_dir = config.midenup_home.join("installed_toolchains");
+    if installed_toolchains_dir.exists() {
+        let entries = std::fs::read_dir(&installed_toolchains_dir).with_context(|| {
+            format!(
+                "failed to read installed toolchains directory '{}'",
+                installed_toolchains_dir.display()
+            )
+        })?;
+        for entry in entries {
+            let path = entry.context("failed to read installed toolchains directory entry")?.path();
+            if path.is_symlink() {
+                // Temporary `<channel>.new` symlinks only exist while an install is published.
+                stale.symlinks.push(path);
+            } else if path.is_dir()
+                && !path.canonicalize().is_ok_and(|dir| referenced.contains(&dir))
+            {
+                stale.install_dirs.push(path);
+            }
+        }
+    }
+
+    // The `opt` symlink points inside the active toolchain, which might have been removed.
+    let opt_dir = config.midenup_home.join("opt");
+    if opt_dir.is_symlink() && !opt_dir.exists() {
+        stale.symlinks.push(opt_dir);
+    }
+
+    stale.channels = local_manifest
+        .get_channels()
+        .filter(|channel| !channel.get_channel_dir(config).exists())
+        .map(|channel| channel.name.clone())
+        .collect();
+
+    stale.install_dirs.sort();
+    stale.symlinks.sort();
+
+    Ok(stale)
+}
+
+/// Removes the `stable` symlink if the toolchain it points to doesn't exist anymore, e.g. because
+/// its directory was deleted by hand. It is then recreated, pointing to the latest stable
+/// toolchain of the local manifest which is still installed, if any.
+///
+/// The `default` symlink is left alone: it can point to a toolchain that is not installed yet,
+/// which `miden` installs on first use after `midenup override`.
+///
+/// This runs in every command that holds the lock on `MIDENUP_HOME` (see [Config::lock]), and in
+/// `midenup doctor`. Returns a description of every repair made.
+pub(crate) fn repair_toolchain_symlinks(
+    config: &Config,
+    local_manifest: &Manifest,
+) -> anyhow::Result<Vec<String>> {
+    let toolchains_dir = config.midenup_home.join("toolchains");
+    let mut repairs = Vec::new();
+
+    let stable_symlink = toolchains_dir.join("stable");
+    if stable_symlink.is_symlink() && !stable_symlink.exists() {
+        let stable = local_manifest
+            .get_channels()
+            .filter(|channel| channel.is_stable() && channel.get_channel_dir(config).exists())
+            .max_by(|a, b| a.name.cmp_precedence(&b.name));
+        match stable {
+            Some(stable) => {
+                // The new symlink is renamed over the dangling one, like when publishing an
+                // install, so that `stable` never goes missing.
+                let temp_symlink = toolchains_dir.join("stable.new");
+                if temp_symlink.symlink_metadata().is_ok() {
+                    std::fs::remove_file(&temp_symlink).with_context(|| {
+                        format!("failed to remove stale temp symlink '{}'", temp_symlink.display())
+                    })?;
+                }
+                let target = PathBuf::from(stable.name.to_string());
+                utils::fs::symlink(&temp_symlink, &target)
+                    .context("failed to recreate 'stable' toolchain symlink")?;
+                std::fs::rename(&temp_symlink, &stable_symlink)
+                    .context("failed to replace dangling 'stable' toolchain symlink")?;
+                repairs.push(format!(
+                    "the `stable` symlink pointed to a toolchain that does not exist, it now \
+                     points to {}",
+                    stable.name
+                ));
+            },
+            None => {
+                std::fs::remove_file(&stable_symlink)
+                    .context("failed to remove dangling 'stable' toolchain symlink")?;
+                repairs.push(String::from(
+                    "removed the `stable` symlink, since the toolchain it pointed to does not \
+                     exist",
+                ));
+            },
+        }
+    }
+
+    Ok(repairs)
+}
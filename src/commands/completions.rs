@@ -0,0 +1,45 @@
+use clap::{CommandFactory, builder::PossibleValuesParser};
+use clap_complete::Shell;
+
+use crate::{channel::ChannelAlias, commands::Midenup, manifest::Manifest};
+
+/// Subcommands whose `CHANNEL` argument refers to an installed toolchain.
+const INSTALLED_CHANNEL_SUBCOMMANDS: [&str; 4] = ["uninstall", "update", "set", "override"];
+
+/// Writes the completion script of `midenup` for the given `shell` to stdout.
+///
+/// Besides the subcommands and their flags, the channel argument of the subcommands that operate
+/// on installed toolchains is completed with the toolchains installed at the time the script is
+/// generated, as listed in the local manifest. Note that only some shells (e.g. bash and zsh)
+/// complete the values of positional arguments.
+pub fn completions(local_manifest: &Manifest, shell: Shell) -> anyhow::Result<()> {
+    // `Midenup` is a multicall command, `midenup` itself is one of its subcommands.
+    let mut command = Midenup::command()
+        .find_subcommand("midenup")
+        .cloned()
+        .expect("midenup is a subcommand of the multicall command");
+
+    let mut channels = vec![String::from("stable"), String::from("nightly")];
+    for channel in local_manifest.get_channels() {
+        channels.push(channel.name.to_string());
+        match &channel.alias {
+            Some(ChannelAlias::Nightly(Some(suffix))) => channels.push(format!("nightly-{suffix}")),
+            Some(ChannelAlias::Tag(tag)) => channels.push(tag.to_string()),
+            _ => (),
+        }
+    }
+    channels.sort();
+    channels.dedup();
+
+    for subcommand in INSTALLED_CHANNEL_SUBCOMMANDS {
+        let channels = channels.clone();
+        command = command.mut_subcommand(subcommand, |subcommand| {
+            subcommand
+                .mut_arg("channel", |arg| arg.value_parser(PossibleValuesParser::new(channels)))
+        });
+    }
+
+    clap_complete::generate(shell, &mut command, "midenup", &mut std::io::stdout());
+
+    Ok(())
+}
@@ -0,0 +1,254 @@
+use std::{
+    collections::BTreeSet,
+    path::{Path, PathBuf},
+};
+
+use anyhow::bail;
+use colored::Colorize;
+
+use crate::{
+    channel::Channel,
+    commands::{
+        clean::repair_toolchain_symlinks,
+        init::{miden_is_accessible, miden_symlink_path},
+        install::cargo_version,
+    },
+    config::Config,
+};
+
+/// Keeps track of the outcome of each check performed by [doctor].
+#[derive(Default)]
+struct Checklist {
+    failures: usize,
+}
+
+impl Checklist {
+    fn pass(&mut self, msg: impl core::fmt::Display) {
+        println!("[{}]   {msg}", "ok".green().bold());
+    }
+
+    /// Something that is not quite right, but that does not prevent midenup from working.
+    fn warn(&mut self, msg: impl core::fmt::Display) {
+        println!("[{}] {msg}", "warn".yellow().bold());
+    }
+
+    fn fail(&mut self, msg: impl core::fmt::Display) {
+        self.failures += 1;
+        println!("[{}] {msg}", "FAIL".red().bold());
+    }
+}
+
+/// Diagnoses the local midenup environment, printing a checklist with the result of every check.
+///
+/// Returns an error if any of the checks failed.
+pub fn doctor(config: &Config) -> anyhow::Result<()> {
+    let mut checklist = Checklist::default();
+
+    // The `miden` symlink, see [crate::commands::setup_midenup].
+    let miden_symlink = miden_symlink_path(&config.cargo_home);
+    match std::fs::read_link(&miden_symlink) {
+        Ok(target) => {
+            let current_exe = std::env::current_exe()
+                .and_then(|exe| exe.canonicalize())
+                .expect("unable to get location of current executable");
+            if miden_symlink.canonicalize().is_ok_and(|resolved| resolved == current_exe) {
+                checklist.pass(format!(
+                    "`miden` symlink at '{}' points to '{}'",
+                    miden_symlink.display(),
+                    target.display()
+                ));
+            } else {
+                checklist.fail(format!(
+                    "`miden` symlink at '{}' points to '{}' instead of '{}'",
+                    miden_symlink.display(),
+                    target.display(),
+                    current_exe.display()
+                ));
+                if let Some(other_home) = midenup_home_of(&miden_symlink, &config.cargo_home)
+                    && other_home != config.midenup_home
+                {
+                    checklist.warn(format!(
+                        "`miden` uses '{}' as midenup's home, instead of '{}'",
+                        other_home.display(),
+                        config.midenup_home.display()
+                    ));
+                }
+            }
+        },
+        Err(_) if miden_symlink.exists() => {
+            checklist.fail(format!("'{}' exists but is not a symlink", miden_symlink.display()));
+        },
+        Err(_) => {
+            checklist.fail(format!(
+                "`miden` symlink is missing from '{}', run `midenup init` to create it",
+                miden_symlink.display()
+            ));
+        },
+    }
+
+    // The directory holding the `miden` symlink should be in the PATH.
+    let cargo_bin = config.cargo_home.join("bin");
+    let in_path = std::env::var_os("PATH")
+        .is_some_and(|path| std::env::split_paths(&path).any(|dir| dir == cargo_bin));
+    if in_path {
+        checklist.pass(format!("'{}' is in PATH", cargo_bin.display()));
+    } else if miden_is_accessible() {
+        checklist.warn(format!(
+            "'{}' is not in PATH, but a `miden` executable was found elsewhere",
+            cargo_bin.display()
+        ));
+    } else {
+        checklist.fail(format!(
+            "'{}' is not in PATH, add `export PATH=\"{}:$PATH\"` to your shell's profile",
+            cargo_bin.display(),
+            cargo_bin.display()
+        ));
+    }
+
+    // The local manifest
+    let local_manifest = match config.local_manifest() {
+        Ok(local_manifest) => {
+            checklist.pass(format!(
+                "local manifest parses ({} installed toolchains)",
+                local_manifest.get_channels().count()
+            ));
+            Some(local_manifest)
+        },
+        Err(err) => {
+            checklist.fail(format!(
+                "local manifest is invalid: {err:#}, run `midenup repair-manifest` to rebuild it"
+            ));
+            None
+        },
+    };
+
+    // Toolchains installed before midenup rejected them can still have the problems that
+    // [crate::channel::Channel::validate] checks for.
+    if let Some(local_manifest) = &local_manifest {
+        let problems: Vec<String> =
+            local_manifest.get_channels().flat_map(Channel::problems).collect();
+        if problems.is_empty() {
+            checklist.pass("the components of the installed toolchains are consistent");
+        }
+        for problem in problems {
+            checklist.warn(problem);
+        }
+        if local_manifest
+            .get_channels()
+            .any(|channel| !channel.alias_collisions().is_empty())
+        {
+            checklist.warn("components shadowed by an alias can be called with `midenup run`");
+        }
+    }
+
+    // The `opt` symlink, see [Config::update_opt_symlinks].
+    let opt_dir = config.midenup_home.join("opt");
+    match config.active_channel() {
+        Ok(active_channel) if active_channel.get_channel_dir(config).exists() => {
+            let expected = active_channel.get_channel_dir(config).join("opt");
+            match std::fs::read_link(&opt_dir) {
+                Ok(target) if target == expected => checklist.pass(format!(
+                    "`opt` symlink points to the active toolchain ({})",
+                    active_channel.name
+                )),
+                Ok(target) => checklist.fail(format!(
+                    "`opt` symlink points to '{}' instead of the active toolchain at '{}'",
+                    target.display(),
+                    expected.display()
+                )),
+                Err(_) => checklist.fail(format!(
+                    "`opt` symlink is missing, it should point to '{}'",
+                    expected.display()
+                )),
+            }
+        },
+        Ok(active_channel) => checklist.warn(format!(
+            "the active toolchain ({}) is not installed, run `midenup install {}`",
+            active_channel.name, active_channel.name
+        )),
+        Err(err) => checklist.fail(format!("unable to determine the active toolchain: {err:#}")),
+    }
+
+    // The `stable` symlink, after repairing it if it points to a toolchain that was removed.
+    if let Some(local_manifest) = &local_manifest {
+        let repairs = config
+            .lock()
+            .and_then(|_lock| repair_toolchain_symlinks(config, local_manifest));
+        match repairs {
+            Ok(repairs) => {
+                for repair in repairs {
+                    checklist.warn(repair);
+                }
+            },
+            Err(err) => checklist.fail(format!("unable to repair toolchain symlinks: {err:#}")),
+        }
+    }
+    let stable_dir = config.midenup_home.join("toolchains").join("stable");
+    match std::fs::read_link(&stable_dir) {
+        Ok(target) if stable_dir.exists() => {
+            checklist.pass(format!("`stable` symlink resolves to '{}'", target.display()))
+        },
+        Ok(target) => checklist.fail(format!(
+            "`stable` symlink points to '{}', which does not exist",
+            target.display()
+        )),
+        Err(_) => checklist.warn("no stable toolchain is installed"),
+    }
+
+    // Cargo, plus every rust toolchain needed to install components. The install script needs
+    // nightly by default, since it is run with `cargo -Zscript`.
+    match cargo_version(None) {
+        Some(version) => checklist.pass(format!("cargo is available ({version})")),
+        None => checklist.fail("cargo is not available"),
+    }
+
+    let mut rust_toolchains = BTreeSet::from([String::from("nightly")]);
+    if let Some(local_manifest) = &local_manifest {
+        rust_toolchains.extend(
+            local_manifest
+                .get_channels()
+                .flat_map(|channel| channel.components.iter())
+                .filter_map(|component| component.rustup_channel.clone()),
+        );
+    }
+    for rust_toolchain in rust_toolchains {
+        match cargo_version(Some(&rust_toolchain)) {
+            Some(version) => checklist
+                .pass(format!("rust toolchain '{rust_toolchain}' is available ({version})")),
+            None => checklist.fail(format!(
+                "rust toolchain '{rust_toolchain}' is not available, run `rustup toolchain \
+                 install {rust_toolchain}`"
+            )),
+        }
+    }
+
+    if checklist.failures > 0 {
+        bail!("{} check(s) failed", checklist.failures);
+    }
+
+    Ok(())
+}
+
+/// Returns the home resolved by the midenup executable at `exe`, as printed by `show home`, or
+/// `None` if it could not be run.
+fn midenup_home_of(exe: &Path, cargo_home: &Path) -> Option<PathBuf> {
+    // NOTE: The same CARGO_HOME is given to the other executable, since it might have been set
+    // with `--cargo-home`.
+    let mut command = std::process::Command::new(exe);
+    // `exe` is usually the `miden` symlink, which would otherwise run as `miden`. Elsewhere, the
+    // executable's own name is used.
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::arg0(&mut command, "midenup");
+    let output = command
+        .args(["show", "home"])
+        .env("CARGO_HOME", cargo_home)
+        .stdin(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    String::from_utf8(output.stdout).ok()?.lines().next().map(PathBuf::from)
+}
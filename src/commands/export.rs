@@ -0,0 +1,186 @@
+use std::path::Path;
+
+use anyhow::{Context, bail};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    channel::Channel,
+    commands::install,
+    config::Config,
+    manifest::Manifest,
+    options::InstallationOptions,
+    profile::Profile,
+    toolchain::Toolchain,
+    version::{Authority, GitTarget},
+};
+
+/// The version of the lockfile format written by `midenup export`.
+const LOCKFILE_VERSION: u32 = 1;
+
+/// The exact state of an installed toolchain, as written by `midenup export` and read by
+/// `midenup import`.
+///
+/// Unlike the channels of the upstream manifest, the components of a locked channel are always
+/// installed from a concrete crate version, git revision or path.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Lockfile {
+    /// This version is used to handle breaking changes in the lockfile format itself
+    pub lockfile_version: u32,
+    /// The locked channel, as installed
+    pub channel: Channel,
+}
+
+impl Lockfile {
+    /// Locks `channel`, as recorded in the local manifest.
+    pub fn new(channel: &Channel) -> Self {
+        let mut channel = channel.clone();
+        // Whether a channel is the stable one depends on the upstream manifest.
+        channel.alias = None;
+        for component in channel.components.iter_mut() {
+            // Branches are locked to the revision that was installed.
+            if let Authority::Git { target, .. } = &mut component.version
+                && let GitTarget::Branch { latest_revision: Some(hash), .. } = target
+            {
+                *target = GitTarget::Revision { hash: hash.clone() };
+            }
+        }
+
+        Lockfile {
+            lockfile_version: LOCKFILE_VERSION,
+            channel,
+        }
+    }
+
+    /// Loads the lockfile at `path`.
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read lockfile '{}'", path.display()))?;
+        let lockfile: Lockfile = serde_json::from_str(&contents)
+            .with_context(|| format!("invalid lockfile '{}'", path.display()))?;
+        if lockfile.lockfile_version != LOCKFILE_VERSION {
+            bail!(
+                "unsupported lockfile version {} in '{}', expected version {LOCKFILE_VERSION}",
+                lockfile.lockfile_version,
+                path.display()
+            );
+        }
+
+        Ok(lockfile)
+    }
+}
+
+/// Prints a lockfile of the active toolchain to stdout, see [Lockfile].
+pub fn export(config: &Config, local_manifest: &Manifest) -> anyhow::Result<()> {
+    let (toolchain, _) = Toolchain::current(config)?;
+    let channel = config
+        .manifest
+        .get_channel(&toolchain.channel)
+        .and_then(|upstream| local_manifest.get_channel_by_name(&upstream.name))
+        .or_else(|| local_manifest.get_channel(&toolchain.channel))
+        .with_context(|| {
+            format!(
+                "the active toolchain '{}' is not installed. To install it, try running:\nmidenup \
+                 install {}",
+                toolchain.channel, toolchain.channel
+            )
+        })?;
+
+    let lockfile = Lockfile::new(channel);
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&lockfile).context("Couldn't serialize lockfile")?
+    );
+
+    Ok(())
+}
+
+/// Installs the toolchain locked in the lockfile at `path`, exactly as described there: pinned
+/// components are installed at the locked version too.
+///
+/// If a toolchain with the same name is installed, the components that differ from the locked
+/// ones are reinstalled.
+pub fn import(
+    config: &Config,
+    local_manifest: &mut Manifest,
+    path: &Path,
+    options: &InstallationOptions,
+) -> anyhow::Result<()> {
+    let Lockfile { channel, .. } = Lockfile::load(path)?;
+    channel.validate()?;
+
+    let components_to_uninstall = local_manifest
+        .get_channel_by_name(&channel.name)
+        .map(|installed| {
+            installed
+                .components
+                .iter()
+                .filter(|component| {
+                    channel
+                        .get_component(&component.name)
+                        .is_none_or(|locked| !component.is_up_to_date(locked))
+                })
+                .cloned()
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let options = InstallationOptions {
+        // Every component in the lockfile was installed, optional or not.
+        profile: Profile::Complete,
+        ignore_pins: true,
+        components_to_uninstall,
+        ..options.clone()
+    };
+    install(config, &channel, local_manifest, &options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Lockfile;
+    use crate::{
+        channel::{Channel, ChannelAlias, Component},
+        version::{Authority, GitTarget},
+    };
+
+    /// Lockfiles record the revision that was installed from a branch, and don't depend on which
+    /// channel is stable.
+    #[test]
+    fn lockfile_records_resolved_revisions() {
+        let vm = Component::new(
+            "vm",
+            Authority::Cargo {
+                package: Some(String::from("miden-vm")),
+                version: semver::Version::new(0, 16, 2),
+            },
+        );
+        let client = Component::new(
+            "client",
+            Authority::Git {
+                repository_url: String::from("https://github.com/0xMiden/miden-client.git"),
+                crate_name: String::from("miden-client-cli"),
+                target: GitTarget::Branch {
+                    name: String::from("next"),
+                    latest_revision: Some(String::from("83df2aa115b2617e")),
+                },
+            },
+        );
+        let channel = Channel::new(
+            semver::Version::new(0, 16, 0),
+            Some(ChannelAlias::Stable),
+            vec![vm.clone(), client],
+            vec![],
+        );
+
+        let lockfile = Lockfile::new(&channel);
+        assert_eq!(lockfile.channel.alias, None);
+        assert_eq!(lockfile.channel.get_component("vm"), Some(&vm));
+        assert!(matches!(
+            &lockfile.channel.get_component("client").unwrap().version,
+            Authority::Git { target: GitTarget::Revision { hash }, .. } if hash == "83df2aa115b2617e"
+        ));
+
+        let serialized = serde_json::to_string(&lockfile).unwrap();
+        let deserialized: Lockfile = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(deserialized.channel, lockfile.channel);
+    }
+}
@@ -0,0 +1,201 @@
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+use colored::Colorize;
+
+use crate::{
+    channel::{Channel, Component, InstalledFile},
+    commands::{install::get_installed_cargo_packages, uninstall::uninstall_executable},
+    config::Config,
+    manifest::Manifest,
+};
+
+/// Files of an installed toolchain which don't belong to any of its components.
+struct Orphans {
+    /// The toolchain's install directory.
+    install_dir: PathBuf,
+    /// Packages installed with `cargo install` which none of the components come from. These are
+    /// removed with `cargo uninstall`, along with their executables.
+    packages: Vec<String>,
+    /// Files in `bin/` and `lib/` which no component installed.
+    files: Vec<PathBuf>,
+    /// Symlinks in `opt/` which don't point to the executable of a component.
+    symlinks: Vec<PathBuf>,
+}
+
+impl Orphans {
+    fn is_empty(&self) -> bool {
+        self.packages.is_empty() && self.files.is_empty() && self.symlinks.is_empty()
+    }
+}
+
+/// Removes the files of installed toolchains which don't belong to any of their components, as
+/// recorded in the local manifest. These are usually left behind by failed installs.
+///
+/// Unlike [crate::commands::clean], which removes whole toolchains that are not in use, this
+/// looks inside the toolchains that are installed.
+///
+/// Unless `yes` is set, the user is asked for confirmation before anything gets removed.
+pub fn gc(config: &Config, local_manifest: &Manifest, yes: bool) -> anyhow::Result<()> {
+    let mut orphans = Vec::new();
+    let mut visited = HashSet::new();
+    for channel in local_manifest.get_channels() {
+        // Toolchains that are not installed are handled by `midenup clean`.
+        let Ok(install_dir) = channel.get_channel_dir(config).canonicalize() else {
+            continue;
+        };
+        if !visited.insert(install_dir.clone()) {
+            continue;
+        }
+
+        let found = find_orphans(channel, install_dir)?;
+        if !found.is_empty() {
+            orphans.push(found);
+        }
+    }
+
+    if orphans.is_empty() {
+        println!("{}: nothing to collect", "info".white().bold());
+        return Ok(());
+    }
+
+    println!("The following entries will be removed:");
+    for orphans in orphans.iter() {
+        for package in orphans.packages.iter() {
+            println!(
+                "  {package} (package installed with cargo in {})",
+                orphans.install_dir.display()
+            );
+        }
+        for file in orphans.files.iter() {
+            println!("  {} (orphaned file)", file.display());
+        }
+        for symlink in orphans.symlinks.iter() {
+            println!("  {} (orphaned symlink)", symlink.display());
+        }
+    }
+
+    if !yes {
+        println!("Proceed? (y/N)");
+        let mut input = String::new();
+        std::io::stdin().read_line(&mut input).context("Failed to read input")?;
+        if !input.trim().eq_ignore_ascii_case("y") {
+            println!("Cancelling, no changes will be applied.");
+            return Ok(());
+        }
+    }
+
+    for orphans in orphans.iter() {
+        for package in orphans.packages.iter() {
+            uninstall_executable(package, None, &orphans.install_dir)?;
+        }
+        for file in orphans.files.iter() {
+            std::fs::remove_file(file)
+                .with_context(|| format!("failed to remove file '{}'", file.display()))?;
+        }
+        for symlink in orphans.symlinks.iter() {
+            std::fs::remove_file(symlink)
+                .with_context(|| format!("failed to remove symlink '{}'", symlink.display()))?;
+        }
+    }
+
+    println!("{}: removed orphaned files from installed toolchains", "info".white().bold());
+
+    Ok(())
+}
+
+/// Finds the files in `install_dir` which don't belong to any of `channel`'s components.
+fn find_orphans(channel: &Channel, install_dir: PathBuf) -> anyhow::Result<Orphans> {
+    let mut executables = HashSet::new();
+    let mut libraries = HashSet::new();
+    for component in channel.components.iter() {
+        match component.get_installed_file() {
+            InstalledFile::Executable { binary_name, .. } => {
+                executables.insert(binary_name);
+            },
+            InstalledFile::Library { library_name, .. } => {
+                // The install script names libraries after their component.
+                libraries.insert(format!("{}.masp", component.name));
+                libraries.insert(library_name);
+            },
+        }
+    }
+    let packages = channel
+        .components
+        .iter()
+        .map(Component::get_cargo_package)
+        .collect::<HashSet<_>>();
+
+    // Packages may install executables other than the component's own, which are kept as long as
+    // the package is.
+    let mut orphaned_packages = Vec::new();
+    let mut packaged_executables = HashSet::new();
+    for (package, binaries) in get_installed_cargo_packages(install_dir.clone())? {
+        if packages.contains(package.as_str()) {
+            packaged_executables.extend(binaries);
+        } else {
+            orphaned_packages.push(package);
+        }
+    }
+
+    let mut files = orphaned_files(&install_dir.join("bin"), |name| {
+        executables.contains(name) || packaged_executables.contains(name)
+    })?;
+    files.extend(orphaned_files(&install_dir.join("lib"), |name| libraries.contains(name))?);
+
+    let mut symlinks = Vec::new();
+    let opt_dir = install_dir.join("opt");
+    if opt_dir.exists() {
+        let entries = std::fs::read_dir(&opt_dir)
+            .with_context(|| format!("failed to read directory '{}'", opt_dir.display()))?;
+        for entry in entries {
+            let path = entry
+                .with_context(|| format!("failed to read entry in '{}'", opt_dir.display()))?
+                .path();
+            let points_to_component = std::fs::read_link(&path).is_ok_and(|target| {
+                path.exists()
+                    && target
+                        .file_name()
+                        .and_then(|name| name.to_str())
+                        .is_some_and(|name| executables.contains(name))
+            });
+            if path.is_symlink() && !points_to_component {
+                symlinks.push(path);
+            }
+        }
+    }
+    symlinks.sort();
+
+    Ok(Orphans {
+        install_dir,
+        packages: orphaned_packages,
+        files,
+        symlinks,
+    })
+}
+
+/// Returns the files in `dir` whose name is not `expected`.
+fn orphaned_files(dir: &Path, expected: impl Fn(&str) -> bool) -> anyhow::Result<Vec<PathBuf>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut orphans = Vec::new();
+    let entries = std::fs::read_dir(dir)
+        .with_context(|| format!("failed to read directory '{}'", dir.display()))?;
+    for entry in entries {
+        let path = entry
+            .with_context(|| format!("failed to read entry in '{}'", dir.display()))?
+            .path();
+        let is_expected = path.file_name().and_then(|name| name.to_str()).is_some_and(&expected);
+        if path.is_file() && !is_expected {
+            orphans.push(path);
+        }
+    }
+    orphans.sort();
+
+    Ok(orphans)
+}
@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use thiserror::Error;
 
@@ -117,7 +117,7 @@ pub fn setup_midenup(
 
         let current_exe =
             std::env::current_exe().expect("unable to get location of current executable");
-        let miden_exe = cargo_bin.join("miden");
+        let miden_exe = miden_symlink_path(&config.cargo_home);
         if !miden_exe.exists() {
             utils::fs::symlink(&miden_exe, &current_exe)
                 .map_err(|e| InitializationError::Symlink(e.to_string()))?;
@@ -126,15 +126,7 @@ pub fn setup_midenup(
 
         // We check if the `miden` executable is accessible via the $PATH. This is most certainly
         // not going to be the case the first time `midenup` is initialized.
-        let miden_is_accessible = std::process::Command::new("miden")
-            .stdout(std::process::Stdio::null())
-            .stderr(std::process::Stdio::null())
-            .stdin(std::process::Stdio::null())
-            .arg("--version")
-            .output()
-            .is_ok();
-
-        if !miden_is_accessible {
+        if !miden_is_accessible() {
             if std::env::var(DEFAULT_USER_DATA_DIR).is_err() {
                 // Some OSs, like MacOs, don't define the XDG_* family of environment variables. In
                 // those cases, we mark the environment as initialized so the updated guidance
@@ -142,7 +134,9 @@ pub fn setup_midenup(
                 state = InitializationState::Initialized;
             }
 
-            println!(
+            // This is printed to stderr, so that it doesn't end up in the output of commands
+            // such as `midenup show home --create`.
+            eprintln!(
                 "
 Could not find `miden` executable in the system's PATH.
 
@@ -170,3 +164,20 @@ source ~/.zprofile
 
     Ok(state)
 }
+
+/// Path of the `miden` symlink created by [setup_midenup], which points to the `midenup`
+/// executable.
+pub(crate) fn miden_symlink_path(cargo_home: &Path) -> PathBuf {
+    cargo_home.join("bin").join("miden")
+}
+
+/// Whether a `miden` executable can be spawned from the system's PATH.
+pub(crate) fn miden_is_accessible() -> bool {
+    std::process::Command::new("miden")
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .stdin(std::process::Stdio::null())
+        .arg("--version")
+        .output()
+        .is_ok()
+}
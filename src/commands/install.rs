@@ -1,56 +1,222 @@
 use std::{
     borrow::Cow,
-    collections::HashSet,
-    io::Write,
+    collections::{BTreeMap, HashSet},
+    io::{IsTerminal, Write},
+    num::NonZeroUsize,
     path::{Path, PathBuf},
-    time::SystemTime,
+    sync::Mutex,
+    time::{Duration, Instant, SystemTime},
 };
 
-use anyhow::{Context, bail};
+use anyhow::{Context, anyhow, bail};
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
 
 use crate::{
     artifact::TargetTriple,
-    channel::{Channel, ChannelAlias, InstalledFile},
-    commands,
+    channel::{Channel, ChannelAlias, Component, InstalledFile, UserChannel},
+    commands::{
+        self,
+        plan::{Execution, Step},
+    },
     config::Config,
     manifest::Manifest,
-    options::InstallationOptions,
+    message::Message,
+    options::{InstallationOptions, MessageFormat},
     profile::Profile,
     utils,
     version::{Authority, GitTarget},
 };
 
+/// The file in a toolchain's directory that holds the output of its last install, see
+/// [install].
+pub const INSTALL_LOG_FILE: &str = "install.log";
+
+/// The directory in a toolchain's directory that holds the output of each component built from
+/// source during its last install, as `<component>.log`.
+pub const COMPONENT_LOGS_DIR: &str = "logs";
+
+/// The file in which the install script records how long each component took to install, one
+/// `<component> <seconds>` line per component. See [InstallTimings].
+const SCRIPT_TIMINGS_FILE: &str = ".install-timings";
+
+/// How long installing a toolchain took, as printed by `midenup install --timings`. This is also
+/// written to `install-timings.json` in the toolchain's directory.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct InstallTimings {
+    /// The components that got installed, in the order they finished installing. Components which
+    /// were already installed are not listed.
+    pub components: Vec<ComponentTiming>,
+    /// The duration of the whole install, in seconds, including compiling the install script.
+    pub total_seconds: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ComponentTiming {
+    pub name: String,
+    pub seconds: f64,
+}
+
+impl InstallTimings {
+    /// Reads the timings recorded by the install script in `install_dir`.
+    fn load(install_dir: &Path, total: Duration) -> anyhow::Result<Self> {
+        let path = install_dir.join(SCRIPT_TIMINGS_FILE);
+        // Nothing is recorded if every component was already installed.
+        let contents = match std::fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(err) => {
+                return Err(err).with_context(|| {
+                    format!("failed to read install timings '{}'", path.display())
+                });
+            },
+        };
+
+        let components = contents
+            .lines()
+            .map(|line| {
+                let (name, seconds) = line
+                    .split_once(' ')
+                    .and_then(|(name, seconds)| Some((name, seconds.parse::<f64>().ok()?)))
+                    .with_context(|| format!("invalid install timing '{line}'"))?;
+                Ok(ComponentTiming { name: name.to_string(), seconds })
+            })
+            .collect::<anyhow::Result<_>>()?;
+
+        Ok(InstallTimings {
+            components,
+            total_seconds: total.as_secs_f64(),
+        })
+    }
+
+    /// Prints a table of how long each component took to install.
+    fn print(&self, config: &Config) {
+        use colored::Colorize;
+
+        let width = self
+            .components
+            .iter()
+            .map(|component| component.name.chars().count())
+            .chain(["Component".len(), "total".len()])
+            .max()
+            .unwrap_or_default();
+
+        config.status(format!("{:<width$}  Time", "Component").bold());
+        for ComponentTiming { name, seconds } in self.components.iter() {
+            config.status(format!("{name:<width$}  {seconds:.1}s"));
+        }
+        config.status(format!("{:<width$}  {:.1}s", "total", self.total_seconds).bold());
+    }
+}
+
 /// Installs a specified toolchain by channel or version.
+///
+/// With `--dry-run`, the steps of the install are only printed, see [execute].
 pub fn install(
     config: &Config,
     channel: &Channel,
     local_manifest: &mut Manifest,
     options: &InstallationOptions,
 ) -> anyhow::Result<()> {
-    commands::setup_midenup(config, local_manifest)?;
+    let mut execution = Execution::new(options.dry_run);
+    execute(config, channel, local_manifest, options, &mut execution)?;
+    execution.finish(config);
+    Ok(())
+}
+
+/// Goes through the steps of installing `channel`, carrying them out or only recording them
+/// depending on `execution`.
+pub(super) fn execute(
+    config: &Config,
+    channel: &Channel,
+    local_manifest: &mut Manifest,
+    options: &InstallationOptions,
+    execution: &mut Execution,
+) -> anyhow::Result<()> {
+    // Options that were not passed on the command line are taken from the settings.
+    let mut options = options.clone();
+    config.settings.apply(&mut options);
+    let options = &options;
+
+    // Pinned components are installed at their pinned version, regardless of the channel's.
+    let mut channel = channel.clone();
+    if !options.ignore_pins {
+        channel.apply_pins(local_manifest.pins());
+    }
+    channel.resolve_versions(config.offline)?;
+    let channel = &channel;
+
+    // Besides the upstream channels, this also covers the ones coming from the local manifest,
+    // e.g. on reinstalls, and the overrides and pins applied to them.
+    channel.validate()?;
+
+    let script_toolchain = script_toolchain(channel, options)?;
+
+    // Without this check, a missing toolchain would only be reported by cargo once the install
+    // script is run, in terms of `-Zscript`.
+    if !execution.is_dry_run() && cargo_version(Some(&script_toolchain)).is_none() {
+        bail!(
+            "rust toolchain '{script_toolchain}' is not available, but it is needed to run the \
+             install script with `cargo -Zscript`. Install it with:
+    rustup toolchain install {script_toolchain}"
+        );
+    }
+
+    let started = Instant::now();
+    if !execution.is_dry_run() {
+        commands::setup_midenup(config, local_manifest)?;
+    }
 
     let toolchains_dir = config.midenup_home.join("toolchains");
     let toolchain_dir = toolchains_dir.join(format!("{}", &channel.name));
 
     let installed_toolchains_dir = config.midenup_home.join("installed_toolchains");
-    let install_dir_name = format!("{}-{}", &channel.name, channel.content_hash());
+    let install_dir_name = install_dir_name(config, channel, options);
     let install_dir = installed_toolchains_dir.join(&install_dir_name);
 
     // Relative path to the newly installed channel directory.
     let relative_install_target =
         PathBuf::from("..").join("installed_toolchains").join(&install_dir_name);
 
+    // An install directory which the toolchain's symlink doesn't point to was left behind by an
+    // interrupted install. Unless forced, the install is resumed: the components that were already
+    // installed are skipped.
+    if !execution.is_dry_run()
+        && !options.force
+        && install_dir.exists()
+        && std::fs::read_link(&toolchain_dir).ok() != Some(relative_install_target.clone())
+    {
+        config.status(format!(
+            "{}: resuming the interrupted install of {}, run with `--force` to start over",
+            "info".white().bold(),
+            channel.name
+        ));
+    }
+
+    // A forced install starts over from an empty install directory, instead of reusing the files
+    // of an interrupted install or of the toolchain's previous install.
+    let resuming = install_dir.exists() && !options.force;
+    if options.force && install_dir.exists() {
+        execution.step(Step::RemoveDirectory { path: install_dir.clone() }, || {
+            std::fs::remove_dir_all(&install_dir).with_context(|| {
+                format!("failed to remove install directory '{}'", install_dir.display())
+            })
+        })?;
+    }
+
     // If the install directory already exists; then that means we are re-issuing
     // an install. That's probably because the installation got interrumpted
     // mid way through.
-    if !install_dir.exists() {
+    //
+    // Otherwise, if a previous install of this channel exists, its components are reused.
+    // For more context behind this, see the [[update_channel]] function
+    // documentation.
+    let seeding = !resuming && toolchain_dir.exists() && !options.force;
+    if !resuming && !execution.is_dry_run() {
         std::fs::create_dir_all(&install_dir).with_context(|| {
             format!("failed to create install directory: '{}'", install_dir.display())
         })?;
-        // If a previous install of this channel exists, reuse the components.
-        // For more context behind this, see the [[update_channel]] function
-        // documentation.
-        if toolchain_dir.exists() {
+        if seeding {
             utils::fs::copy_dir_recursive(&toolchain_dir, &install_dir, &[]).with_context(
                 || {
                     format!(
@@ -60,14 +226,139 @@ pub fn install(
                     )
                 },
             )?;
-
-            commands::uninstall::uninstall_components(
-                &install_dir,
-                &options.components_to_uninstall,
+        }
+    }
+    if seeding {
+        for component in options.components_to_uninstall.iter() {
+            execution.step(
+                Step::RemoveComponent { component: component.name.to_string() },
+                || {
+                    commands::uninstall::uninstall_components(
+                        &install_dir,
+                        core::slice::from_ref(component),
+                    )
+                    .map_err(Into::into)
+                },
             )?;
         }
     }
 
+    // The components which are already installed, either by the interrupted install being resumed
+    // or by the previous install, are kept. This is decided here rather than by the install script,
+    // so that a dry run shows the same components being installed.
+    let components = components_to_install(channel, options)
+        .filter(|component| {
+            let installed_file = component.get_installed_file();
+            let installed = if resuming {
+                installed_file.get_path_from(&install_dir).exists()
+            } else if seeding {
+                installed_file.get_path_from(&toolchain_dir).exists()
+                    && !options
+                        .components_to_uninstall
+                        .iter()
+                        .any(|uninstalled| uninstalled.name == component.name)
+            } else {
+                false
+            };
+            !installed
+        })
+        .collect::<Vec<_>>();
+    for component in components.iter() {
+        execution.record(install_step(config, component));
+        if let InstalledFile::Executable { binary_name, .. } = component.get_installed_file() {
+            execution.record(Step::CreateSymlink {
+                link: install_dir.join("opt").join(component.get_symlink_name()),
+                target: PathBuf::from("..").join("bin").join(binary_name),
+            });
+        }
+    }
+
+    let timings = if execution.is_dry_run() {
+        None
+    } else {
+        run_install_script(
+            config,
+            channel,
+            &components,
+            options,
+            &install_dir,
+            &script_toolchain,
+            started,
+        )?
+    };
+
+    // The toolchain is only published once its hooks succeeded, so that a failing hook fails the
+    // install as a whole.
+    run_post_install_hooks(config, channel, &install_dir, execution)?;
+
+    execution.step(
+        Step::CreateSymlink {
+            link: toolchain_dir.clone(),
+            target: relative_install_target.clone(),
+        },
+        || publish_toolchain(config, channel, &relative_install_target),
+    )?;
+
+    let is_latest_stable = config.manifest.is_latest_stable(channel);
+
+    // If this channel is the new stable, we update the symlink
+    if is_latest_stable {
+        let stable_dir = toolchains_dir.join("stable");
+        let relative_channel_target = PathBuf::from(format!("{}", &channel.name));
+        execution.step(
+            Step::CreateSymlink {
+                link: stable_dir.clone(),
+                target: relative_channel_target.clone(),
+            },
+            || {
+                // NOTE: `exists` follows symlinks, so it would miss a dangling `stable` symlink.
+                if stable_dir.symlink_metadata().is_ok() {
+                    std::fs::remove_file(&stable_dir).context("Couldn't remove stable symlink")?;
+                }
+                utils::fs::symlink(&stable_dir, &relative_channel_target)
+                    .context("Couldn't create stable symlink")
+            },
+        )?;
+    }
+
+    let channel_name = channel.name.to_string();
+    let step = if local_manifest.get_channel_by_name(&channel.name).is_some() {
+        Step::UpdateChannel { channel: channel_name }
+    } else {
+        Step::AddChannel {
+            channel: channel_name,
+            stable: is_latest_stable,
+        }
+    };
+    execution.step(step, || {
+        save_installed_channel(config, channel, local_manifest, is_latest_stable)
+    })?;
+
+    if execution.is_dry_run() {
+        return Ok(());
+    }
+
+    config.emit(&Message::Installed { channel: channel.name.to_string() });
+
+    if let Some(timings) = timings {
+        timings.print(config);
+    }
+
+    Ok(())
+}
+
+/// Installs `components` of `channel` into `install_dir`, by running the install script with
+/// `script_toolchain`. Returns the install timings if they were requested, counting from
+/// `started`.
+fn run_install_script(
+    config: &Config,
+    channel: &Channel,
+    components: &[&Component],
+    options: &InstallationOptions,
+    install_dir: &Path,
+    script_toolchain: &str,
+    started: Instant,
+) -> anyhow::Result<Option<InstallTimings>> {
     let bin_dir = install_dir.join("bin");
     if !bin_dir.exists() {
         std::fs::create_dir_all(&bin_dir).with_context(|| {
@@ -99,6 +390,25 @@ pub fn install(
         })?;
     }
 
+    // Timings left behind by an interrupted install would be mixed up with the new ones.
+    let script_timings_path = install_dir.join(SCRIPT_TIMINGS_FILE);
+    if script_timings_path.exists() {
+        std::fs::remove_file(&script_timings_path).with_context(|| {
+            format!("failed to remove stale install timings '{}'", script_timings_path.display())
+        })?;
+    }
+
+    // Logs of components which are not rebuilt this time would be mistaken for the new ones.
+    let logs_dir = install_dir.join(COMPONENT_LOGS_DIR);
+    if logs_dir.exists() {
+        std::fs::remove_dir_all(&logs_dir).with_context(|| {
+            format!("failed to remove stale component logs '{}'", logs_dir.display())
+        })?;
+    }
+    std::fs::create_dir_all(&logs_dir).with_context(|| {
+        format!("failed to create component logs directory '{}'", logs_dir.display())
+    })?;
+
     // NOTE: Even when performing an update, we still need to re-generate the install script.
     // This is because, the versions that will be installed are written directly into the file; so
     // the file can't be "re-used".
@@ -107,36 +417,129 @@ pub fn install(
         format!("failed to create file for install script at '{}'", install_file_path.display())
     })?;
 
-    let install_script_contents = generate_install_script(config, channel, options, &install_dir);
+    let install_script_contents =
+        generate_install_script(config, channel, components, options, install_dir)?;
     install_file.write_all(&install_script_contents.into_bytes()).with_context(|| {
         format!("failed to write install script at '{}'", install_file_path.display())
     })?;
 
-    let mut child = std::process::Command::new("cargo")
+    for component in components {
+        config.emit(&Message::installing(component));
+    }
+
+    // Everything the install script prints, including cargo's output, is also written to
+    // `install.log`, so that failed installs can be looked into afterwards.
+    let log_path = install_dir.join(INSTALL_LOG_FILE);
+    let log = std::fs::File::create(&log_path)
+        .with_context(|| format!("failed to create install log '{}'", log_path.display()))?;
+    let log = Mutex::new(log);
+
+    let mut command = crate::external::cargo_command();
+    command
         .current_dir(&config.working_directory)
-        .env("MIDEN_SYSROOT", &install_dir)
+        .env("MIDEN_SYSROOT", install_dir)
         // HACK(pauls): This is for the benefit of the compiler, until it moves to using
         // MIDEN_SYSROOT instead.
-        .env("MIDENC_SYSROOT", &install_dir)
-        .args(["+nightly", "-Zscript"])
+        .env("MIDENC_SYSROOT", install_dir)
+        .env(commands::MIDENUP_OFFLINE_ENV, config.offline.to_string())
+        // The timeout may come from midenup's settings, which the script doesn't read.
+        .env(
+            crate::external::HTTP_TIMEOUT_ENV,
+            crate::external::http_timeout().as_secs().to_string(),
+        )
+        .arg(format!("+{script_toolchain}"))
+        .arg("-Zscript")
         .arg(&install_file_path)
-        .stderr(std::process::Stdio::inherit())
-        .stdout(std::process::Stdio::inherit())
-        .spawn()
-        .context("error occurred while running install script")?;
-
-    let status = child
-        .wait()
-        .context(format!("Error occurred while waiting to install {}", channel.name))?;
+        .stderr(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped());
+    // The output is piped through midenup, so colors have to be requested explicitly.
+    // `colored` follows `--color`, see [crate::options::ColorChoice].
+    let colorize = colored::control::SHOULD_COLORIZE.should_colorize();
+    if colorize && config.message_format == MessageFormat::Human {
+        command.env("CLICOLOR_FORCE", "1");
+    }
+    if !colorize {
+        command.env("CARGO_TERM_COLOR", "never");
+    } else if std::io::stderr().is_terminal() {
+        command.env("CARGO_TERM_COLOR", "always");
+    }
+    let mut child = command.spawn().context("error occurred while running install script")?;
+
+    let script_stdout = child.stdout.take().expect("install script's stdout is piped");
+    let script_stderr = child.stderr.take().expect("install script's stderr is piped");
+    let status = std::thread::scope(|scope| {
+        // When emitting JSON, stdout is reserved for events, so the install script's output goes
+        // to stderr instead.
+        let stdout: Box<dyn Write + Send> = match config.message_format {
+            MessageFormat::Human => Box::new(std::io::stdout()),
+            MessageFormat::Json => Box::new(std::io::stderr()),
+        };
+        let printed = scope.spawn(|| crate::external::tee(script_stdout, stdout, &log));
+        scope.spawn(|| crate::external::tee(script_stderr, std::io::stderr(), &log));
+        let status = child.wait();
+        status.map(|status| (status, printed.join().unwrap_or_default()))
+    });
+    let (status, printed) =
+        status.context(format!("Error occurred while waiting to install {}", channel.name))?;
+
+    // The install script prints something as soon as it starts, so if it didn't, it failed to
+    // compile. Library components are part of it, so they are the likely culprits.
+    let libraries = components
+        .iter()
+        .filter(|component| matches!(component.get_installed_file(), InstalledFile::Library { .. }))
+        .map(|component| format!("'{}'", component.name))
+        .collect::<Vec<_>>();
+    if !status.success() && printed == 0 && !libraries.is_empty() {
+        bail!(
+            "the install script of toolchain {} failed to compile, check that the library \
+             components it builds ({}) compile with rust toolchain '{script_toolchain}', see '{}' \
+             for the full output",
+            channel.name,
+            libraries.join(", "),
+            log_path.display()
+        )
+    }
 
     if !status.success() {
         bail!(
-            "midenup failed to install toolchain from channel {} with status {}",
+            "midenup failed to install toolchain from channel {} with status {}, see '{}' for the \
+             full output",
             channel.name,
-            status.code().unwrap_or(1)
+            status.code().unwrap_or(1),
+            log_path.display()
         )
     }
 
+    let timings = if options.timings {
+        let timings = InstallTimings::load(install_dir, started.elapsed())?;
+        let timings_path = install_dir.join("install-timings.json");
+        std::fs::write(
+            &timings_path,
+            serde_json::to_string_pretty(&timings).context("Couldn't serialize install timings")?,
+        )
+        .with_context(|| format!("failed to write install timings '{}'", timings_path.display()))?;
+        Some(timings)
+    } else {
+        None
+    };
+    if script_timings_path.exists() {
+        std::fs::remove_file(&script_timings_path).with_context(|| {
+            format!("failed to remove install timings '{}'", script_timings_path.display())
+        })?;
+    }
+
+    Ok(timings)
+}
+
+/// Points the toolchain symlink of `channel` to `relative_install_target`, which marks its install
+/// as completed.
+fn publish_toolchain(
+    config: &Config,
+    channel: &Channel,
+    relative_install_target: &Path,
+) -> anyhow::Result<()> {
+    let installed_toolchains_dir = config.midenup_home.join("installed_toolchains");
+    let toolchain_dir = channel.get_channel_dir(config);
     let temp_symlink = installed_toolchains_dir.join(format!("{}.new", &channel.name));
     if std::fs::symlink_metadata(&temp_symlink).is_ok() {
         std::fs::remove_file(&temp_symlink).with_context(|| {
@@ -150,7 +553,7 @@ pub fn install(
     // if tmp_link file is moved, it will still point to relative_install_target.
     // For further reference on atomic directory updates, see:
     // https://axialcorps.wordpress.com/2013/07/03/atomically-replacing-files-and-directories/
-    utils::fs::symlink(&temp_symlink, &relative_install_target)?;
+    utils::fs::symlink(&temp_symlink, relative_install_target)?;
 
     // We now rename tmp_link to toolchain_dir. When renamed, it will still be
     // pointing to relative_install_target. If the channel directory existed, it
@@ -161,23 +564,18 @@ pub fn install(
             toolchain_dir.display(),
             relative_install_target.display()
         )
-    })?;
-
-    let is_latest_stable = config.manifest.is_latest_stable(channel);
-
-    // If this channel is the new stable, we update the symlink
-    if is_latest_stable {
-        let stable_dir = toolchains_dir.join("stable");
-        if stable_dir.exists() {
-            std::fs::remove_file(&stable_dir).context("Couldn't remove stable symlink")?;
-        }
-        let relative_channel_target = PathBuf::from(format!("{}", &channel.name));
-        utils::fs::symlink(&stable_dir, &relative_channel_target)
-            .expect("Couldn't create stable dir");
-    }
+    })
+}
 
-    // Update local manifest
-    let local_manifest_path = config.midenup_home.join("manifest").with_extension("json");
+/// Records the installed `channel` in `local_manifest`, along with how its components got
+/// installed, and saves it.
+fn save_installed_channel(
+    config: &Config,
+    channel: &Channel,
+    local_manifest: &mut Manifest,
+    is_latest_stable: bool,
+) -> anyhow::Result<()> {
+    let toolchain_dir = channel.get_channel_dir(config);
     {
         // Check if the installed channel needs to marked as stable
         let mut channel_to_save = if is_latest_stable {
@@ -233,17 +631,18 @@ pub fn install(
                     } else {
                         Cow::Owned(config.working_directory.join(path.as_path()))
                     };
-                    let latest_time = utils::fs::latest_modification(&path)
-                        .ok()
-                        .map(|(latest_modification, _)| latest_modification)
-                        .unwrap_or(SystemTime::now());
+                    let latest_time =
+                        utils::fs::latest_modification(&path, utils::fs::IGNORED_DIRECTORIES)
+                            .ok()
+                            .map(|(latest_modification, _)| latest_modification)
+                            .unwrap_or(SystemTime::now());
                     component.version = Authority::Path {
                         path: path.to_path_buf(),
                         crate_name: crate_name.clone(),
                         last_modification: Some(latest_time),
                     }
                 },
-                Authority::Cargo { package, .. } => {
+                Authority::Cargo { package, .. } | Authority::CargoRequirement { package, .. } => {
                     // If a component is marked with Cargo as an authority and
                     // also has artifacts listed as available, determine which
                     // got used for the installation.
@@ -279,24 +678,235 @@ pub fn install(
         local_manifest.add_channel(channel_to_save);
     }
 
-    let mut local_manifest_file =
-        std::fs::File::create(&local_manifest_path).with_context(|| {
-            format!(
-                "failed to create file for local manifest at '{}'",
-                local_manifest_path.display()
-            )
+    commands::save_local_manifest(config, local_manifest)
+}
+
+/// Reinstalls the installed toolchain `channel` from scratch, with exactly the components it was
+/// installed with. Useful when a toolchain got corrupted.
+///
+/// The components are taken from the local manifest rather than from the upstream channel, so a
+/// partial install stays partial, and every component keeps its version, source and features. The
+/// toolchain is installed into a new directory before the previous one is removed, so it is left
+/// untouched if the reinstall fails, see [InstallationOptions::replace_install].
+pub fn reinstall(
+    config: &Config,
+    channel: &UserChannel,
+    local_manifest: &mut Manifest,
+    options: &InstallationOptions,
+) -> anyhow::Result<()> {
+    let Some(installed_channel) = local_manifest.get_channel(channel).cloned() else {
+        bail!("toolchain '{channel}' is not installed");
+    };
+    let options = InstallationOptions {
+        // Every component in the local manifest was installed, optional or not.
+        profile: Profile::Complete,
+        ignore_pins: true,
+        force: true,
+        replace_install: true,
+        ..options.clone()
+    };
+    let previous_install_dir = installed_channel.get_channel_dir(config).canonicalize().ok();
+
+    let mut execution = Execution::new(options.dry_run);
+    execute(config, &installed_channel, local_manifest, &options, &mut execution)?;
+
+    if let Some(previous_install_dir) = previous_install_dir {
+        execution.step(Step::RemoveDirectory { path: previous_install_dir.clone() }, || {
+            std::fs::remove_dir_all(&previous_install_dir).with_context(|| {
+                format!(
+                    "failed to remove the previous install of {}, at '{}'",
+                    installed_channel.name,
+                    previous_install_dir.display()
+                )
+            })
         })?;
-    local_manifest_file
-        .write_all(
-            serde_json::to_string_pretty(&local_manifest)
-                .context("Couldn't serialize local manifest")?
-                .as_bytes(),
-        )
-        .context("Couldn't create local manifest file")?;
+    }
 
+    execution.finish(config);
     Ok(())
 }
 
+/// Runs the [Channel::post_install] hooks of `channel`, installed in `install_dir`, one after the
+/// other. Fails as soon as a hook does, without running the remaining ones.
+fn run_post_install_hooks(
+    config: &Config,
+    channel: &Channel,
+    install_dir: &Path,
+    execution: &mut Execution,
+) -> anyhow::Result<()> {
+    for hook in channel.resolve_post_install_hooks(install_dir)? {
+        execution.step(Step::RunHook { command: command_line(&hook) }, || {
+            run_post_install_hook(config, channel, install_dir, &hook)
+        })?;
+    }
+
+    Ok(())
+}
+
+/// Runs `hook`, one of the resolved [Channel::post_install] hooks of `channel`.
+fn run_post_install_hook(
+    config: &Config,
+    channel: &Channel,
+    install_dir: &Path,
+    hook: &[std::ffi::OsString],
+) -> anyhow::Result<()> {
+    let Some((program, args)) = hook.split_first() else {
+        return Ok(());
+    };
+    let command_line = command_line(hook);
+    config.status(format!("{}: running post-install hook `{command_line}`", "info".white().bold()));
+
+    // When emitting JSON, stdout is reserved for events.
+    let stdout = match config.message_format {
+        MessageFormat::Human => std::process::Stdio::inherit(),
+        MessageFormat::Json => std::process::Stdio::from(std::io::stderr()),
+    };
+    let status = std::process::Command::new(program)
+        .args(args)
+        .current_dir(&config.working_directory)
+        .env("MIDENUP_HOME", &config.midenup_home)
+        .env("MIDENUP_TOOLCHAIN", channel.name.to_string())
+        .env("MIDEN_SYSROOT", install_dir)
+        .stdout(stdout)
+        .status()
+        .with_context(|| format!("failed to run post-install hook `{command_line}`"))?;
+    if !status.success() {
+        bail!(
+            "post-install hook `{command_line}` of toolchain {} failed with status {}",
+            channel.name,
+            status.code().unwrap_or(1)
+        );
+    }
+
+    Ok(())
+}
+
+/// The name of the directory in `installed_toolchains/` that `channel` gets installed into.
+///
+/// With [InstallationOptions::replace_install], this is never the directory the toolchain is
+/// currently installed in: a forced install removes its install directory before starting over,
+/// which would leave nothing behind if the install then failed.
+fn install_dir_name(config: &Config, channel: &Channel, options: &InstallationOptions) -> String {
+    let install_dir_name = format!("{}-{}", &channel.name, channel.content_hash());
+    if !options.replace_install {
+        return install_dir_name;
+    }
+
+    let current_install_dir = channel.get_channel_dir(config).canonicalize().ok();
+    let install_dir = config.midenup_home.join("installed_toolchains").join(&install_dir_name);
+    if current_install_dir.is_some() && current_install_dir == install_dir.canonicalize().ok() {
+        format!("{install_dir_name}.reinstall")
+    } else {
+        install_dir_name
+    }
+}
+
+/// Joins the words of a resolved command, as they would be typed in a shell.
+fn command_line(words: &[std::ffi::OsString]) -> String {
+    words.iter().map(|word| word.to_string_lossy()).collect::<Vec<_>>().join(" ")
+}
+
+/// The step which installs `component` into `install_dir`.
+///
+/// Components with an artifact for this system are expected to be downloaded, although the install
+/// falls back to building them from source if the download fails.
+fn install_step(config: &Config, component: &Component) -> Step {
+    let installed_file = component.get_installed_file();
+    let target = match installed_file {
+        InstalledFile::Executable { .. } => &config.target,
+        InstalledFile::Library { .. } => &TargetTriple::MidenVM,
+    };
+    // Remote artifacts can't be downloaded in offline mode.
+    let artifact_uri = component
+        .get_artifact_uri(target)
+        .filter(|uri| !(config.offline && uri.starts_with("https://")));
+
+    let component_name = component.name.to_string();
+    match (artifact_uri, &installed_file) {
+        (Some(uri), _) => Step::DownloadArtifact { component: component_name, uri },
+        (None, InstalledFile::Executable { .. }) => Step::CargoInstall {
+            component: component_name,
+            source: commands::plan::describe_source(component),
+        },
+        (None, InstalledFile::Library { .. }) => Step::BuildLibrary {
+            component: component_name,
+            source: commands::plan::describe_source(component),
+        },
+    }
+}
+
+/// Returns the components of `channel` which get installed with the selected profile.
+fn components_to_install<'a>(
+    channel: &'a Channel,
+    options: &InstallationOptions,
+) -> impl Iterator<Item = &'a Component> {
+    // Partial channels already contain exactly the components that were selected, optional or not.
+    let minimal_install =
+        matches!(options.profile, Profile::Minimal) && !channel.is_partially_installed();
+    channel
+        .components
+        .iter()
+        .filter(move |component| !(minimal_install && component.optional))
+}
+
+/// Returns the rust toolchain the install script is run with, which also builds the channel's
+/// library components, since they are dependencies of the script.
+///
+/// This is `nightly` unless the library components ask for a specific toolchain with their
+/// `rustup_channel`. Since `cargo -Zscript` is unstable, that toolchain must be a nightly one, and
+/// all library components must agree on it.
+fn script_toolchain(channel: &Channel, options: &InstallationOptions) -> anyhow::Result<String> {
+    let mut toolchains: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+    for component in components_to_install(channel, options) {
+        if let (InstalledFile::Library { .. }, Some(toolchain)) =
+            (component.get_installed_file(), &component.rustup_channel)
+        {
+            toolchains
+                .entry(toolchain.as_str())
+                .or_default()
+                .push(format!("'{}'", component.name));
+        }
+    }
+
+    match toolchains.len() {
+        0 => Ok(String::from("nightly")),
+        1 => {
+            let (toolchain, components) = toolchains.pop_first().unwrap();
+            if !toolchain.starts_with("nightly") {
+                bail!(
+                    "library components {} require rust toolchain '{toolchain}', but libraries \
+                     are built by the install script, which requires a nightly toolchain",
+                    components.join(", ")
+                );
+            }
+            Ok(toolchain.to_string())
+        },
+        _ => bail!(
+            "library components require different rust toolchains, but they are all built by the \
+             same install script:\n{}",
+            toolchains
+                .iter()
+                .map(|(toolchain, components)| format!("{toolchain}: {}", components.join(", ")))
+                .collect::<Vec<_>>()
+                .join("\n")
+        ),
+    }
+}
+
+/// Returns the output of `cargo [+toolchain] --version`, or `None` if it could not be run.
+pub(crate) fn cargo_version(toolchain: Option<&str>) -> Option<String> {
+    let mut command = crate::external::cargo_command();
+    if let Some(toolchain) = toolchain {
+        command.arg(format!("+{toolchain}"));
+    }
+    let output = command.arg("--version").output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
 /// This function generates the install script that will later be saved in
 /// `midenup/toolchains/<version>/install.rs`.
 ///
@@ -304,9 +914,10 @@ pub fn install(
 fn generate_install_script(
     config: &Config,
     channel: &Channel,
+    components: &[&Component],
     options: &InstallationOptions,
     toolchain_directory: &Path,
-) -> String {
+) -> anyhow::Result<String> {
     // Prepare install script template
     let engine = upon::Engine::new();
     let template = engine
@@ -316,12 +927,16 @@ fn generate_install_script(
 [dependencies]
 {%- for dep in dependencies %}
 {{ dep.package }} = { version = "{{ dep.version }}"
-{%- if dep.git_uri %}, git = "{{ dep.git_uri }}"
+{%- if dep.git_uri %}, git = "{{ dep.git_uri }}", {{ dep.git_target }}
 {%- else if dep.path %}, path = "{{ dep.path }}"
+{%- else if dep.registry %}, registry = "{{ dep.registry }}"
+{%- endif %}
+{%- if not dep.default_features %}, default-features = false
 {%- endif %} }
 {%- endfor %}
 colored = "3.0"
 curl = "{{ curl_version }}"
+sha2 = "{{ sha2_version }}"
 ---
 
 // NOTE: This file was generated by midenup. Do not edit by hand
@@ -352,6 +967,192 @@ fn info(msg: impl core::fmt::Display) {
     print!("info: {msg}")
 }
 
+/// How long each component took to install, in seconds. See [record_timing].
+static TIMINGS: std::sync::Mutex<Vec<(&str, f64)>> = std::sync::Mutex::new(Vec::new());
+
+/// Records how long installing `component` took, if it was started at `started`.
+fn record_timing(component: &'static str, started: std::time::Instant) {
+    TIMINGS.lock().unwrap().push((component, started.elapsed().as_secs_f64()));
+}
+
+/// Writes the timings recorded with [record_timing] to `$MIDEN_SYSROOT/{{ timings_file }}`, for
+/// midenup to report.
+fn write_timings(miden_sysroot_dir: &Path) {
+    let timings = TIMINGS
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|(component, seconds)| format!("{component} {seconds}\n"))
+        .collect::<String>();
+    if let Err(err) = std::fs::write(miden_sysroot_dir.join("{{ timings_file }}"), timings) {
+        error(format!("failed to record install timings: {err}\n"));
+    }
+}
+
+/// An executable component to be installed into `$MIDEN_SYSROOT/bin`.
+struct Executable {
+    name: &'static str,
+    /// The artifact's (uri, destination, sha256). The uri is empty if there is no artifact.
+    artifact: (&'static str, &'static str, &'static str),
+    required_toolchain_flag: &'static str,
+    args: &'static [&'static str],
+    /// Executables that must be installed before this one.
+    requires: &'static [&'static str],
+}
+
+const EXECUTABLES: &[Executable] = &[
+    {%- for component in installable_components %}
+    Executable {
+        name: "{{ component.name }}",
+        artifact: ("{{ component.artifact.0 }}", "{{ component.artifact.1 }}", "{{ component.artifact.2 }}"),
+        required_toolchain_flag: "{{ component.required_toolchain_flag }}",
+        args: &[
+            {%- for arg in component.args %}
+            "{{ arg }}",
+            {%- endfor %}
+        ],
+        requires: &[
+            {%- for dependency in component.requires %}
+            "{{ dependency }}",
+            {%- endfor %}
+        ],
+    },
+    {%- endfor %}
+];
+
+/// Installs a single executable, either from its artifact or from source. Returns whether the
+/// executable ended up installed.
+///
+/// The status line is only printed once the install finishes, so that output from concurrent
+/// installs does not interleave.
+fn install_executable(executable: &Executable, miden_sysroot_dir: &Path) -> bool {
+    let name = executable.name.white().bold();
+    let width = {{ max_component_width }};
+
+    let started = std::time::Instant::now();
+    let (uri, destination, sha256) = executable.artifact;
+    let mut should_build = uri.is_empty();
+    if !should_build {
+        if let Err(err) = install_artifact(executable.name, uri, sha256, destination, {{ show_progress }}) {
+            error(format!("failed to fetch artifact for {}: {err}\n", executable.name));
+            should_build = true;
+        }
+    }
+
+    if should_build {
+        if let Err(err) = install_from_source(
+            executable.required_toolchain_flag,
+            &[
+                {%- for arg in chosen_profile %}
+                "{{ arg }}",
+                {%- endfor %}
+            ],
+            &[
+                {%- for flag in verbosity_flags %}
+                "{{ flag }}",
+                {%- endfor %}
+            ],
+            &[
+                {%- for flag in cargo_jobs_flags %}
+                "{{ flag }}",
+                {%- endfor %}
+            ],
+            executable.args,
+            miden_sysroot_dir,
+            &miden_sysroot_dir.join("{{ logs_dir }}").join(format!("{}.log", executable.name)),
+        ) {
+            info(format!(
+                "installing {name:.<width$}{}: unable to install {} from source: {err}\n",
+                "failed".red().bold(),
+                executable.name,
+            ));
+            record_timing(executable.name, started);
+            return false;
+        }
+    }
+
+    record_timing(executable.name, started);
+    info(format!("installing {name:.<width$}{}\n", "installed".green().bold()));
+    true
+}
+
+/// Installs `executables` using up to `jobs` concurrent workers. An executable is only started
+/// once every executable it requires has finished. Returns whether all of them got installed.
+///
+/// If `keep_going` is false, no new installs are started after the first failure.
+fn install_executables(
+    executables: &[Executable],
+    jobs: usize,
+    keep_going: bool,
+    miden_sysroot_dir: &Path,
+) -> bool {
+    struct State {
+        started: Vec<bool>,
+        finished: Vec<bool>,
+        running: usize,
+        failed: bool,
+    }
+
+    let state = std::sync::Mutex::new(State {
+        started: vec![false; executables.len()],
+        finished: vec![false; executables.len()],
+        running: 0,
+        failed: false,
+    });
+    let wakeup = std::sync::Condvar::new();
+
+    // Requirements on components which are not executables (or are not being installed) are
+    // ignored: libraries are installed before any executable.
+    let is_ready = |state: &State, index: usize| {
+        !state.started[index]
+            && executables[index].requires.iter().all(|dependency| {
+                executables
+                    .iter()
+                    .position(|executable| executable.name == *dependency)
+                    .is_none_or(|dependency| state.finished[dependency])
+            })
+    };
+
+    std::thread::scope(|scope| {
+        for _ in 0..jobs.clamp(1, executables.len().max(1)) {
+            scope.spawn(|| loop {
+                let index = {
+                    let mut state = state.lock().unwrap();
+                    loop {
+                        if (state.failed && !keep_going) || state.started.iter().all(|s| *s) {
+                            return;
+                        }
+                        let next = (0..executables.len()).find(|index| is_ready(&state, *index));
+                        // If nothing is ready and nothing is running, the remaining executables
+                        // require each other. Rather than waiting forever, start the first one.
+                        let next = next.or_else(|| {
+                            (state.running == 0)
+                                .then(|| state.started.iter().position(|s| !s))
+                                .flatten()
+                        });
+                        if let Some(index) = next {
+                            state.started[index] = true;
+                            state.running += 1;
+                            break index;
+                        }
+                        state = wakeup.wait(state).unwrap();
+                    }
+                };
+
+                let installed = install_executable(&executables[index], miden_sysroot_dir);
+
+                let mut state = state.lock().unwrap();
+                state.finished[index] = true;
+                state.running -= 1;
+                state.failed |= !installed;
+                wakeup.notify_all();
+            });
+        }
+    });
+
+    !state.into_inner().unwrap().failed
+}
+
 fn main() -> ExitCode {
     // MIDEN_SYSROOT is set by `midenup` when invoking this script, and will contain the resolved
     // (and prepared) sysroot path to which this script will install the desired toolchain
@@ -368,61 +1169,13 @@ fn main() -> ExitCode {
         // Write library to $MIDEN_SYSROOT/lib/dep.masp
         let lib = {{ dep.exposing_function }};
         let lib_path = lib_dir.join("{{ dep.name }}").with_extension("masp");
-        // NOTE: If the file already exists, then we are running an update and we don't need to
-        // update this element. We treat failure to detect existence as non-existence, and in cases
-        // where that is due to permissions or some other issue, we let the actual install fail.
-        if !std::fs::exists(&lib_path).unwrap_or(false) {
-            let mut successfully_installed = false;
-            let should_fetch = !"{{ dep.artifact.0 }}".is_empty();
-            let mut should_build = !should_fetch;
-
-            if should_fetch {
-                if let Err(err) = install_artifact("{{ dep.artifact.0 }}", "{{ dep.artifact.1 }}") {
-                    error(format!("failed to fetch artifact: {err}\n"));
-                    should_build = true;
-                } else {
-                    println!("{}", "installed".green().bold());
-                    successfully_installed = true;
-                }
-            }
-
-            if should_build {
-                // NOTE(pauls): This needs to be redone after the transition to packages is complete
-                if let Err(err) = lib.as_ref().write_to_file(&lib_path) {
-                    println!("{}: unable to install {{ dep.name }} from source: {err}", "failed".red().bold());
-                    if !{{ keep_going }} {
-                        return ExitCode::FAILURE;
-                    }
-                } else {
-                    println!("{}", "installed".green().bold());
-                    successfully_installed = true;
-                }
-            }
-
-            if !successfully_installed {
-                exit_status = ExitCode::FAILURE;
-            }
-        } else {
-            println!("already installed");
-        }
-        {%- endfor %}
-    }
-
-
-    // Install executables
-    let bin_dir = miden_sysroot_dir.join("bin");
-    {% for component in installable_components %}
-
-    // Install {{ component.name }}
-    info(format!("installing {:.<width$}", "{{ component.name }}".white().bold(), width = {{ max_component_width }}));
-    let bin_path = bin_dir.join("{{ component.installed_file }}");
-    if !std::fs::exists(&bin_path).unwrap_or(false) {
-        let should_fetch = !"{{ component.artifact.0 }}".is_empty();
-        let mut should_build = !should_fetch;
+        let started = std::time::Instant::now();
         let mut successfully_installed = false;
+        let should_fetch = !"{{ dep.artifact.0 }}".is_empty();
+        let mut should_build = !should_fetch;
 
         if should_fetch {
-            if let Err(err) = install_artifact("{{ component.artifact.0 }}", "{{ component.artifact.1 }}") {
+            if let Err(err) = install_artifact("{{ dep.name }}", "{{ dep.artifact.0 }}", "{{ dep.artifact.2 }}", "{{ dep.artifact.1 }}", {{ show_progress }}) {
                 error(format!("failed to fetch artifact: {err}\n"));
                 should_build = true;
             } else {
@@ -432,22 +1185,9 @@ fn main() -> ExitCode {
         }
 
         if should_build {
-            if let Err(err) = install_from_source(
-                "{{ component.required_toolchain_flag }}",
-                &[
-                    {%- for arg in chosen_profile %}
-                    "{{ arg }}",
-                    {%- endfor %}
-                ],
-                "{{ verbosity.quiet_flag }}",
-                &[
-                    {%- for arg in component.args %}
-                    "{{ arg }}",
-                    {%- endfor %}
-                ],
-                miden_sysroot_dir,
-            ) {
-                println!("{}: unable to install {{ component.name }} from source: {err}", "failed".red().bold());
+            // NOTE(pauls): This needs to be redone after the transition to packages is complete
+            if let Err(err) = lib.as_ref().write_to_file(&lib_path) {
+                println!("{}: unable to install {{ dep.name }} from source: {err}", "failed".red().bold());
                 if !{{ keep_going }} {
                     return ExitCode::FAILURE;
                 }
@@ -457,13 +1197,24 @@ fn main() -> ExitCode {
             }
         }
 
+        record_timing("{{ dep.name }}", started);
         if !successfully_installed {
             exit_status = ExitCode::FAILURE;
         }
-    } else {
-        println!("already installed");
+        {%- endfor %}
+    }
+
+
+    // Install executables
+    //
+    // Executables which do not require one another are installed concurrently, see
+    // [install_executables].
+    if !install_executables(EXECUTABLES, {{ jobs }}, {{ keep_going }}, miden_sysroot_dir) {
+        if !{{ keep_going }} {
+            return ExitCode::FAILURE;
+        }
+        exit_status = ExitCode::FAILURE;
     }
-    {% endfor %}
 
     let opt_dir = miden_sysroot_dir.join("opt");
 
@@ -484,22 +1235,22 @@ fn main() -> ExitCode {
         std::fs::create_dir(&var_dir).expect("failed to create 'var' subdirectory in sysroot");
     }
 
+    if {{ timings }} {
+        write_timings(miden_sysroot_dir);
+    }
+
     exit_status
 }
 "##,
         )
-        .unwrap_or_else(|err| panic!("invalid install script template: {err:#}"));
+        .map_err(|err| anyhow!("invalid install script template: {err:#}"))?;
 
     let mut max_component_width = 0usize;
 
     // Prepare install script context with available channel components
     let mut dependencies = Vec::new();
     let mut installable_components = Vec::new();
-    let minimal_install = matches!(options.profile, Profile::Minimal);
-    for component in channel.components.iter() {
-        if minimal_install && component.optional {
-            continue;
-        }
+    for &component in components {
         max_component_width = core::cmp::max(max_component_width, component.name.chars().count());
         match component.get_installed_file() {
             InstalledFile::Executable { .. } => {
@@ -507,7 +1258,9 @@ fn main() -> ExitCode {
                     component.get_artifact_uri(&config.target).map(|uri| {
                         let destination =
                             component.get_installed_file().get_path_from(toolchain_directory);
-                        (uri, destination)
+                        let sha256 =
+                            component.get_artifact_sha256(&config.target).unwrap_or_default();
+                        (uri, destination, sha256)
                     })
                 };
                 installable_components.push((component, artifact_destination))
@@ -517,8 +1270,11 @@ fn main() -> ExitCode {
                     component.get_artifact_uri(&TargetTriple::MidenVM).map(|uri| {
                         let destination =
                             component.get_installed_file().get_path_from(toolchain_directory);
+                        let sha256 = component
+                            .get_artifact_sha256(&TargetTriple::MidenVM)
+                            .unwrap_or_default();
 
-                        (uri, destination)
+                        (uri, destination, sha256)
                     })
                 };
 
@@ -533,10 +1289,8 @@ fn main() -> ExitCode {
     //
     // - A symlink that adds the 'miden ' prefix to the corresponding executable,   done in order to
     //   "trick" clap into displaying midenup compatile messages, for more information, see: https://github.com/0xMiden/midenup/pull/73.
-    let symlinks = channel
-        .components
+    let symlinks = components
         .iter()
-        .filter(|c| !(minimal_install && c.optional))
         .flat_map(|component| {
             let mut executables = Vec::new();
 
@@ -556,26 +1310,26 @@ fn main() -> ExitCode {
         })
         .collect::<Vec<_>>();
 
+    // Crates published on crates.io are taken from the chosen registry instead, if any.
+    let registry = options.registry.clone().unwrap_or_default();
+
     // The set of cargo dependencies needed for the install script
     let dependencies = dependencies
         .into_iter()
         .map(|(component, artifact)| {
             let installed_file = component.get_installed_file();
-            let library_struct = installed_file
-                .get_library_struct()
-                .with_context(|| {
-                    format!(
-                        "Component {} is marked as library, however the manifest does not contain \
-                         the associated Library struct from where it will obtain the `.masp` \
-                         file. \nThe manifest should contain a line like the following: \
-                         \nlibrary_struct: \"miden_stdlib::MidenStdLib::default()\"",
-                        component.name
-                    )
-                })
-                .unwrap();
+            let library_struct = installed_file.get_library_struct().with_context(|| {
+                format!(
+                    "Component {} is marked as library, however the manifest does not contain the \
+                     associated Library struct from where it will obtain the `.masp` file. \nThe \
+                     manifest should contain a line like the following: \nlibrary_struct: \
+                     \"miden_stdlib::MidenStdLib::default()\"",
+                    component.name
+                )
+            })?;
             let exposing_function = format!("{library_struct}::default()");
             let artifact = artifact.unwrap_or_default();
-            match &component.version {
+            let dependency = match &component.version {
                 Authority::Cargo { package, version } => {
                     let package = package.as_deref().unwrap_or(component.name.as_ref()).to_string();
                     upon::value! {
@@ -583,7 +1337,25 @@ fn main() -> ExitCode {
                         package: package,
                         version: version.to_string(),
                         git_uri: "",
+                        git_target: "",
+                        path: "",
+                        registry: registry.clone(),
+                        default_features: component.default_features,
+                        exposing_function: exposing_function,
+                        artifact: artifact,
+                    }
+                },
+                Authority::CargoRequirement { package, requirement } => {
+                    let package = package.as_deref().unwrap_or(component.name.as_ref()).to_string();
+                    upon::value! {
+                        name: component.name.to_string(),
+                        package: package,
+                        version: requirement.to_string(),
+                        git_uri: "",
+                        git_target: "",
                         path: "",
+                        registry: registry.clone(),
+                        default_features: component.default_features,
                         exposing_function: exposing_function,
                         artifact: artifact,
                     }
@@ -593,8 +1365,11 @@ fn main() -> ExitCode {
                         name: component.name.to_string(),
                         package: crate_name,
                         version: "> 0.0.0",
-                        git_uri: format!("{}\", {target}", repository_url.clone()),
+                        git_uri: repository_url.clone(),
+                        git_target: target.to_string(),
                         path: "",
+                        registry: "",
+                        default_features: component.default_features,
                         exposing_function: exposing_function,
                         artifact: artifact,
                     }
@@ -605,14 +1380,18 @@ fn main() -> ExitCode {
                         package: crate_name,
                         version: "> 0.0.0",
                         git_uri: "",
+                        git_target: "",
                         path: path.display().to_string(),
+                        registry: "",
+                        default_features: component.default_features,
                         exposing_function: exposing_function,
                         artifact: artifact,
                     }
                 },
-            }
+            };
+            Ok(dependency)
         })
-        .collect::<Vec<_>>();
+        .collect::<anyhow::Result<Vec<_>>>()?;
 
     // The set of components to be installed with `cargo install`
     let installable_components = installable_components
@@ -626,6 +1405,14 @@ fn main() -> ExitCode {
                     args.push("--version".to_string());
                     args.push(version.to_string());
                 },
+                // Requirements are resolved before installing (see [Channel::resolve_versions]),
+                // but `cargo install` accepts them as well.
+                Authority::CargoRequirement { package, requirement } => {
+                    let package = package.as_deref().unwrap_or(component.name.as_ref());
+                    args.push(package.to_string());
+                    args.push("--version".to_string());
+                    args.push(requirement.to_string());
+                },
                 Authority::Git { repository_url, target, crate_name } => {
                     args.push("--git".to_string());
                     args.push(repository_url.clone());
@@ -637,6 +1424,12 @@ fn main() -> ExitCode {
                     args.push(path.display().to_string());
                 },
             }
+            if let (Authority::Cargo { .. } | Authority::CargoRequirement { .. }, Some(registry)) =
+                (&component.version, &options.registry)
+            {
+                args.push("--registry".to_string());
+                args.push(registry.clone());
+            }
 
             let required_toolchain =
                 component.rustup_channel.clone().unwrap_or(String::from("stable"));
@@ -649,15 +1442,24 @@ fn main() -> ExitCode {
                 args.push("--features".to_string());
                 args.push(features);
             };
-
-            let installed_file = component.get_installed_file().to_string();
+            if let Some(bin) = &component.bin {
+                args.push("--bin".to_string());
+                args.push(bin.clone());
+            }
+            // `cargo install` is always run with `--locked`, this also forbids network access.
+            if options.frozen {
+                args.push("--frozen".to_string());
+            }
+            if !component.default_features {
+                args.push("--no-default-features".to_string());
+            }
 
             upon::value! {
                 name: component.name.to_string(),
-                installed_file: installed_file,
                 required_toolchain_flag: required_toolchain_flag,
                 args: args,
                 artifact: artifact.unwrap_or_default(),
+                requires: component.requires.clone(),
             }
         })
         .collect::<Vec<_>>();
@@ -668,15 +1470,12 @@ fn main() -> ExitCode {
         ["--profile", "release"]
     };
 
-    // NOTE: We do not pass cargo's --verbose flag since it displays a *lot* of information.
-    let verbosity = if !options.verbose {
-        upon::value! {
-            quiet_flag: "--quiet"
-        }
-    } else {
-        upon::value! {
-            quiet_flag: ""
-        }
+    // NOTE: cargo's --verbose flag displays a *lot* of information, so it is only passed with
+    // `-vv`.
+    let verbosity_flags = match options.verbose {
+        0 => vec!["--quiet"],
+        1 => vec![],
+        _ => vec!["--verbose"],
     };
 
     let install_artifact_function = {
@@ -685,7 +1484,15 @@ fn main() -> ExitCode {
         }
     };
 
+    // The number of executables the install script is allowed to install at once.
+    let jobs = options.jobs.map(NonZeroUsize::get).unwrap_or(1);
+    let cargo_jobs_flags = cargo_jobs_flags(
+        jobs,
+        std::thread::available_parallelism().map(NonZeroUsize::get).unwrap_or(1),
+    );
+
     let curl_version = env!("CURL_VERSION");
+    let sha2_version = env!("SHA2_VERSION");
 
     // This determines whether to panic if a component fails to be install. In release builds, we
     // want midenup to keep going; but on debug builds we want to catch those errors.
@@ -706,28 +1513,46 @@ fn main() -> ExitCode {
             &engine,
             upon::value! {
                 max_component_width: max_component_width + 2,
+                logs_dir: COMPONENT_LOGS_DIR,
                 dependencies: dependencies,
                 installable_components: installable_components,
-                channel_json : serde_json::to_string_pretty(channel).unwrap(),
+                channel_json : serde_json::to_string_pretty(channel)
+                    .context("Couldn't serialize the channel for the install script")?,
                 symlinks: symlinks,
                 chosen_profile: chosen_profile,
-                verbosity: verbosity,
+                verbosity_flags: verbosity_flags,
+                cargo_jobs_flags: cargo_jobs_flags,
+                show_progress: !options.quiet,
                 install_artifact: install_artifact_function,
                 curl_version: curl_version,
+                sha2_version: sha2_version,
                 keep_going: install_keep_going,
+                jobs: jobs,
+                timings: options.timings,
+                timings_file: SCRIPT_TIMINGS_FILE,
             },
         )
         .to_string()
-        .unwrap_or_else(|err| panic!("install script rendering failed: {err}"))
+        .map_err(|err| anyhow!("install script rendering failed: {err:#}"))
 }
 
 type InstalledBinary = String;
 
 /// Returns the names of all packages installed via cargo at the given root.
 ///
-/// Runs `cargo install --list --root <root>` and parses each package header line.
+/// See [get_installed_cargo_packages].
 pub fn get_installed_cargo_binaries(root_dir: PathBuf) -> anyhow::Result<HashSet<InstalledBinary>> {
-    let output = std::process::Command::new("cargo")
+    Ok(get_installed_cargo_packages(root_dir)?.into_keys().collect())
+}
+
+/// Returns every package installed via cargo at the given root, along with the executables it
+/// installed.
+///
+/// Runs `cargo install --list --root <root>` and parses its output.
+pub fn get_installed_cargo_packages(
+    root_dir: PathBuf,
+) -> anyhow::Result<BTreeMap<String, Vec<String>>> {
+    let output = crate::external::cargo_command()
         .arg("install")
         .arg("--root")
         .arg(&root_dir)
@@ -740,23 +1565,592 @@ pub fn get_installed_cargo_binaries(root_dir: PathBuf) -> anyhow::Result<HashSet
         bail!("Failed to obtain binaries installed via cargo {stderr}");
     }
 
+    // The format of cargo install --list is as follows:
+    // <crate> <version>
+    //     <binary>
+    //
+    // e.g.:
+    // ripgrep v15.1.0:
+    //     rg
+    // sccache v0.10.0:
+    //     sccache
     let stdout = String::from_utf8_lossy(&output.stdout);
-    let programs = stdout
-        .lines()
-        // The format of cargo install --list is as follows:
-        // <crate> <version>
-        //     <binary>
-        //
-        // e.g.:
-        // ripgrep v15.1.0:
-        //     rg
-        // sccache v0.10.0:
-        //     sccache
-        .filter(|line| !line.is_empty() && !line.starts_with(char::is_whitespace))
-        // The first item is the name of the crate that we have installed.
-        .filter_map(|line| line.split_whitespace().next())
-        .map(String::from)
-        .collect();
-
-    Ok(programs)
+    let mut packages = BTreeMap::<String, Vec<String>>::new();
+    let mut current_package = None;
+    for line in stdout.lines().filter(|line| !line.trim().is_empty()) {
+        if !line.starts_with(char::is_whitespace) {
+            // The first item is the name of the crate that we have installed.
+            current_package = line.split_whitespace().next();
+            if let Some(package) = current_package {
+                packages.entry(package.to_string()).or_default();
+            }
+        } else if let Some(package) = current_package {
+            packages.entry(package.to_string()).or_default().push(line.trim().to_string());
+        }
+    }
+
+    Ok(packages)
+}
+
+/// The flags limiting the number of jobs of each `cargo install`, when the install script runs
+/// up to `jobs` of them at once on a machine with `cpus` CPUs.
+///
+/// Each `cargo install` uses every CPU by default, so concurrent installs share them instead.
+fn cargo_jobs_flags(jobs: usize, cpus: usize) -> Vec<String> {
+    if jobs <= 1 {
+        return vec![];
+    }
+
+    vec![String::from("--jobs"), (cpus / jobs).max(1).to_string()]
+}
+
+#[cfg(test)]
+mod tests {
+    use tempdir::TempDir;
+
+    use super::{
+        ComponentTiming, InstallTimings, SCRIPT_TIMINGS_FILE, cargo_jobs_flags,
+        components_to_install, execute, generate_install_script, run_post_install_hooks,
+        script_toolchain,
+    };
+    use crate::{
+        channel::Channel,
+        commands::plan::{Execution, Plan, Step},
+        config::{Config, test_config},
+        external::{install_from_source, tee},
+        manifest::Manifest,
+        options::InstallationOptions,
+        profile::Profile,
+    };
+
+    /// The [Plan] of installing `channel`, see [execute].
+    fn plan(
+        config: &Config,
+        channel: &Channel,
+        local_manifest: &Manifest,
+        options: &InstallationOptions,
+    ) -> Plan {
+        let mut execution = Execution::new(true);
+        execute(config, channel, &mut local_manifest.clone(), options, &mut execution).unwrap();
+        execution.into_plan()
+    }
+
+    /// The install script of `channel`, installing every component selected by `options` into
+    /// `toolchain/` in the midenup home.
+    fn install_script(config: &Config, channel: &Channel, options: &InstallationOptions) -> String {
+        let components = components_to_install(channel, options).collect::<Vec<_>>();
+        let install_dir = config.midenup_home.join("toolchain");
+        generate_install_script(config, channel, &components, options, &install_dir).unwrap()
+    }
+
+    /// The cargo manifest embedded in an install script.
+    fn cargo_manifest(script: &str) -> toml::Table {
+        let cargo_manifest = script
+            .split_once("---cargo")
+            .and_then(|(_, rest)| rest.split_once("---"))
+            .map(|(cargo_manifest, _)| cargo_manifest)
+            .expect("install script is missing its cargo manifest");
+        toml::from_str(cargo_manifest).expect("install script's cargo manifest is invalid")
+    }
+
+    /// The entry of the executable component `name` in an install script, up to the executables
+    /// it requires.
+    fn component_section<'a>(script: &'a str, name: &str) -> &'a str {
+        let section = script.split(&format!("name: \"{name}\"")).nth(1).unwrap();
+        section.split("requires:").next().unwrap()
+    }
+
+    /// Git dependencies must render as valid TOML in the install script's cargo manifest, no
+    /// matter how they are pinned.
+    #[test]
+    fn install_script_renders_tag_pinned_library() {
+        let channel: Channel = serde_json::from_str(
+            r#"{
+                "name": "0.15.0",
+                "components": [
+                    {
+                        "name": "std",
+                        "repository_url": "https://github.com/0xMiden/miden-vm.git",
+                        "crate_name": "miden-stdlib",
+                        "tag": "v0.15.0",
+                        "installed_library": "std.masp",
+                        "library_struct": "miden_stdlib::StdLibrary"
+                    }
+                ]
+            }"#,
+        )
+        .expect("invalid channel");
+
+        let home = TempDir::new("install_script_renders_tag_pinned_library").unwrap();
+        let config = test_config(home.path().to_path_buf());
+
+        let script = install_script(&config, &channel, &InstallationOptions::default());
+
+        let cargo_manifest = cargo_manifest(&script);
+
+        let dependency = &cargo_manifest["dependencies"]["miden-stdlib"];
+        assert_eq!(dependency["git"].as_str(), Some("https://github.com/0xMiden/miden-vm.git"));
+        assert_eq!(dependency["tag"].as_str(), Some("v0.15.0"));
+    }
+
+    /// Components that disable their default features in the manifest are built without them,
+    /// be they executables or libraries.
+    #[test]
+    fn install_script_disables_default_features() {
+        let channel: Channel = serde_json::from_str(
+            r#"{
+                "name": "0.15.0",
+                "components": [
+                    {
+                        "name": "std",
+                        "package": "miden-stdlib",
+                        "version": "0.15.0",
+                        "default_features": false,
+                        "installed_library": "std.masp",
+                        "library_struct": "miden_stdlib::StdLibrary"
+                    },
+                    {
+                        "name": "vm",
+                        "package": "miden-vm",
+                        "version": "0.15.0",
+                        "default_features": false
+                    },
+                    {
+                        "name": "client",
+                        "package": "miden-client-cli",
+                        "version": "0.9.0"
+                    }
+                ]
+            }"#,
+        )
+        .expect("invalid channel");
+
+        let home = TempDir::new("install_script_disables_default_features").unwrap();
+        let config = test_config(home.path().to_path_buf());
+        let options = InstallationOptions {
+            profile: Profile::Complete,
+            ..Default::default()
+        };
+
+        let script = install_script(&config, &channel, &options);
+
+        let cargo_manifest = cargo_manifest(&script);
+        assert_eq!(
+            cargo_manifest["dependencies"]["miden-stdlib"]["default-features"].as_bool(),
+            Some(false)
+        );
+
+        let vm = component_section(&script, "vm");
+        assert!(vm.contains("\"--no-default-features\","), "{vm}");
+        let client = component_section(&script, "client");
+        assert!(!client.contains("--no-default-features"), "{client}");
+    }
+
+    /// Components that name a binary only install that one, and their executable is named after
+    /// it.
+    #[test]
+    fn install_script_selects_binary() {
+        let channel: Channel = serde_json::from_str(
+            r#"{
+                "name": "0.15.0",
+                "components": [
+                    {
+                        "name": "client",
+                        "package": "miden-client-cli",
+                        "version": "0.9.0",
+                        "bin": "miden-client"
+                    },
+                    {
+                        "name": "vm",
+                        "package": "miden-vm",
+                        "version": "0.15.0"
+                    }
+                ]
+            }"#,
+        )
+        .expect("invalid channel");
+
+        let home = TempDir::new("install_script_selects_binary").unwrap();
+        let config = test_config(home.path().to_path_buf());
+        let options = InstallationOptions {
+            profile: Profile::Complete,
+            ..Default::default()
+        };
+
+        let script = install_script(&config, &channel, &options);
+
+        let client = component_section(&script, "client");
+        assert!(client.contains("\"--bin\",\n            \"miden-client\","), "{client}");
+        let client = channel.get_component("client").unwrap();
+        assert_eq!(client.get_installed_file().to_string(), "miden-client");
+        let vm = component_section(&script, "vm");
+        assert!(!vm.contains("--bin"), "{vm}");
+    }
+
+    /// With `--frozen`, every component built from source is installed with `cargo install
+    /// --frozen`, whatever its source.
+    #[test]
+    fn install_script_propagates_frozen() {
+        let channel: Channel = serde_json::from_str(
+            r#"{
+                "name": "0.15.0",
+                "components": [
+                    {
+                        "name": "vm",
+                        "package": "miden-vm",
+                        "version": "0.15.0"
+                    },
+                    {
+                        "name": "client",
+                        "repository_url": "https://github.com/0xMiden/miden-client",
+                        "crate_name": "miden-client-cli",
+                        "branch": "main"
+                    },
+                    {
+                        "name": "compiler",
+                        "path": "/path/to/compiler",
+                        "crate_name": "midenc"
+                    }
+                ]
+            }"#,
+        )
+        .expect("invalid channel");
+
+        let home = TempDir::new("install_script_propagates_frozen").unwrap();
+        let config = test_config(home.path().to_path_buf());
+        let executable_args = |options: &InstallationOptions| {
+            let script = install_script(&config, &channel, options);
+            ["vm", "client", "compiler"].map(|name| component_section(&script, name).to_string())
+        };
+
+        let options = InstallationOptions {
+            profile: Profile::Complete,
+            frozen: true,
+            ..Default::default()
+        };
+        for args in executable_args(&options) {
+            assert!(args.contains("\"--frozen\","), "{args}");
+        }
+
+        let options = InstallationOptions { frozen: false, ..options };
+        for args in executable_args(&options) {
+            assert!(!args.contains("--frozen"), "{args}");
+        }
+    }
+
+    /// Crates.io components are taken from the registry passed with `--registry`, while git
+    /// components are left alone.
+    #[test]
+    fn install_script_uses_registry() {
+        let channel: Channel = serde_json::from_str(
+            r#"{
+                "name": "0.15.0",
+                "components": [
+                    {
+                        "name": "std",
+                        "package": "miden-stdlib",
+                        "version": "0.15.0",
+                        "installed_library": "std.masp",
+                        "library_struct": "miden_stdlib::StdLibrary"
+                    },
+                    {
+                        "name": "vm",
+                        "package": "miden-vm",
+                        "version": "0.15.0"
+                    },
+                    {
+                        "name": "client",
+                        "repository_url": "https://github.com/0xMiden/miden-client.git",
+                        "crate_name": "miden-client-cli",
+                        "branch": "main"
+                    }
+                ]
+            }"#,
+        )
+        .expect("invalid channel");
+
+        let home = TempDir::new("install_script_uses_registry").unwrap();
+        let config = test_config(home.path().to_path_buf());
+        let options = InstallationOptions {
+            profile: Profile::Complete,
+            registry: Some(String::from("mirror")),
+            ..Default::default()
+        };
+
+        let script = install_script(&config, &channel, &options);
+
+        let cargo_manifest = cargo_manifest(&script);
+        assert_eq!(
+            cargo_manifest["dependencies"]["miden-stdlib"]["registry"].as_str(),
+            Some("mirror")
+        );
+
+        let vm = component_section(&script, "vm");
+        assert!(vm.contains("\"--registry\",\n            \"mirror\","), "{vm}");
+        let client = component_section(&script, "client");
+        assert!(!client.contains("--registry"), "{client}");
+    }
+
+    /// The install script runs with the nightly toolchain its library components ask for.
+    #[test]
+    fn script_toolchain_follows_library_components() {
+        let channel = |toolchains: [Option<&str>; 2]| -> Channel {
+            let mut channel: Channel = serde_json::from_str(
+                r#"{
+                    "name": "0.15.0",
+                    "components": [
+                        {
+                            "name": "std",
+                            "package": "miden-stdlib",
+                            "version": "0.15.0",
+                            "installed_library": "std.masp",
+                            "library_struct": "miden_stdlib::StdLibrary"
+                        },
+                        {
+                            "name": "base",
+                            "package": "miden-lib",
+                            "version": "0.10.0",
+                            "installed_library": "base.masp",
+                            "library_struct": "miden_lib::MidenLib"
+                        },
+                        {
+                            "name": "vm",
+                            "package": "miden-vm",
+                            "version": "0.15.0",
+                            "rustup_channel": "1.85.0"
+                        }
+                    ]
+                }"#,
+            )
+            .expect("invalid channel");
+            for (component, toolchain) in channel.components.iter_mut().zip(toolchains) {
+                component.rustup_channel = toolchain.map(String::from);
+            }
+            channel
+        };
+        let options = InstallationOptions::default();
+
+        assert_eq!(script_toolchain(&channel([None, None]), &options).unwrap(), "nightly");
+        assert_eq!(
+            script_toolchain(&channel([Some("nightly-2025-12-10"), None]), &options).unwrap(),
+            "nightly-2025-12-10"
+        );
+
+        let err = script_toolchain(&channel([Some("stable"), None]), &options).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("library components 'std' require rust toolchain 'stable'")
+        );
+
+        let err = script_toolchain(
+            &channel([Some("nightly-2025-12-10"), Some("nightly-2026-01-01")]),
+            &options,
+        )
+        .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("nightly-2025-12-10: 'std'\nnightly-2026-01-01: 'base'")
+        );
+    }
+
+    /// Concurrent `cargo install`s share the machine's CPUs.
+    #[test]
+    fn cargo_jobs_are_split_among_installs() {
+        assert!(cargo_jobs_flags(1, 16).is_empty());
+        assert_eq!(cargo_jobs_flags(4, 16), ["--jobs", "4"]);
+        assert_eq!(cargo_jobs_flags(3, 8), ["--jobs", "2"]);
+        assert_eq!(cargo_jobs_flags(8, 4), ["--jobs", "1"]);
+    }
+
+    /// Forced installs start from an empty install directory.
+    #[test]
+    fn forced_install_plan_removes_install_dir() {
+        let channel: Channel = serde_json::from_str(
+            r#"{
+                "name": "0.15.0",
+                "components": [
+                    {
+                        "name": "vm",
+                        "package": "miden-vm",
+                        "version": "0.15.0"
+                    }
+                ]
+            }"#,
+        )
+        .expect("invalid channel");
+
+        let home = TempDir::new("forced_install_plan_removes_install_dir").unwrap();
+        let install_dir = home.path().join("installed_toolchains").join(format!(
+            "{}-{}",
+            channel.name,
+            channel.content_hash()
+        ));
+        std::fs::create_dir_all(&install_dir).unwrap();
+        let config = test_config(home.path().to_path_buf());
+        let local_manifest = Manifest::default();
+
+        let options = InstallationOptions::default();
+        let steps = plan(&config, &channel, &local_manifest, &options).steps;
+        assert!(!steps.iter().any(|step| matches!(step, Step::RemoveDirectory { .. })));
+
+        let options = InstallationOptions { force: true, ..Default::default() };
+        let steps = plan(&config, &channel, &local_manifest, &options).steps;
+        assert_eq!(steps.first(), Some(&Step::RemoveDirectory { path: install_dir }));
+    }
+
+    /// Resuming an interrupted install only installs the components that are still missing.
+    #[test]
+    fn interrupted_install_plan_skips_installed_components() {
+        let channel: Channel = serde_json::from_str(
+            r#"{
+                "name": "0.15.0",
+                "components": [
+                    {
+                        "name": "vm",
+                        "package": "miden-vm",
+                        "version": "0.15.0",
+                        "installed_executable": "miden-vm"
+                    },
+                    {
+                        "name": "client",
+                        "package": "miden-client-cli",
+                        "version": "0.9.0",
+                        "installed_executable": "miden-client"
+                    }
+                ]
+            }"#,
+        )
+        .expect("invalid channel");
+
+        let home = TempDir::new("interrupted_install_plan_skips_installed_components").unwrap();
+        let install_dir = home.path().join("installed_toolchains").join(format!(
+            "{}-{}",
+            channel.name,
+            channel.content_hash()
+        ));
+        std::fs::create_dir_all(install_dir.join("bin")).unwrap();
+        std::fs::write(install_dir.join("bin").join("miden-vm"), "").unwrap();
+        let config = test_config(home.path().to_path_buf());
+        let local_manifest = Manifest::default();
+
+        let installed = |steps: &[Step]| {
+            steps
+                .iter()
+                .filter_map(|step| match step {
+                    Step::CargoInstall { component, .. } => Some(component.clone()),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let options = InstallationOptions::default();
+        let steps = plan(&config, &channel, &local_manifest, &options).steps;
+        assert_eq!(installed(&steps), ["client"]);
+
+        let options = InstallationOptions { force: true, ..Default::default() };
+        let steps = plan(&config, &channel, &local_manifest, &options).steps;
+        assert_eq!(installed(&steps), ["vm", "client"]);
+    }
+
+    /// The timings recorded by the install script are read back along with the total duration.
+    #[test]
+    fn install_timings_are_loaded() {
+        let dir = TempDir::new("install_timings_are_loaded").unwrap();
+        let install_dir = dir.path();
+
+        // Nothing is recorded if every component was already installed.
+        let timings = InstallTimings::load(install_dir, std::time::Duration::from_secs(3)).unwrap();
+        assert!(timings.components.is_empty());
+        assert_eq!(timings.total_seconds, 3.0);
+
+        std::fs::write(install_dir.join(SCRIPT_TIMINGS_FILE), "std 0.5\nmidenc 120.25\n").unwrap();
+        let timings =
+            InstallTimings::load(install_dir, std::time::Duration::from_secs(150)).unwrap();
+        assert_eq!(
+            timings.components,
+            [
+                ComponentTiming { name: String::from("std"), seconds: 0.5 },
+                ComponentTiming {
+                    name: String::from("midenc"),
+                    seconds: 120.25
+                },
+            ]
+        );
+
+        std::fs::write(install_dir.join(SCRIPT_TIMINGS_FILE), "std\n").unwrap();
+        assert!(InstallTimings::load(install_dir, std::time::Duration::ZERO).is_err());
+    }
+
+    /// The install script's output is forwarded as is, and written to the install log as well.
+    #[test]
+    fn tee_writes_output_to_log() {
+        let dir = TempDir::new("tee_writes_output_to_log").unwrap();
+        let log_path = dir.path().join("install.log");
+        let log = std::sync::Mutex::new(std::fs::File::create(&log_path).unwrap());
+
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        tee(&b"info: installing vm\n"[..], &mut stdout, &log);
+        tee(&b"error: could not compile\n"[..], &mut stderr, &log);
+
+        assert_eq!(stdout, b"info: installing vm\n");
+        assert_eq!(stderr, b"error: could not compile\n");
+        assert_eq!(
+            std::fs::read_to_string(&log_path).unwrap(),
+            "info: installing vm\nerror: could not compile\n"
+        );
+    }
+
+    /// cargo's errors end up in the component's log, which the returned error points to.
+    #[test]
+    fn install_from_source_writes_component_log() {
+        let dir = TempDir::new("install_from_source_writes_component_log").unwrap();
+        let log_path = dir.path().join("vm.log");
+
+        let err = install_from_source(
+            "+stable",
+            &["--profile", "release"],
+            &["--quiet"],
+            &[],
+            &["--midenup-nonexistent-flag"],
+            dir.path(),
+            &log_path,
+        )
+        .unwrap_err();
+
+        assert!(err.contains(&log_path.display().to_string()), "{err}");
+        let log = std::fs::read_to_string(&log_path).unwrap();
+        assert!(log.contains("error"), "{log}");
+    }
+
+    /// Post-install hooks run in order, and the first one that fails stops the rest.
+    #[test]
+    fn post_install_hooks_stop_at_first_failure() {
+        let channel: Channel = serde_json::from_str(
+            r#"{
+                "name": "0.15.0",
+                "components": [],
+                "post_install": [
+                    ["touch", "var_path", "marker"],
+                    ["false"],
+                    ["touch", "var_path", "unreachable"]
+                ]
+            }"#,
+        )
+        .expect("invalid channel");
+
+        let home = TempDir::new("post_install_hooks_stop_at_first_failure").unwrap();
+        let install_dir = home.path().join("toolchain");
+        std::fs::create_dir_all(install_dir.join("var")).unwrap();
+        let config = test_config(home.path().to_path_buf());
+
+        let err = run_post_install_hooks(&config, &channel, &install_dir, &mut Execution::Live)
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "post-install hook `false` of toolchain 0.15.0 failed with status 1"
+        );
+        assert!(install_dir.join("var").join("marker").exists());
+        assert!(!install_dir.join("var").join("unreachable").exists());
+    }
 }
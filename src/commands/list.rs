@@ -1,15 +1,34 @@
 use colored::Colorize;
 
-use crate::{config::Config, manifest::Manifest};
+use crate::{channel::ChannelAlias, config::Config, manifest::Manifest, options::ListOptions};
 
 /// List all the available [[Channels]] presents in the upstream manifest.
-pub fn list(config: &Config, local_manifest: &Manifest) {
-    let upstream_channels = config.manifest.get_channels();
+///
+/// Channels are marked as installed if they are present in the `local_manifest`.
+pub fn list(config: &Config, local_manifest: &Manifest, options: &ListOptions) {
+    let stable_toolchain = config.manifest.get_latest_stable();
+    let upstream_channels = config.manifest.get_channels().filter(|channel| {
+        // With neither filter set, every channel is listed.
+        options.stable == options.nightly
+            || (options.stable && channel.is_stable())
+            || (options.nightly && channel.is_nightly())
+    });
 
     let toolchains_display: Vec<String> = upstream_channels
         .map(|channel| {
             let channel_name = &channel.name;
 
+            let channel_kind = match &channel.alias {
+                Some(ChannelAlias::Nightly(None)) => format!(" {}", "(nightly)".bold()),
+                Some(ChannelAlias::Nightly(Some(suffix))) => {
+                    format!(" {}", format!("(nightly-{suffix})").bold())
+                },
+                _ if stable_toolchain.is_some_and(|stable| stable.name == channel.name) => {
+                    format!(" {}", "(stable)".bold())
+                },
+                _ => String::new(),
+            };
+
             let installed_indicator = if local_manifest.get_channel_by_name(&channel.name).is_some()
             {
                 format!(" {}", "(installed)".green())
@@ -17,7 +36,7 @@ pub fn list(config: &Config, local_manifest: &Manifest) {
                 String::new()
             };
 
-            format!("{channel_name}{installed_indicator}")
+            format!("{channel_name}{channel_kind}{installed_indicator}")
         })
         .collect();
 
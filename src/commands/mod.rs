@@ -1,30 +1,65 @@
+mod clean;
+mod completions;
+mod doctor;
+mod export;
+mod gc;
 mod init;
 mod install;
 mod list;
 mod r#override;
+mod pin;
+mod plan;
+mod repair;
+mod self_update;
 mod set;
+mod settings;
 mod show;
 mod uninstall;
 mod update;
+mod which;
 
 use std::{ffi::OsString, path::PathBuf};
 
 use anyhow::{Context, anyhow, bail};
 use clap::{ArgAction, Args, Parser, Subcommand};
+use colored::Colorize;
+use thiserror::Error;
 
 pub use self::{
+    clean::clean,
+    completions::completions,
+    doctor::doctor,
+    export::{Lockfile, export, import},
+    gc::gc,
     init::{init, setup_midenup},
-    install::install,
+    install::{install, reinstall},
     list::list,
     r#override::r#override,
+    pin::{pin, unpin},
+    plan::{Plan, Step},
+    repair::repair_manifest,
+    self_update::SelfCommand,
     set::set,
+    settings::SettingsCommand,
     show::ShowCommand,
     uninstall::uninstall,
     update::{ComponentUpdate, update},
+    which::which,
+};
+use crate::{
+    channel, config, manifest, miden_wrapper, options,
+    toolchain::{Toolchain, ToolchainJustification},
 };
-use crate::{channel, config, manifest, options};
 
 pub const MIDENUP_MANIFEST_URI_ENV: &str = "MIDENUP_MANIFEST_URI";
+pub const MIDENUP_OFFLINE_ENV: &str = crate::external::OFFLINE_ENV;
+
+/// Returned by commands whose outcome is reported through midenup's exit code, e.g.
+/// [show::OUTDATED_EXIT_CODE]. Everything the command had to print was already printed, so it is
+/// not an error to report: midenup just exits with this code.
+#[derive(Error, Debug)]
+#[error("exit code {0}")]
+pub struct ExitCode(pub i32);
 
 #[derive(Debug, Parser)]
 #[command(
@@ -42,6 +77,7 @@ pub struct Midenup {
 
 /// What set of behavior the CLI should exhibit
 #[derive(Debug, Subcommand)]
+#[allow(clippy::large_enum_variant)]
 enum Behavior {
     /// The Miden toolchain installer
     Midenup {
@@ -63,20 +99,36 @@ struct GlobalArgs {
     pub midenup_home: Option<PathBuf>,
     #[arg(long, hide(true), value_name = "DIR", env = "CARGO_HOME")]
     pub cargo_home: Option<PathBuf>,
-    /// The URI from which we should load the global toolchain manifest
-    #[arg(
-        long,
-        hide(true),
-        value_name = "FILE",
-        env = MIDENUP_MANIFEST_URI_ENV,
-        default_value = manifest::Manifest::PUBLISHED_MANIFEST_URI
-    )]
-    pub manifest_uri: String,
+    /// The URI from which we should load the global toolchain manifest. Defaults to the value of
+    /// `MIDENUP_MANIFEST_URI`, or to the published manifest.
+    #[arg(long, hide(true), value_name = "FILE")]
+    pub manifest_uri: Option<String>,
     /// Determines wether the components are installed in debug mode. Useful for
     /// debugging and faster installations. This flag is only avaialble to
     /// `midenup`, not `miden`.
     #[arg(env = "MIDENUP_DEBUG_MODE", action = ArgAction::Set, default_value = "false", hide = true)]
     pub debug: bool,
+    /// Forbid network access. Manifests must then be loaded from `file://` URIs, and components
+    /// can only be installed from source (or be already installed).
+    ///
+    /// Also enabled by setting `MIDENUP_OFFLINE`, e.g. to `1` or `true`.
+    ///
+    /// Note that cargo must be told to work offline separately, e.g. with
+    /// `CARGO_NET_OFFLINE=true`.
+    #[arg(long, action = ArgAction::SetTrue)]
+    pub offline: bool,
+    /// Fetch the upstream manifest again, even if the cached copy has not expired yet. The
+    /// cached copy expires after `MIDENUP_MANIFEST_TTL` seconds (one hour by default).
+    #[arg(long, action = ArgAction::SetTrue)]
+    pub refresh: bool,
+    /// The format of the messages printed by `install` and `update`. With `json`,
+    /// newline-delimited JSON events are printed to stdout, and everything else to stderr.
+    #[arg(long, value_enum, value_name = "FORMAT", default_value = "human")]
+    pub message_format: options::MessageFormat,
+    /// When to color the output. With `auto`, colors are disabled when stdout is not a terminal,
+    /// or when `NO_COLOR` is set.
+    #[arg(long, value_enum, value_name = "WHEN", default_value = "auto")]
+    pub color: options::ColorChoice,
     /// Display verbose output, mainly used during install.
     #[arg(short, long, action, default_value_t = false)]
     pub verbose: bool,
@@ -95,19 +147,99 @@ enum Commands {
     /// Install a Miden toolchain
     Install {
         /// The channel or version to install, e.g. `stable` or `0.15.0`
-        #[arg(required(true), value_name = "CHANNEL", value_parser)]
-        channel: channel::UserChannel,
+        #[arg(required_unless_present = "toolchain_file", value_name = "CHANNEL", value_parser)]
+        channel: Option<channel::UserChannel>,
+
+        /// Install the channel and components selected by a `miden-toolchain.toml` file instead,
+        /// e.g. the one of a project being set up.
+        #[arg(long, value_name = "FILE", conflicts_with_all = ["channel", "components", "profile"])]
+        toolchain_file: Option<PathBuf>,
+
+        /// Load the upstream manifest from this file, just for this installation. Either a path or
+        /// a `file://` URI, e.g. `./channel-manifest.json`.
+        ///
+        /// Takes precedence over `MIDENUP_MANIFEST_URI`.
+        #[arg(long, value_name = "FILE")]
+        manifest: Option<String>,
 
         #[clap(flatten)]
         options: options::InstallationOptions,
     },
-    /// List all available toolchains
-    List,
+    /// Print a lockfile of the active toolchain to stdout, e.g. `midenup export > miden.lock`.
+    ///
+    /// The lockfile records the exact crate version, git revision or path that every installed
+    /// component of the toolchain came from, so that `midenup import` can install the very same
+    /// toolchain on another machine.
+    Export,
+    /// Install the toolchain described by a lockfile written by `midenup export`.
+    ///
+    /// Components are installed exactly as locked, ignoring `midenup pin`.
+    Import {
+        /// The lockfile to install, e.g. `miden.lock`
+        #[arg(value_name = "LOCKFILE")]
+        lockfile: PathBuf,
+        /// Displays the entirety of cargo's output when performing installations. Pass it twice
+        /// (`-vv`) to run cargo with `--verbose` as well.
+        #[arg(long, short, action = ArgAction::Count)]
+        verbose: u8,
+        /// Don't report the progress of artifact downloads.
+        #[arg(long, short, action)]
+        quiet: bool,
+        /// The maximum number of components to install concurrently. Defaults to 1.
+        ///
+        /// When greater than 1, the CPUs are split among the concurrent `cargo install`s.
+        #[arg(long, short)]
+        jobs: Option<std::num::NonZeroUsize>,
+        /// Print what would be installed, without changing anything.
+        #[arg(long, action)]
+        dry_run: bool,
+    },
+    /// Reinstall an installed toolchain from scratch, e.g. when it got corrupted.
+    ///
+    /// The toolchain keeps exactly the components it was installed with, including when only some
+    /// of them were installed, e.g. via a `miden-toolchain.toml` file.
+    Reinstall {
+        /// The channel or version to reinstall, e.g. `stable` or `0.15.0`
+        #[arg(required(true), value_name = "CHANNEL", value_parser)]
+        channel: channel::UserChannel,
+        /// Displays the entirety of cargo's output when performing installations. Pass it twice
+        /// (`-vv`) to run cargo with `--verbose` as well.
+        #[arg(long, short, action = ArgAction::Count)]
+        verbose: u8,
+        /// Don't report the progress of artifact downloads.
+        #[arg(long, short, action)]
+        quiet: bool,
+        /// The maximum number of components to install concurrently. Defaults to 1.
+        ///
+        /// When greater than 1, the CPUs are split among the concurrent `cargo install`s.
+        #[arg(long, short)]
+        jobs: Option<std::num::NonZeroUsize>,
+        /// Print what would be reinstalled, without changing anything.
+        #[arg(long, action)]
+        dry_run: bool,
+    },
+    /// List all the toolchains available upstream, marking the installed ones
+    List {
+        #[clap(flatten)]
+        options: options::ListOptions,
+    },
     /// Uninstall a Miden toolchain
     Uninstall {
         /// The channel or version to install, e.g. `stable` or `0.15.0`
-        #[arg(required(true), value_name = "CHANNEL", value_parser)]
-        channel: channel::UserChannel,
+        #[arg(required_unless_present("all"), value_name = "CHANNEL", value_parser)]
+        channel: Option<channel::UserChannel>,
+        /// Only uninstall this component, leaving the rest of the toolchain installed
+        #[arg(long, value_name = "COMPONENT")]
+        component: Option<String>,
+        /// Uninstall every installed toolchain, and unset the stable and default toolchains
+        #[arg(long, action, conflicts_with_all(["channel", "component"]))]
+        all: bool,
+        /// Don't ask for confirmation before uninstalling every toolchain
+        #[arg(long, short, action, requires("all"))]
+        yes: bool,
+        /// Print what would be uninstalled, without changing anything.
+        #[arg(long, action)]
+        dry_run: bool,
     },
     /// Show information about the local midenup environment.
     #[command(subcommand)]
@@ -116,8 +248,11 @@ enum Commands {
     /// This creates a miden-toolchain.toml file in the present working directory.
     Set {
         /// The channel or version to set, e.g. `stable` or `0.15.0`
-        #[arg(required(true), value_name = "CHANNEL", value_parser)]
-        channel: channel::UserChannel,
+        #[arg(required_unless_present("unset"), value_name = "CHANNEL", value_parser)]
+        channel: Option<channel::UserChannel>,
+        /// Remove the miden-toolchain.toml file from the present working directory instead
+        #[arg(long, action, conflicts_with("channel"))]
+        unset: bool,
     },
     /// Sets the system's default toolchain.
     ///
@@ -129,6 +264,98 @@ enum Commands {
         #[arg(required(true), value_name = "CHANNEL", value_parser)]
         channel: channel::UserChannel,
     },
+    /// Pin a component to a specific version of its crate, in every toolchain.
+    ///
+    /// The pinned version is installed instead of the one in the channel manifest. Only components
+    /// installed from crates.io can be pinned. Installed toolchains are changed by the next
+    /// `midenup update`.
+    Pin {
+        /// The name of the component, e.g. `midenc`
+        #[arg(value_name = "COMPONENT")]
+        component: String,
+        /// The version of the component's crate to install, e.g. `0.1.5`
+        #[arg(value_name = "VERSION")]
+        version: semver::Version,
+    },
+    /// Remove the pin of a component set via `midenup pin`.
+    Unpin {
+        /// The name of the component, e.g. `midenc`
+        #[arg(value_name = "COMPONENT")]
+        component: String,
+    },
+    /// Run a component of the current Miden toolchain, e.g. `midenup run vm -- prove foo.masm`.
+    ///
+    /// Unlike `miden <COMPONENT>`, aliases are not taken into account: COMPONENT always refers to
+    /// a component, even if an alias with the same name exists.
+    Run {
+        /// The name of the component, e.g. `vm`
+        #[arg(value_name = "COMPONENT")]
+        component: String,
+        /// The arguments passed to the component, verbatim
+        #[arg(last(true), value_name = "ARGS")]
+        args: Vec<OsString>,
+    },
+    /// Print the command that `miden <NAME>` runs in the current Miden toolchain, e.g. `midenup
+    /// which vm`.
+    ///
+    /// Nothing gets installed, so the current toolchain must already be installed.
+    Which {
+        /// The name of the alias or component, e.g. `vm`
+        #[arg(value_name = "NAME", required_unless_present = "all")]
+        name: Option<String>,
+        /// Print every component and alias of the toolchain instead, along with their commands
+        #[arg(long, action, conflicts_with = "name")]
+        all: bool,
+        /// Print the output as JSON
+        #[arg(long, action)]
+        json: bool,
+    },
+    /// Remove leftovers of interrupted installs, updates and uninstalls.
+    ///
+    /// This removes unused toolchain install directories, dangling symlinks and local manifest
+    /// entries of toolchains that are not installed.
+    Clean {
+        /// Don't ask for confirmation before removing anything
+        #[arg(long, short, action)]
+        yes: bool,
+    },
+    /// Remove the files of installed toolchains which don't belong to any of their components.
+    ///
+    /// This removes executables, libraries and `opt/` symlinks left behind by failed installs,
+    /// along with packages installed with cargo which no component comes from.
+    Gc {
+        /// Don't ask for confirmation before removing anything
+        #[arg(long, short, action)]
+        yes: bool,
+    },
+    /// Manage the midenup installation itself.
+    #[command(name = "self", subcommand)]
+    SelfCommand(SelfCommand),
+    /// Manage the defaults stored in `$MIDENUP_HOME/config.toml`.
+    ///
+    /// Settings are overridden by the corresponding command line flags and environment
+    /// variables, and fall back to midenup's built-in defaults when unset.
+    #[command(name = "config", subcommand)]
+    Settings(SettingsCommand),
+    /// Print a completion script for the given shell to stdout.
+    ///
+    /// The channels of the toolchains that are installed when the script is generated are offered
+    /// as completions wherever an installed toolchain is expected.
+    Completions {
+        /// The shell to generate the completion script for
+        #[arg(value_enum)]
+        shell: clap_complete::Shell,
+    },
+    /// Diagnose common problems with the local midenup environment.
+    ///
+    /// Prints a checklist with the result of every check, and exits with an error if any of them
+    /// failed.
+    Doctor,
+    /// Rebuild a corrupt local manifest from the installed toolchains.
+    ///
+    /// Installed toolchains are matched against the upstream manifest, keeping the components
+    /// which are present. The corrupt manifest is kept as `manifest.json.corrupt`.
+    RepairManifest,
     /// Update your installed Miden toolchains.
     Update {
         /// `midenup update`'s behavior differs depending on the specified [CHANNEL]
@@ -145,67 +372,231 @@ enum Commands {
 }
 
 impl Commands {
+    /// Whether this command only prints what it would do, see `--dry-run`.
+    fn is_dry_run(&self) -> bool {
+        match self {
+            Self::Install { options, .. } => options.dry_run,
+            Self::Uninstall { dry_run, .. } => *dry_run,
+            Self::Update { options, .. } => options.dry_run || options.check,
+            Self::Import { dry_run, .. } => *dry_run,
+            Self::Reinstall { dry_run, .. } => *dry_run,
+            _ => false,
+        }
+    }
+
     /// Execute the requested subcommand
     pub fn execute(
         &self,
         config: &config::Config,
         local_manifest: &mut manifest::Manifest,
     ) -> anyhow::Result<()> {
+        // Commands that modify the installed toolchains or the local manifest must not run
+        // concurrently with other midenup processes. Since another process might have changed
+        // the local manifest while we waited for the lock, it is reloaded afterwards.
+        let _lock = match &self {
+            // Dry runs don't change anything.
+            _ if self.is_dry_run() => None,
+            Self::Install { .. }
+            | Self::Import { .. }
+            | Self::Reinstall { .. }
+            | Self::Uninstall { .. }
+            | Self::Update { .. }
+            | Self::Clean { .. }
+            | Self::Gc { .. }
+            | Self::Pin { .. }
+            | Self::Unpin { .. }
+            | Self::Override { .. }
+            | Self::Init => {
+                let lock = config.lock()?;
+                *local_manifest = config.local_manifest()?;
+                // A dangling `stable` symlink would otherwise make toolchain resolution fail in
+                // confusing ways. Failing to repair it is not a reason to fail the command.
+                match clean::repair_toolchain_symlinks(config, local_manifest) {
+                    Ok(repairs) => {
+                        for repair in repairs {
+                            eprintln!("{}: {repair}", "warn".yellow());
+                        }
+                    },
+                    Err(err) => eprintln!("{}: {err:#}", "warn".yellow()),
+                }
+                Some(lock)
+            },
+            _ => None,
+        };
+
         match &self {
             Self::Init => {
                 init(config, local_manifest)?;
                 Ok(())
             },
-            Self::List => {
-                list(config, local_manifest);
+            Self::List { options } => {
+                list(config, local_manifest, options);
                 Ok(())
             },
-            Self::Install { channel, options } => {
-                let Some(channel) = config.manifest.get_channel(channel) else {
-                    bail!("channel '{}' doesn't exist or is unavailable", channel);
+            Self::Install { channel, toolchain_file, options, .. } => {
+                let mut channel = match (channel, toolchain_file) {
+                    (_, Some(path)) => {
+                        let toolchain = Toolchain::from_toolchain_file(path)?;
+                        let Some(channel) = config.manifest.get_channel(&toolchain.channel) else {
+                            bail!(
+                                "channel '{}' is set in {}, however the channel doesn't exist or \
+                                 is unavailable",
+                                toolchain.channel,
+                                path.display()
+                            );
+                        };
+                        channel.validate()?;
+                        let justification =
+                            ToolchainJustification::MidenToolchainFile { path: path.clone() };
+                        channel
+                            .create_subset(&toolchain, &justification)
+                            .unwrap_or_else(|| channel.clone())
+                    },
+                    (Some(channel), None) => {
+                        let Some(channel) = config.manifest.get_channel(channel) else {
+                            bail!("channel '{}' doesn't exist or is unavailable", channel);
+                        };
+                        channel.validate()?;
+                        if options.components.is_empty() {
+                            channel.clone()
+                        } else {
+                            let partial_channel = channel.select_components(
+                                options.components.iter().map(String::as_str),
+                                None,
+                            );
+                            if partial_channel.components.is_empty() {
+                                bail!(
+                                    "none of the requested components are available in channel \
+                                     '{}'",
+                                    channel.name
+                                );
+                            }
+                            partial_channel
+                        }
+                    },
+                    (None, None) => unreachable!("clap requires a channel or a toolchain file"),
                 };
-                install(config, channel, local_manifest, options)
+                for options::ComponentOverride { component, path } in &options.overrides {
+                    channel.override_with_path(component, path.clone())?;
+                }
+                for options::ComponentFeature { component, feature } in &options.features {
+                    channel.enable_feature(component, feature)?;
+                }
+                for component in &options.no_default_features {
+                    channel.disable_default_features(component)?;
+                }
+                install(config, &channel, local_manifest, options)
+            },
+            Self::Export => export(config, local_manifest),
+            Self::Import { lockfile, verbose, quiet, jobs, dry_run } => {
+                let options = options::InstallationOptions {
+                    verbose: *verbose,
+                    quiet: *quiet,
+                    jobs: *jobs,
+                    dry_run: *dry_run,
+                    registry: options::registry_from_env(),
+                    ..Default::default()
+                };
+                import(config, local_manifest, lockfile, &options)
+            },
+            Self::Reinstall { channel, verbose, quiet, jobs, dry_run } => {
+                let options = options::InstallationOptions {
+                    verbose: *verbose,
+                    quiet: *quiet,
+                    jobs: *jobs,
+                    dry_run: *dry_run,
+                    registry: options::registry_from_env(),
+                    ..Default::default()
+                };
+                reinstall(config, channel, local_manifest, &options)
+            },
+            Self::Uninstall { channel: None, dry_run, yes, .. } => {
+                if *dry_run {
+                    uninstall::plan_all(config, local_manifest)?.print_dry_run(config);
+                    return Ok(());
+                }
+                uninstall::uninstall_all(config, local_manifest, *yes)
             },
-            Self::Uninstall { channel, .. } => {
-                let Some(channel) = config.manifest.get_channel(channel) else {
+            Self::Uninstall {
+                channel: Some(channel),
+                component,
+                dry_run,
+                ..
+            } => {
+                // Toolchains that are no longer available upstream can still be uninstalled.
+                let Some(channel) = config
+                    .manifest
+                    .get_channel(channel)
+                    .or_else(|| local_manifest.get_channel(channel))
+                    .cloned()
+                else {
                     bail!("channel '{}' doesn't exist or is unavailable", channel);
                 };
-                uninstall(config, channel, local_manifest)
+                let channel = &channel;
+                if *dry_run {
+                    let plan = match component {
+                        Some(component) => {
+                            uninstall::plan_component(config, channel, component, local_manifest)?
+                        },
+                        None => uninstall::plan(config, channel, local_manifest)?,
+                    };
+                    plan.print_dry_run(config);
+                    return Ok(());
+                }
+                match component {
+                    Some(component) => {
+                        uninstall::uninstall_component(config, channel, component, local_manifest)
+                    },
+                    None => uninstall(config, channel, local_manifest),
+                }
             },
             Self::Update { channel, options } => {
                 update(config, channel.as_ref(), local_manifest, options)
             },
             Self::Show(cmd) => cmd.execute(config, local_manifest),
-            Self::Set { channel } => set(config, channel),
+            Self::Completions { shell } => completions(local_manifest, *shell),
+            Self::Doctor => doctor(config),
+            Self::RepairManifest => {
+                repair_manifest(config)?;
+                *local_manifest = config.local_manifest()?;
+                Ok(())
+            },
+            Self::Clean { yes } => clean(config, local_manifest, *yes),
+            Self::Gc { yes } => gc(config, local_manifest, *yes),
+            Self::Pin { component, version } => pin(config, local_manifest, component, version),
+            Self::Unpin { component } => unpin(config, local_manifest, component),
+            Self::Run { component, args } => {
+                miden_wrapper::run_component(component, args, config, local_manifest)
+            },
+            Self::Which { name, all: _, json } => {
+                which(config, local_manifest, name.as_deref(), *json)
+            },
+            Self::SelfCommand(cmd) => cmd.execute(config),
+            Self::Settings(cmd) => cmd.execute(config),
+            Self::Set { channel, unset } => match channel {
+                Some(channel) if !*unset => set(config, channel),
+                _ => set::unset(config),
+            },
             Self::Override { channel } => r#override(config, local_manifest, channel),
         }
     }
 }
 
 impl Midenup {
+    /// When to color the output, as requested with `--color`.
+    pub fn color(&self) -> options::ColorChoice {
+        match &self.behavior {
+            Behavior::Miden(argv) => miden_wrapper::color_argument(argv),
+            Behavior::Midenup { config, .. } => config.color,
+        }
+    }
+
     /// Get the effective configuration for the current session
     pub fn config(&self) -> anyhow::Result<config::Config> {
         let working_directory =
             std::env::current_dir().context("unable to read current directory")?;
         match &self.behavior {
-            Behavior::Miden(_) => {
-                // Always respect XDG dirs if set
-                let midenup_home = std::env::var_os("XDG_DATA_HOME")
-                    .map(PathBuf::from)
-                    .map(|dir| dir.join("midenup"))
-                    .or_else(|| dirs::data_dir().map(|dir| dir.join("midenup")))
-                    // If for whatever reason, we can't access the data dir, we fall
-                    // back to .local/share
-                    .or_else(|| {
-                        dirs::home_dir()
-                            .map(|home| home.join(".local").join("share"))
-                    })
-                    .ok_or_else(||
-                                anyhow!("Failed to set midenup directory.\
-                                        Consider setting a value for XDG_DATA_HOME in your shell's profile"
-                                )
-                    )?;
-
+            Behavior::Miden(argv) => {
                 let cargo_home = std::env::var_os("CARGO_HOME")
                     .map(PathBuf::from)
                     .or_else(|| dirs::home_dir().map(|home| home.join(".cargo")))
@@ -215,39 +606,20 @@ impl Midenup {
                              your shell's profile"
                         )
                     })?;
+                let midenup_home = config::Config::resolve_home(None)?;
 
-                let manifest_uri = std::env::var(MIDENUP_MANIFEST_URI_ENV)
-                    .unwrap_or(manifest::Manifest::PUBLISHED_MANIFEST_URI.to_string());
+                let manifest_uri = manifest_uri(miden_wrapper::manifest_uri_argument(argv));
                 config::Config::init(
                     working_directory,
                     midenup_home,
                     cargo_home,
                     manifest_uri,
                     false,
+                    crate::external::offline_from_env(),
+                    false,
                 )
             },
-            Behavior::Midenup { config, .. } => {
-                let midenup_home = config
-                    .midenup_home
-                    .clone()
-                    .or_else(|| {
-                        // Always respect XDG dirs if set
-                        std::env::var_os("XDG_DATA_HOME")
-                            .map(PathBuf::from)
-                            .map(|dir| dir.join("midenup"))
-                    })
-                    .or_else(|| dirs::data_dir().map(|dir| dir.join("midenup")))
-                    // If for whatever reason, we can't access the data dir, we fall
-                    // back to .local/share
-                    .or_else(|| {
-                        dirs::home_dir()
-                            .map(|home| home.join(".local").join("share"))
-                    })
-                    .ok_or_else(||
-                                anyhow!("Failed to set midenup directory.\
-                                        Consider setting a value for XDG_DATA_HOME in your shell's profile"
-                                )
-                    )?;
+            Behavior::Midenup { config, command } => {
                 let cargo_home = config
                     .cargo_home
                     .clone()
@@ -259,21 +631,54 @@ impl Midenup {
                              your shell's profile"
                         )
                     })?;
+                let midenup_home = config::Config::resolve_home(config.midenup_home.clone())?;
+                let manifest_argument = match command {
+                    Some(Commands::Install { manifest: Some(manifest), .. }) => {
+                        Some(manifest_file_uri(manifest))
+                    },
+                    _ => config.manifest_uri.clone(),
+                };
 
-                config::Config::init(
+                let mut session_config = config::Config::init(
                     working_directory,
                     midenup_home,
                     cargo_home,
-                    &config.manifest_uri,
+                    manifest_uri(manifest_argument),
                     config.debug,
-                )
+                    config.offline || crate::external::offline_from_env(),
+                    config.refresh,
+                )?;
+                session_config.message_format = config.message_format;
+
+                Ok(session_config)
             },
         }
     }
 
     /// Execute this session with the provided configuration.
     pub fn execute(&self, config: &config::Config) -> anyhow::Result<()> {
-        let mut local_manifest = config.local_manifest()?;
+        // `doctor` checks the local manifest itself, and `repair-manifest` rebuilds it, so they
+        // have to run even if it fails to load.
+        match &self.behavior {
+            Behavior::Midenup { command: Some(Commands::Doctor), .. } => return doctor(config),
+            Behavior::Midenup {
+                command: Some(Commands::RepairManifest), ..
+            } => {
+                return repair_manifest(config);
+            },
+            _ => (),
+        }
+
+        let mut local_manifest = config.local_manifest().map_err(|err| {
+            if let Some(manifest::ManifestError::Invalid(_)) = err.downcast_ref() {
+                err.context(
+                    "the local manifest is corrupt. Run `midenup repair-manifest` to rebuild it \
+                     from the installed toolchains",
+                )
+            } else {
+                err
+            }
+        })?;
 
         self.execute_with_manifest(config, &mut local_manifest)
     }
@@ -284,7 +689,10 @@ impl Midenup {
         config: &config::Config,
         local_manifest: &mut manifest::Manifest,
     ) -> anyhow::Result<()> {
-        use crate::miden_wrapper;
+        let dry_run = matches!(
+            &self.behavior,
+            Behavior::Midenup { command: Some(command), .. } if command.is_dry_run()
+        );
 
         match &self.behavior {
             Behavior::Miden(argv) => {
@@ -305,12 +713,53 @@ impl Midenup {
         // After execution we check if need to update the midenup/opt symlink
         // This is done *after* execution because some commands change what the active toolchain
         // (update, set) and some remove the directory entirely (uninstall)
-        config.update_opt_symlinks(config)?;
+        if !dry_run {
+            config.update_opt_symlinks(config)?;
+        }
 
         Ok(())
     }
 }
 
+/// Resolves the URI of the upstream manifest, for both `midenup` and `miden`.
+///
+/// A URI passed with `--manifest-uri` takes precedence over the `MIDENUP_MANIFEST_URI`
+/// environment variable. If neither is set, [config::Config::init] falls back to the
+/// `manifest-uri` setting, and then to [manifest::Manifest::PUBLISHED_MANIFEST_URI].
+fn manifest_uri(argument: Option<String>) -> Option<String> {
+    argument.or_else(|| std::env::var(MIDENUP_MANIFEST_URI_ENV).ok())
+}
+
+/// Turns the manifest passed to `midenup install --manifest` into a URI that
+/// [manifest::Manifest::load_from] accepts. Plain paths become `file://` URIs.
+fn manifest_file_uri(manifest: &str) -> String {
+    if manifest.starts_with("file://") {
+        manifest.to_string()
+    } else {
+        format!("file://{manifest}")
+    }
+}
+
+/// Writes `local_manifest` to `$MIDENUP_HOME/manifest.json`, recording when it was written.
+///
+/// The manifest is written to a temporary file first, which is then renamed over
+/// `manifest.json`. So if midenup gets interrupted, the previous manifest is left intact rather
+/// than half-written.
+///
+/// This is the only place where the local manifest gets written, see
+/// [config::Config::local_manifest] for where it is read.
+pub(crate) fn save_local_manifest(
+    config: &config::Config,
+    local_manifest: &mut manifest::Manifest,
+) -> anyhow::Result<()> {
+    let local_manifest_path = config.midenup_home.join("manifest").with_extension("json");
+    local_manifest.update_last_modified();
+    let contents = serde_json::to_string_pretty(&local_manifest)
+        .context("Couldn't serialize local manifest")?;
+    crate::utils::fs::write_atomically(&local_manifest_path, contents.as_bytes())
+        .context("Couldn't write local manifest file")
+}
+
 fn get_full_command(argv: &[OsString]) -> String {
     use core::fmt::Write;
 
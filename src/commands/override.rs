@@ -6,13 +6,16 @@ use crate::{
     commands,
     config::Config,
     manifest::Manifest,
-    toolchain::{Toolchain, ToolchainJustification},
+    toolchain::{DEFAULT_TOOLCHAIN_FILE_NAME, Toolchain, ToolchainFile, ToolchainJustification},
     utils,
 };
 
 /// This functions sets the system's default toolchain. This is handled similarly to how we handle
 /// the `stable`. We create a symlink called `default` that points to the desired toolchain
 /// directory.
+///
+/// The toolchain is also written to the [DEFAULT_TOOLCHAIN_FILE_NAME] file, which is used when the
+/// symlink can't be created.
 // This function requires raw identifier syntax because "override" is a reserved keyword.
 // Source: https://doc.rust-lang.org/reference/keywords.html#r-lex.keywords.reserved
 pub fn r#override(
@@ -42,7 +45,8 @@ pub fn r#override(
     };
 
     let default_path = toolchains_dir.join("default");
-    if default_path.exists() {
+    // NOTE: `exists` follows symlinks, so it would miss a dangling `default` symlink.
+    if default_path.symlink_metadata().is_ok() {
         std::fs::remove_file(&default_path)
             .context("failed to remove 'default' toolchain symlink")?;
     }
@@ -58,7 +62,22 @@ This will take prescedence over the configuration done by `midenup override`.",
             active.channel
         );
     };
-    utils::fs::symlink(&default_path, &channel_dir)?;
+
+    let default_toolchain_file = config.midenup_home.join(DEFAULT_TOOLCHAIN_FILE_NAME);
+    let default_toolchain = ToolchainFile::new(Toolchain::new(channel.clone(), None, vec![]));
+    let default_toolchain_contents = toml::to_string_pretty(&default_toolchain)
+        .context("failed to generate default toolchain file")?;
+    std::fs::write(&default_toolchain_file, default_toolchain_contents).with_context(|| {
+        format!("failed to write default toolchain file '{}'", default_toolchain_file.display())
+    })?;
+
+    if let Err(err) = utils::fs::symlink(&default_path, &channel_dir) {
+        println!(
+            "{}: {err:#}, the default toolchain will be read from {} instead",
+            "warn".yellow(),
+            default_toolchain_file.display()
+        );
+    }
 
     Ok(())
 }
@@ -0,0 +1,64 @@
+use anyhow::bail;
+use colored::Colorize;
+
+use crate::{commands, config::Config, manifest::Manifest, version::Authority};
+
+/// Pins `component` to `version` of its crate. The pinned version is used instead of the
+/// channel's whenever the component gets installed, in every toolchain.
+///
+/// Toolchains that are already installed are only changed by the next `midenup update`.
+pub fn pin(
+    config: &Config,
+    local_manifest: &mut Manifest,
+    component: &str,
+    version: &semver::Version,
+) -> anyhow::Result<()> {
+    // Only components installed from crates.io have a version that can be overridden.
+    let is_cargo_component = config
+        .manifest
+        .get_channels()
+        .filter_map(|channel| channel.get_component(component))
+        .any(|component| {
+            matches!(
+                component.version,
+                Authority::Cargo { .. } | Authority::CargoRequirement { .. }
+            )
+        });
+    if !is_cargo_component {
+        bail!(
+            "component '{component}' is not installed from crates.io in any channel, so it can't \
+             be pinned"
+        );
+    }
+
+    local_manifest.pin(component.to_string(), version.clone());
+    commands::save_local_manifest(config, local_manifest)?;
+
+    println!(
+        "{}: pinned {component} to version {version}. Run `midenup update` to apply it to the \
+         installed toolchains",
+        "info".white().bold()
+    );
+
+    Ok(())
+}
+
+/// Removes the pin of `component`, set via [pin].
+pub fn unpin(
+    config: &Config,
+    local_manifest: &mut Manifest,
+    component: &str,
+) -> anyhow::Result<()> {
+    let Some(version) = local_manifest.unpin(component) else {
+        bail!("component '{component}' is not pinned");
+    };
+    commands::save_local_manifest(config, local_manifest)?;
+
+    println!(
+        "{}: unpinned {component} from version {version}. Run `midenup update` to apply it to the \
+         installed toolchains",
+        "info".white().bold()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,223 @@
+use std::{fmt, path::PathBuf};
+
+use colored::Colorize;
+
+use crate::{channel::Component, config::Config, version::Authority};
+
+/// The changes that an install, update or uninstall is going to make, computed before anything is
+/// changed. See `--dry-run`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Plan {
+    pub steps: Vec<Step>,
+}
+
+/// A single change that is part of a [Plan].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Step {
+    /// Install an executable component from source, with `cargo install`.
+    CargoInstall {
+        component: String,
+        source: String,
+    },
+    /// Build a library component from source.
+    BuildLibrary {
+        component: String,
+        source: String,
+    },
+    /// Download the pre-built artifact of a component.
+    DownloadArtifact {
+        component: String,
+        uri: String,
+    },
+    /// Remove the files of an installed component.
+    RemoveComponent {
+        component: String,
+    },
+    CreateSymlink {
+        link: PathBuf,
+        target: PathBuf,
+    },
+    RemoveSymlink {
+        link: PathBuf,
+    },
+    RemoveDirectory {
+        path: PathBuf,
+    },
+    RemoveFile {
+        path: PathBuf,
+    },
+    /// Run one of the post-install hooks of a toolchain.
+    RunHook {
+        command: String,
+    },
+    /// Add a toolchain to the local manifest, marking it as the stable toolchain if `stable` is
+    /// set.
+    AddChannel {
+        channel: String,
+        stable: bool,
+    },
+    /// Replace a toolchain already present in the local manifest.
+    UpdateChannel {
+        channel: String,
+    },
+    /// Remove a toolchain from the local manifest.
+    RemoveChannel {
+        channel: String,
+    },
+}
+
+impl Plan {
+    pub fn push(&mut self, step: Step) {
+        self.steps.push(step);
+    }
+
+    pub fn extend(&mut self, plan: Plan) {
+        self.steps.extend(plan.steps);
+    }
+
+    /// Prints this plan in place of executing it.
+    pub fn print_dry_run(&self, config: &Config) {
+        if self.steps.is_empty() {
+            config.status(format!("{}: nothing to do", "dry run".bold()));
+            return;
+        }
+
+        config.status(format!("{}: no changes were made, midenup would:", "dry run".bold()));
+        config.status(self);
+    }
+}
+
+/// How the steps of an install, update or uninstall are gone through: either carried out, or only
+/// recorded into a [Plan] for `--dry-run`.
+///
+/// Both go through the same code, so that a dry run describes exactly what would be done.
+#[derive(Debug)]
+pub enum Execution {
+    /// Every step is carried out.
+    Live,
+    /// The steps are only recorded, nothing is changed.
+    DryRun(Plan),
+}
+
+impl Execution {
+    pub fn new(dry_run: bool) -> Self {
+        if dry_run {
+            Self::DryRun(Plan::default())
+        } else {
+            Self::Live
+        }
+    }
+
+    pub fn is_dry_run(&self) -> bool {
+        matches!(self, Self::DryRun(_))
+    }
+
+    /// Carries out `step` by running `action`, or only records it on a dry run.
+    pub fn step(
+        &mut self,
+        step: Step,
+        action: impl FnOnce() -> anyhow::Result<()>,
+    ) -> anyhow::Result<()> {
+        match self {
+            Self::Live => action(),
+            Self::DryRun(_) => {
+                self.record(step);
+                Ok(())
+            },
+        }
+    }
+
+    /// Records `step` on a dry run, for steps which are carried out as part of another action,
+    /// e.g. the components installed by the install script.
+    ///
+    /// A step which was already recorded, e.g. removing the `stable` symlink when uninstalling
+    /// every toolchain, is not recorded twice.
+    pub fn record(&mut self, step: Step) {
+        if let Self::DryRun(plan) = self
+            && !plan.steps.contains(&step)
+        {
+            plan.push(step);
+        }
+    }
+
+    /// Returns the recorded plan, which is empty unless this is a dry run.
+    pub fn into_plan(self) -> Plan {
+        match self {
+            Self::Live => Plan::default(),
+            Self::DryRun(plan) => plan,
+        }
+    }
+
+    /// Prints the recorded plan if this is a dry run, see [Plan::print_dry_run].
+    pub fn finish(self, config: &Config) {
+        if let Self::DryRun(plan) = self {
+            plan.print_dry_run(config);
+        }
+    }
+}
+
+impl fmt::Display for Plan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, step) in self.steps.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "  - {step}")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Step::CargoInstall { component, source } => {
+                write!(f, "cargo install {} from {source}", component.bold())
+            },
+            Step::BuildLibrary { component, source } => {
+                write!(f, "build library {} from {source}", component.bold())
+            },
+            Step::DownloadArtifact { component, uri } => {
+                write!(f, "download {} from {uri}", component.bold())
+            },
+            Step::RemoveComponent { component } => write!(f, "remove {}", component.bold()),
+            Step::CreateSymlink { link, target } => {
+                write!(f, "create symlink {} -> {}", link.display(), target.display())
+            },
+            Step::RemoveSymlink { link } => write!(f, "remove symlink {}", link.display()),
+            Step::RemoveDirectory { path } => write!(f, "remove directory {}", path.display()),
+            Step::RemoveFile { path } => write!(f, "remove file {}", path.display()),
+            Step::RunHook { command } => write!(f, "run post-install hook `{command}`"),
+            Step::AddChannel { channel, stable: false } => {
+                write!(f, "add toolchain {channel} to the local manifest")
+            },
+            Step::AddChannel { channel, stable: true } => {
+                write!(f, "add toolchain {channel} to the local manifest, as stable")
+            },
+            Step::UpdateChannel { channel } => {
+                write!(f, "update toolchain {channel} in the local manifest")
+            },
+            Step::RemoveChannel { channel } => {
+                write!(f, "remove toolchain {channel} from the local manifest")
+            },
+        }
+    }
+}
+
+/// Describes where `component` is installed from when built from source.
+pub(super) fn describe_source(component: &Component) -> String {
+    match &component.version {
+        Authority::Cargo { package, version } => {
+            let package = package.as_deref().unwrap_or(component.name.as_ref());
+            format!("crates.io ({package} {version})")
+        },
+        Authority::CargoRequirement { package, requirement } => {
+            let package = package.as_deref().unwrap_or(component.name.as_ref());
+            format!("crates.io ({package} {requirement})")
+        },
+        Authority::Git { repository_url, crate_name, target } => {
+            format!("{repository_url} ({crate_name}, {target})")
+        },
+        Authority::Path { path, .. } => path.display().to_string(),
+    }
+}
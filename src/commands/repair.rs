@@ -0,0 +1,114 @@
+use anyhow::Context;
+use colored::Colorize;
+
+use crate::{
+    channel::{Channel, Tags},
+    commands,
+    config::Config,
+    manifest::Manifest,
+};
+
+/// Name of the file in which toolchains installed before midenup 1.0.1 recorded their channel.
+const LEGACY_CHANNEL_FILE_NAME: &str = ".installed_channel.json";
+
+/// Rebuilds the local manifest from the installed toolchains, for when it got corrupted.
+///
+/// Every `toolchains/<version>` entry is recovered from its `.installed_channel.json` file, which
+/// toolchains installed before midenup 1.0.1 have. Otherwise, it is recovered from the upstream
+/// channel of the same version, keeping the components whose executable or library is present in
+/// the toolchain. Toolchains which are in neither can't be recovered, and are skipped.
+///
+/// The corrupt manifest is kept as `manifest.json.corrupt`. Pins are not recovered.
+pub fn repair_manifest(config: &Config) -> anyhow::Result<()> {
+    let _lock = config.lock()?;
+
+    let local_manifest_path = config.midenup_home.join("manifest").with_extension("json");
+    if let Ok(local_manifest) = config.local_manifest()
+        && local_manifest.get_channels().next().is_some()
+    {
+        println!("{}: the local manifest is valid, nothing to repair", "info".white().bold());
+        return Ok(());
+    }
+
+    let toolchains_dir = config.midenup_home.join("toolchains");
+    let mut local_manifest = Manifest::default();
+    if toolchains_dir.exists() {
+        let entries = std::fs::read_dir(&toolchains_dir).with_context(|| {
+            format!("failed to read toolchains directory '{}'", toolchains_dir.display())
+        })?;
+        let mut versions = Vec::new();
+        for entry in entries {
+            let entry = entry.with_context(|| {
+                format!("failed to read entry in '{}'", toolchains_dir.display())
+            })?;
+            // `stable` and `default` point to one of the versions.
+            if let Some(version) =
+                entry.file_name().to_str().and_then(|name| semver::Version::parse(name).ok())
+            {
+                versions.push(version);
+            }
+        }
+        versions.sort_by(|a, b| a.cmp_precedence(b));
+
+        for version in versions {
+            match recover_channel(config, &version)? {
+                Some(channel) => {
+                    println!(
+                        "{}: recovered toolchain {version} with {} component(s)",
+                        "info".white().bold(),
+                        channel.components.len()
+                    );
+                    local_manifest.add_channel(channel);
+                },
+                None => println!(
+                    "{}: toolchain {version} is not in the upstream manifest, it can't be \
+                     recovered",
+                    "warn".yellow()
+                ),
+            }
+        }
+    }
+
+    if local_manifest_path.exists() {
+        let backup = local_manifest_path.with_extension("json.corrupt");
+        std::fs::rename(&local_manifest_path, &backup).with_context(|| {
+            format!("failed to rename {} -> {}", local_manifest_path.display(), backup.display())
+        })?;
+        println!(
+            "{}: the previous local manifest was kept at {}",
+            "info".white().bold(),
+            backup.display()
+        );
+    }
+    commands::save_local_manifest(config, &mut local_manifest)?;
+
+    println!("{}: rebuilt the local manifest", "info".white().bold());
+    Ok(())
+}
+
+/// Reconstructs the local manifest entry of the toolchain installed as `toolchains/<version>`.
+fn recover_channel(config: &Config, version: &semver::Version) -> anyhow::Result<Option<Channel>> {
+    let channel_dir = config.midenup_home.join("toolchains").join(version.to_string());
+
+    let legacy_channel_file = channel_dir.join(LEGACY_CHANNEL_FILE_NAME);
+    if legacy_channel_file.exists() {
+        let contents = std::fs::read_to_string(&legacy_channel_file)
+            .with_context(|| format!("failed to read '{}'", legacy_channel_file.display()))?;
+        let channel = serde_json::from_str(&contents)
+            .with_context(|| format!("invalid channel in '{}'", legacy_channel_file.display()))?;
+        return Ok(Some(channel));
+    }
+
+    let Some(upstream) = config.manifest.get_channel_by_name(version) else {
+        return Ok(None);
+    };
+    let mut channel = upstream.clone();
+    channel
+        .components
+        .retain(|component| component.get_installed_file().get_path_from(&channel_dir).exists());
+    if channel.components.len() < upstream.components.len() {
+        channel.tags.push(Tags::Partial);
+    }
+
+    Ok(Some(channel))
+}
@@ -0,0 +1,144 @@
+use anyhow::{Context, bail};
+use clap::Subcommand;
+use colored::Colorize;
+
+use crate::{
+    artifact::TargetTriple,
+    commands::init::miden_symlink_path,
+    config::Config,
+    external::{TransferError, configure_timeouts, install_artifact, retry_with_backoff},
+    utils,
+};
+
+/// Where the latest published version of midenup is looked up.
+const CRATES_IO_URI: &str = "https://crates.io/api/v1/crates/midenup";
+/// Where the prebuilt midenup binaries are published. Binaries follow the same naming scheme as
+/// component artifacts: `v<version>/midenup-<target triple>`.
+const RELEASES_URI: &str = "https://github.com/0xMiden/midenup/releases/download";
+
+#[derive(Debug, Subcommand)]
+pub enum SelfCommand {
+    /// Update midenup to the latest published version
+    Update,
+}
+
+impl SelfCommand {
+    pub fn execute(&self, config: &Config) -> anyhow::Result<()> {
+        match self {
+            Self::Update => self_update(config),
+        }
+    }
+}
+
+/// Replaces the running `midenup` executable with the latest version published on crates.io.
+///
+/// The new binary is downloaded next to the current executable, and then renamed over it, so
+/// that the `miden` symlink created by [crate::commands::setup_midenup] keeps pointing to a valid
+/// executable.
+fn self_update(config: &Config) -> anyhow::Result<()> {
+    if config.offline {
+        bail!("midenup can't update itself in offline mode");
+    }
+
+    let current_version = semver::Version::parse(env!("CARGO_PKG_VERSION"))
+        .expect("midenup's version is valid semver");
+    let latest_version = latest_published_version()?;
+
+    if latest_version <= current_version {
+        println!("{}: midenup is already up to date ({current_version})", "info".white().bold());
+        return Ok(());
+    }
+
+    let TargetTriple::Custom(target) = &config.target else {
+        bail!("unable to determine the target triple of this system");
+    };
+
+    let current_exe = std::env::current_exe()
+        .and_then(|exe| exe.canonicalize())
+        .context("unable to get location of current executable")?;
+    let new_exe = current_exe.with_extension("new");
+    let uri = format!("{RELEASES_URI}/v{latest_version}/midenup-{target}");
+
+    println!(
+        "{}: updating midenup from {current_version} to {latest_version}",
+        "info".white().bold()
+    );
+    install_artifact("midenup", &uri, "", &new_exe, true).map_err(|err| {
+        anyhow::anyhow!(
+            "failed to download midenup {latest_version}: {err}\nYou can update it by running \
+             `cargo install midenup --force` instead"
+        )
+    })?;
+
+    // Renaming is atomic as long as both files are on the same filesystem, which is why the new
+    // binary is placed next to the current one.
+    std::fs::rename(&new_exe, &current_exe).with_context(|| {
+        format!("failed to replace '{}' with '{}'", current_exe.display(), new_exe.display())
+    })?;
+
+    // The `miden` symlink points to the path of the executable, so it normally survives the
+    // update. It is recreated in case it went missing.
+    let miden_symlink = miden_symlink_path(&config.cargo_home);
+    if std::fs::symlink_metadata(&miden_symlink).is_err()
+        && miden_symlink.parent().is_some_and(|bin| bin.exists())
+    {
+        utils::fs::symlink(&miden_symlink, &current_exe)?;
+    }
+
+    println!(
+        "{}: midenup was updated from {current_version} to {latest_version}",
+        "info".white().bold()
+    );
+
+    Ok(())
+}
+
+/// Queries crates.io for the latest stable version of midenup.
+fn latest_published_version() -> anyhow::Result<semver::Version> {
+    let data = retry_with_backoff(|| {
+        let mut data = Vec::new();
+        let mut handle = curl::easy::Easy::new();
+        // crates.io rejects requests without a user agent.
+        handle
+            .useragent(concat!("midenup/", env!("CARGO_PKG_VERSION")))
+            .and_then(|_| configure_timeouts(&mut handle))
+            .and_then(|_| handle.url(CRATES_IO_URI))
+            .map_err(|err| TransferError::Permanent(format!("failed to setup curl: {err}")))?;
+
+        {
+            let mut transfer = handle.transfer();
+            transfer
+                .write_function(|new_data| {
+                    data.extend_from_slice(new_data);
+                    Ok(new_data.len())
+                })
+                .unwrap();
+            transfer.perform().map_err(|error| {
+                let err = if error.is_operation_timedout() {
+                    format!("timed out reaching crates.io: {error}")
+                } else {
+                    format!("failed to reach crates.io: {error}")
+                };
+                TransferError::from_curl(&error, err)
+            })?;
+        }
+
+        match handle.response_code().unwrap_or_default() {
+            200..300 => Ok(data),
+            code @ 500..600 => {
+                Err(TransferError::Transient(format!("crates.io returned status {code}")))
+            },
+            code => Err(TransferError::Permanent(format!("crates.io returned status {code}"))),
+        }
+    })
+    .map_err(anyhow::Error::msg)?;
+
+    let response: serde_json::Value =
+        serde_json::from_slice(&data).context("crates.io returned an invalid response")?;
+    let version = response["crate"]["max_stable_version"]
+        .as_str()
+        .context("crates.io response does not contain midenup's latest version")?;
+
+    semver::Version::parse(version)
+        .with_context(|| format!("crates.io returned an invalid version for midenup: {version}"))
+}
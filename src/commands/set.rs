@@ -1,6 +1,6 @@
 use std::io::Write;
 
-use anyhow::Context;
+use anyhow::{Context, bail};
 
 use crate::{
     channel::UserChannel,
@@ -31,3 +31,20 @@ pub fn set(config: &Config, channel: &UserChannel) -> anyhow::Result<()> {
         .context("failed to write miden-toolchain.toml")?;
     Ok(())
 }
+
+/// Removes the `miden-toolchain.toml` from the present working directory, undoing [set].
+///
+/// Files in parent directories are left alone, even though they might still apply to the present
+/// working directory.
+pub fn unset(config: &Config) -> anyhow::Result<()> {
+    let toolchain_file_path = config.working_directory.join(TOOLCHAIN_FILE_NAME);
+    if !toolchain_file_path.exists() {
+        bail!("there is no {TOOLCHAIN_FILE_NAME} in '{}'", config.working_directory.display());
+    }
+
+    std::fs::remove_file(&toolchain_file_path)
+        .with_context(|| format!("failed to remove '{}'", toolchain_file_path.display()))?;
+    println!("Removed {}", toolchain_file_path.display());
+
+    Ok(())
+}
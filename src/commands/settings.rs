@@ -0,0 +1,66 @@
+use clap::Subcommand;
+use colored::Colorize;
+
+use crate::config::{Config, SettingKey, Settings};
+
+#[derive(Debug, Subcommand)]
+pub enum SettingsCommand {
+    /// Print the value of a setting
+    Get {
+        /// The setting to print
+        #[arg(value_enum)]
+        key: SettingKey,
+    },
+    /// Change the value of a setting
+    Set {
+        /// The setting to change
+        #[arg(value_enum)]
+        key: SettingKey,
+        /// Its new value
+        value: String,
+    },
+    /// Unset a setting, so that its built-in default is used again
+    Unset {
+        /// The setting to unset
+        #[arg(value_enum)]
+        key: SettingKey,
+    },
+}
+
+impl SettingsCommand {
+    pub fn execute(&self, config: &Config) -> anyhow::Result<()> {
+        match self {
+            Self::Get { key } => {
+                // Unlike `config.settings`, which falls back to the defaults, this reports invalid
+                // settings.
+                let settings = Settings::load(&config.midenup_home)?;
+                match settings.get(*key) {
+                    Some(value) => println!("{value}"),
+                    None => println!("{}: {} is not set", "info".white().bold(), key.name()),
+                }
+                Ok(())
+            },
+            Self::Set { key, value } => {
+                // The settings are reloaded, since `config.settings` might not reflect the file if
+                // it was changed concurrently.
+                let mut settings = load_or_replace(config);
+                settings.set(*key, value)?;
+                settings.save(&config.midenup_home)
+            },
+            Self::Unset { key } => {
+                let mut settings = load_or_replace(config);
+                settings.unset(*key);
+                settings.save(&config.midenup_home)
+            },
+        }
+    }
+}
+
+/// Loads the settings to modify. Invalid settings are replaced by the defaults, since `config set`
+/// and `config unset` are how they get fixed.
+fn load_or_replace(config: &Config) -> Settings {
+    Settings::load(&config.midenup_home).unwrap_or_else(|err| {
+        eprintln!("{}: {err:#}, they will be replaced", "warn".yellow());
+        Settings::default()
+    })
+}
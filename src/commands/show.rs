@@ -1,12 +1,40 @@
+use std::{collections::BTreeMap, path::Path};
+
+use anyhow::Context;
 use clap::Subcommand;
 use colored::Colorize;
+use serde::Serialize;
 
 use crate::{
+    channel::{Alias, Channel, CliCommands, Component, InstalledFile},
+    commands,
     config::Config,
     manifest::Manifest,
-    toolchain::{Toolchain, ToolchainJustification},
+    miden_wrapper::channel_contents_display,
+    toolchain::{InstallationStatus, Toolchain, ToolchainJustification},
+    utils,
+    version::Authority,
 };
 
+/// The exit code of `midenup show outdated` when some component is outdated.
+pub const OUTDATED_EXIT_CODE: i32 = 100;
+
+/// The exit code of `midenup show active-toolchain --installed` when the active toolchain is not
+/// installed.
+pub const NOT_INSTALLED_EXIT_CODE: i32 = 101;
+
+/// The exit code of `midenup show active-toolchain --installed` when the active toolchain is
+/// installed, but some of its components are not.
+pub const MISSING_COMPONENTS_EXIT_CODE: i32 = 102;
+
+/// An installed component for which a newer version is available upstream.
+struct OutdatedComponent {
+    channel: String,
+    component: String,
+    installed: String,
+    available: String,
+}
+
 #[derive(Debug, Subcommand)]
 pub enum ShowCommand {
     /// Show the active toolchain
@@ -14,20 +42,145 @@ pub enum ShowCommand {
     Current {
         #[arg(long, action)]
         verbose: bool,
+        /// Check whether the active toolchain is installed instead, without installing it.
+        ///
+        /// Exits with code 0 if it is, 101 if it is not, and 102 if some of its components are
+        /// missing.
+        #[arg(long, action, conflicts_with = "verbose")]
+        installed: bool,
     },
     /// Display the computed value of MIDENUP_HOME
-    Home,
+    Home {
+        /// Initialize MIDENUP_HOME if it doesn't exist yet
+        #[arg(long, action)]
+        create: bool,
+        /// Also display the URI of the channel manifest in use, on a second line
+        #[arg(long, action)]
+        manifest_uri: bool,
+    },
     /// List installed toolchains
-    List,
+    List {
+        /// Also list the components of each toolchain, along with the version they were installed
+        /// from.
+        #[arg(long, visible_alias = "verbose", action)]
+        components: bool,
+    },
+    /// List the components pinned via `midenup pin`
+    Pins,
+    /// List the nightly toolchains available upstream or installed, marking the latest one
+    Nightlies,
+    /// List the installed components for which a newer version is available upstream, including
+    /// components installed from a git branch that got new commits.
+    ///
+    /// Nothing is changed. Exits with code 100 if any component is outdated, so that it can be
+    /// used to check for updates in scripts.
+    Outdated,
+    /// Print the directory that holds the executables of the active toolchain, to be prepended to
+    /// `PATH`
+    Path {
+        /// Print a shell command that prepends the directory to `PATH` instead, e.g. for use with
+        /// `eval "$(midenup show path --export)"`
+        #[arg(long, action)]
+        export: bool,
+    },
+    /// Show the configuration midenup resolved from its environment, along with the active
+    /// toolchain
+    Config {
+        /// Print the output as JSON
+        #[arg(long, action)]
+        json: bool,
+    },
+    /// Print the path of the `miden-toolchain.toml` file that selects the active toolchain, if
+    /// any, followed by the channel of the active toolchain on a second line
+    #[command(name = "toolchain-file")]
+    ToolchainFile,
+    /// Show how much disk space each installed toolchain takes, largest first, along with the
+    /// total
+    #[command(name = "disk-usage")]
+    DiskUsage {
+        /// Print the output as JSON
+        #[arg(long, action)]
+        json: bool,
+    },
+    /// Show when the upstream manifest was generated, and when the local manifest was last
+    /// written
+    Manifest,
+    /// Show the aliases, components and libraries available in the active toolchain
+    Components {
+        /// Print the output as JSON
+        #[arg(long, action)]
+        json: bool,
+    },
+}
+
+/// The contents of the active toolchain, as printed by `midenup show components --json`.
+#[derive(Serialize)]
+struct ToolchainContents<'a> {
+    channel: String,
+    installed: bool,
+    aliases: BTreeMap<Alias, CliCommands>,
+    executables: Vec<&'a str>,
+    libraries: Vec<String>,
+    components: &'a [Component],
+}
+
+/// The disk space taken by the installed toolchains, as printed by `midenup show disk-usage
+/// --json`.
+#[derive(Serialize)]
+struct DiskUsage {
+    /// Each installed toolchain along with its size in bytes, largest first.
+    toolchains: Vec<ToolchainDiskUsage>,
+    /// The size of every installed toolchain combined, in bytes.
+    total: u64,
+}
+
+#[derive(Serialize)]
+struct ToolchainDiskUsage {
+    channel: String,
+    size: u64,
+}
+
+/// The resolved configuration, as printed by `midenup show config --json`.
+#[derive(Serialize)]
+struct ConfigContents<'a> {
+    midenup_version: &'static str,
+    git_revision: &'static str,
+    working_directory: &'a Path,
+    midenup_home: &'a Path,
+    cargo_home: &'a Path,
+    manifest_uri: &'a str,
+    debug: bool,
+    offline: bool,
+    target: String,
+    active_toolchain: String,
+    justification: ToolchainJustification,
 }
 
 impl ShowCommand {
     pub fn execute(&self, config: &Config, local_manifest: &Manifest) -> anyhow::Result<()> {
         match self {
-            Self::Current { verbose } => {
+            Self::Current { verbose, installed } => {
                 let (toolchain, justification) = Toolchain::current(config)?;
 
-                if !verbose {
+                if *installed {
+                    match toolchain.installation_status(config, local_manifest, &justification) {
+                        InstallationStatus::Installed => {
+                            println!("{} is installed", toolchain.channel);
+                        },
+                        InstallationStatus::MissingComponents(missing) => {
+                            println!(
+                                "{} is installed, but not its components {}",
+                                toolchain.channel,
+                                missing.join(", ")
+                            );
+                            return Err(commands::ExitCode(MISSING_COMPONENTS_EXIT_CODE).into());
+                        },
+                        InstallationStatus::NotInstalled => {
+                            println!("{} is not installed", toolchain.channel);
+                            return Err(commands::ExitCode(NOT_INSTALLED_EXIT_CODE).into());
+                        },
+                    }
+                } else if !verbose {
                     println!("{}", &toolchain.channel);
                 } else {
                     match justification {
@@ -50,39 +203,364 @@ impl ShowCommand {
                                 "info".white().bold()
                             );
                         },
+                        ToolchainJustification::CommandLine => {
+                            println!(
+                                "{}: current toolchain was passed as `+{}`",
+                                "info".white().bold(),
+                                &toolchain.channel
+                            );
+                        },
                     }
                     println!("The current active toolchain is {}", &toolchain.channel);
                 }
 
                 Ok(())
             },
-            Self::Home => {
-                println!("{}", config.midenup_home.display());
+            Self::ToolchainFile => {
+                let (toolchain, justification) = Toolchain::current(config)?;
+
+                match justification {
+                    ToolchainJustification::MidenToolchainFile { path } => {
+                        println!("{}", path.display())
+                    },
+                    ToolchainJustification::Override => {
+                        println!("none; using the system default set via `midenup override`")
+                    },
+                    ToolchainJustification::Default => {
+                        println!("none; using the default toolchain")
+                    },
+                    ToolchainJustification::CommandLine => {
+                        println!("none; using the toolchain passed as `+<channel>`")
+                    },
+                }
+                println!("{}", &toolchain.channel);
 
                 Ok(())
             },
-            Self::List => {
-                let channels = local_manifest.get_channels();
-                let stable_toolchain = config.manifest.get_latest_stable();
+            Self::Pins => {
+                if local_manifest.pins().is_empty() {
+                    println!("{}: no components are pinned", "info".white().bold());
+                }
+                for (component, version) in local_manifest.pins() {
+                    println!("{component} {version}");
+                }
+
+                Ok(())
+            },
+            Self::Nightlies => {
+                // Installed nightlies may no longer be available upstream.
+                let mut nightlies = config
+                    .manifest
+                    .get_channels()
+                    .chain(local_manifest.get_channels().filter(|channel| {
+                        config.manifest.get_channel_by_name(&channel.name).is_none()
+                    }))
+                    .filter(|channel| channel.is_nightly())
+                    .collect::<Vec<_>>();
+                if nightlies.is_empty() {
+                    println!("{}: no nightly toolchains are available", "info".white().bold());
+                    return Ok(());
+                }
+                nightlies.sort_by(|a, b| a.name.cmp_precedence(&b.name));
+
+                let latest = config.manifest.get_latest_nightly();
+                println!("{}", "Nightly toolchains:".bold().underline());
+                for channel in nightlies {
+                    let mut line = format!("{}", channel.name);
+                    if let Some(alias) = &channel.alias {
+                        line.push_str(&format!(" {}", format!("({alias})").bold()));
+                    }
+                    if latest.is_some_and(|latest| latest.name == channel.name) {
+                        line.push_str(&format!(" {}", "(latest)".bold()));
+                    }
+                    if local_manifest.get_channel_by_name(&channel.name).is_some() {
+                        line.push_str(&format!(" {}", "(installed)".green()));
+                    }
+                    println!("{line}");
+                }
+
+                Ok(())
+            },
+            Self::Outdated => {
+                let mut outdated = Vec::new();
+                for channel in local_manifest.get_channels() {
+                    outdated.extend(outdated_components(config, channel, local_manifest)?);
+                }
+
+                if outdated.is_empty() {
+                    println!("{}: all components are up to date", "info".white().bold());
+                    return Ok(());
+                }
 
-                let toolchains_display: Vec<_> = channels
-                    .map(|channel| {
-                        (
-                            &channel.name,
-                            stable_toolchain
-                                .as_ref()
-                                .is_some_and(|stable| stable.name == channel.name),
-                        )
+                let headers = ["Toolchain", "Component", "Installed", "Available"];
+                let rows = outdated
+                    .into_iter()
+                    .map(|outdated| {
+                        [
+                            outdated.channel,
+                            outdated.component,
+                            outdated.installed,
+                            outdated.available,
+                        ]
                     })
-                    .map(|(channel_name, is_stable)| match (channel_name, is_stable) {
-                        (name, false) => format!("{name}"),
-                        (name, true) => format!("{name} {}", "(stable)".bold()),
+                    .collect::<Vec<_>>();
+                let widths: [usize; 4] = core::array::from_fn(|column| {
+                    rows.iter()
+                        .map(|row| row[column].chars().count())
+                        .chain([headers[column].len()])
+                        .max()
+                        .unwrap_or_default()
+                });
+
+                let [w0, w1, w2, _] = widths;
+                let [h0, h1, h2, h3] = headers;
+                println!("{}", format!("{h0:<w0$}  {h1:<w1$}  {h2:<w2$}  {h3}").bold());
+                for [channel, component, installed, available] in rows {
+                    println!("{channel:<w0$}  {component:<w1$}  {installed:<w2$}  {available}");
+                }
+
+                Err(commands::ExitCode(OUTDATED_EXIT_CODE).into())
+            },
+            Self::Home { create, manifest_uri } => {
+                if *create {
+                    commands::setup_midenup(config, local_manifest)?;
+                }
+
+                println!("{}", config.midenup_home.display());
+                if *manifest_uri {
+                    println!("{}", config.manifest_uri);
+                }
+
+                Ok(())
+            },
+            Self::Config { json } => {
+                let (toolchain, justification) = Toolchain::current(config)?;
+                let contents = ConfigContents {
+                    midenup_version: env!("CARGO_PKG_VERSION"),
+                    // NOTE: This file is generated in the project's build.rs.
+                    git_revision: include_str!(concat!(env!("OUT_DIR"), "/git_revision.in")).trim(),
+                    working_directory: &config.working_directory,
+                    midenup_home: &config.midenup_home,
+                    cargo_home: &config.cargo_home,
+                    manifest_uri: &config.manifest_uri,
+                    debug: config.debug,
+                    offline: config.offline,
+                    target: config.target.to_string(),
+                    active_toolchain: toolchain.channel.to_string(),
+                    justification,
+                };
+
+                if *json {
+                    println!(
+                        "{}",
+                        serde_json::to_string_pretty(&contents)
+                            .context("Couldn't serialize configuration")?
+                    );
+                    return Ok(());
+                }
+
+                let justification = match &contents.justification {
+                    ToolchainJustification::MidenToolchainFile { path } => {
+                        format!("set by {}", path.display())
+                    },
+                    ToolchainJustification::Override => String::from("set by `midenup override`"),
+                    ToolchainJustification::Default => String::from("system default"),
+                    ToolchainJustification::CommandLine => String::from("set by `+<channel>`"),
+                };
+                let rows = [
+                    ("midenup version", contents.midenup_version.to_string()),
+                    ("git revision", contents.git_revision.to_string()),
+                    ("working directory", contents.working_directory.display().to_string()),
+                    ("midenup home", contents.midenup_home.display().to_string()),
+                    ("cargo home", contents.cargo_home.display().to_string()),
+                    ("manifest URI", contents.manifest_uri.to_string()),
+                    ("debug", contents.debug.to_string()),
+                    ("offline", contents.offline.to_string()),
+                    ("target", contents.target),
+                    (
+                        "active toolchain",
+                        format!("{} ({justification})", contents.active_toolchain),
+                    ),
+                ];
+                let width = rows.iter().map(|(name, _)| name.len()).max().unwrap_or_default();
+                for (name, value) in rows {
+                    println!("{:<width$}  {value}", format!("{name}:").bold(), width = width + 1);
+                }
+
+                Ok(())
+            },
+            Self::DiskUsage { json } => {
+                let mut toolchains = local_manifest
+                    .get_channels()
+                    .map(|channel| ToolchainDiskUsage {
+                        channel: channel.name.to_string(),
+                        size: utils::fs::disk_usage(&channel.get_channel_dir(config)),
                     })
-                    .collect();
+                    .collect::<Vec<_>>();
+                toolchains
+                    .sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.channel.cmp(&b.channel)));
+                let total = toolchains.iter().map(|toolchain| toolchain.size).sum();
+                let usage = DiskUsage { toolchains, total };
+
+                if *json {
+                    println!(
+                        "{}",
+                        serde_json::to_string_pretty(&usage)
+                            .context("Couldn't serialize disk usage")?
+                    );
+                    return Ok(());
+                }
+
+                if usage.toolchains.is_empty() {
+                    println!("{}: no toolchains are installed", "info".white().bold());
+                    return Ok(());
+                }
+
+                let width = usage
+                    .toolchains
+                    .iter()
+                    .map(|toolchain| toolchain.channel.chars().count())
+                    .chain(["Total".len()])
+                    .max()
+                    .unwrap_or_default();
+                for toolchain in &usage.toolchains {
+                    println!("{:<width$}  {:>10}", toolchain.channel, format_size(toolchain.size));
+                }
+                println!(
+                    "{}  {:>10}",
+                    format!("{:<width$}", "Total").bold(),
+                    format_size(usage.total)
+                );
+
+                Ok(())
+            },
+            Self::Manifest => {
+                println!("{} {}", "Upstream manifest:".bold(), config.manifest_uri);
+                println!("  generated on {}", local_time(config.manifest.last_updated()));
+
+                let local_manifest_path =
+                    config.midenup_home.join("manifest").with_extension("json");
+                println!("{} {}", "Local manifest:".bold(), local_manifest_path.display());
+                // The local manifest is left empty until a toolchain is installed.
+                if std::fs::metadata(&local_manifest_path).is_ok_and(|metadata| metadata.len() > 0)
+                {
+                    println!("  last written on {}", local_time(local_manifest.last_updated()));
+                } else {
+                    println!("  no toolchain was installed yet");
+                }
+
+                Ok(())
+            },
+            Self::Path { export } => {
+                let (toolchain, _) = Toolchain::current(config)?;
+                let channel = config
+                    .manifest
+                    .get_channel(&toolchain.channel)
+                    .or_else(|| local_manifest.get_channel(&toolchain.channel))
+                    .with_context(|| {
+                        format!("channel '{}' doesn't exist or is unavailable", toolchain.channel)
+                    })?;
+
+                let bin_dir = channel.get_channel_dir(config).join("bin");
+                if !bin_dir.exists() {
+                    eprintln!(
+                        "{}: the active toolchain '{}' is not installed. To install it, try \
+                         running:\nmidenup install {}",
+                        "warn".yellow(),
+                        toolchain.channel,
+                        toolchain.channel
+                    );
+                }
+
+                if *export {
+                    println!("export PATH=\"{}:$PATH\"", bin_dir.display());
+                } else {
+                    println!("{}", bin_dir.display());
+                }
+
+                Ok(())
+            },
+            Self::Components { json } => {
+                let (toolchain, justification) = Toolchain::current(config)?;
+                let upstream_channel =
+                    config.manifest.get_channel(&toolchain.channel).with_context(|| {
+                        format!("channel '{}' doesn't exist or is unavailable", toolchain.channel)
+                    })?;
+
+                // Prefer what was actually installed over what is available upstream.
+                let installed_channel = local_manifest.get_channel_by_name(&upstream_channel.name);
+                let channel = installed_channel.unwrap_or(upstream_channel);
+                let subset = channel.create_subset(&toolchain, &justification);
+                let channel = subset.as_ref().unwrap_or(channel);
+
+                if !json {
+                    print!("{}", channel_contents_display(channel));
+                    return Ok(());
+                }
+
+                let contents = ToolchainContents {
+                    channel: channel.name.to_string(),
+                    installed: installed_channel.is_some(),
+                    aliases: channel.get_aliases().into_iter().collect(),
+                    executables: channel
+                        .components
+                        .iter()
+                        .filter(|component| {
+                            matches!(
+                                component.get_installed_file(),
+                                InstalledFile::Executable { alias_only: false, .. }
+                            )
+                        })
+                        .map(|component| component.name.as_ref())
+                        .collect(),
+                    libraries: channel
+                        .components
+                        .iter()
+                        .filter_map(|component| match component.get_installed_file() {
+                            InstalledFile::Library { library_name, .. } => Some(library_name),
+                            InstalledFile::Executable { .. } => None,
+                        })
+                        .collect(),
+                    components: &channel.components,
+                };
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&contents)
+                        .context("Couldn't serialize toolchain contents")?
+                );
+
+                Ok(())
+            },
+            Self::List { components } => {
+                let stable_toolchain = config.manifest.get_latest_stable();
 
                 println!("{}", "Installed toolchains:".bold().underline());
-                for toolchain in toolchains_display {
-                    println!("{toolchain}");
+                for channel in local_manifest.get_channels() {
+                    let is_stable =
+                        stable_toolchain.as_ref().is_some_and(|stable| stable.name == channel.name);
+                    let mut line = format!("{}", channel.name);
+                    if is_stable {
+                        line.push_str(&format!(" {}", "(stable)".bold()));
+                    }
+                    if !components {
+                        println!("{line}");
+                        continue;
+                    }
+
+                    if channel.is_partially_installed() {
+                        line.push_str(&format!(" {}", "(partial)".yellow()));
+                    }
+                    println!("{line}");
+
+                    let width = channel
+                        .components
+                        .iter()
+                        .map(|component| component.name.chars().count())
+                        .max()
+                        .unwrap_or_default();
+                    for component in channel.components.iter() {
+                        println!("  {:<width$}  {}", component.name, component.version);
+                    }
                 }
 
                 Ok(())
@@ -90,3 +568,63 @@ impl ShowCommand {
         }
     }
 }
+
+/// Formats a size in bytes with the largest binary unit that keeps it above 1, e.g. `1.5 GiB`.
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{size:.1} {}", UNITS[unit])
+}
+
+/// Formats `date` in the local time zone, e.g. `2025-06-30 14:05:12 +02:00`.
+fn local_time(date: chrono::DateTime<chrono::Utc>) -> String {
+    date.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M:%S %:z").to_string()
+}
+
+/// Compares the components installed in `channel` against its upstream counterpart, in the same
+/// way `midenup update` does, and returns the ones that would be updated.
+fn outdated_components(
+    config: &Config,
+    channel: &Channel,
+    local_manifest: &Manifest,
+) -> anyhow::Result<Vec<OutdatedComponent>> {
+    let Some(mut upstream) = channel.find_upstream_counterpart(config) else {
+        return Ok(Vec::new());
+    };
+    upstream.channel.apply_pins(local_manifest.pins());
+    upstream.channel.resolve_versions(config.offline)?;
+
+    // Components installed from a path are compared against the current contents of the path,
+    // see `midenup install --override`.
+    for component in channel.components.iter() {
+        if matches!(component.version, Authority::Path { .. })
+            && let Some(upstream_component) = upstream.channel.get_component_mut(&component.name)
+        {
+            let mut synced = component.clone();
+            synced.sync(config);
+            *upstream_component = synced;
+        }
+    }
+
+    let outdated = channel
+        .components_to_update(&upstream.channel)
+        .changed()
+        .map(|change| OutdatedComponent {
+            channel: channel.name.to_string(),
+            component: change.installed.name.to_string(),
+            installed: change.installed.version.describe(),
+            available: change.upstream.version.describe(),
+        })
+        .collect();
+
+    Ok(outdated)
+}
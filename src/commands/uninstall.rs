@@ -1,17 +1,21 @@
 use std::{
     ffi::OsStr,
-    io::Write,
     path::{Path, PathBuf},
 };
 
 use anyhow::{Context, bail};
+use colored::Colorize;
 use thiserror::Error;
 
 use crate::{
-    channel::{Channel, Component, InstalledFile},
+    channel::{Channel, Component, InstalledFile, Tags},
+    commands::{
+        self,
+        plan::{Execution, Plan, Step},
+    },
     config::Config,
     manifest::Manifest,
-    version::Authority,
+    toolchain::DEFAULT_TOOLCHAIN_FILE_NAME,
 };
 
 #[derive(Error, Debug)]
@@ -34,6 +38,28 @@ pub fn uninstall(
     config: &Config,
     upstream_channel: &Channel,
     local_manifest: &mut Manifest,
+) -> anyhow::Result<()> {
+    execute(config, upstream_channel, local_manifest, &mut Execution::Live)
+}
+
+/// Computes the [Plan] of [uninstall]ing `upstream_channel`, without changing anything.
+pub fn plan(
+    config: &Config,
+    upstream_channel: &Channel,
+    local_manifest: &Manifest,
+) -> anyhow::Result<Plan> {
+    let mut execution = Execution::new(true);
+    execute(config, upstream_channel, &mut local_manifest.clone(), &mut execution)?;
+    Ok(execution.into_plan())
+}
+
+/// Goes through the steps of uninstalling `upstream_channel`, carrying them out or only recording
+/// them depending on `execution`.
+pub(super) fn execute(
+    config: &Config,
+    upstream_channel: &Channel,
+    local_manifest: &mut Manifest,
+    execution: &mut Execution,
 ) -> anyhow::Result<()> {
     let Some(local_channel) = local_manifest.get_channel_by_name(&upstream_channel.name).cloned()
     else {
@@ -56,66 +82,262 @@ pub fn uninstall(
 
         // Only remove the stable symlink if it actually points to the toolchain being uninstalled.
         // This prevents removing a symlink that was just created for a migrated channel.
-        let symlink_points_to_this_channel = stable_symlink
-            .canonicalize()
-            .ok()
-            .zip(toolchain_symlink.canonicalize().ok())
-            .map(|(a, b)| a == b)
-            .unwrap_or(false);
-
-        if symlink_points_to_this_channel
+        if stable_symlink_points_to(&stable_symlink, &toolchain_symlink)
             // If it doesn't exist, that probably means that there was a previous
             // uninstallation attempt that got interrumpted.
             && stable_symlink.exists()
         {
-            std::fs::remove_file(stable_symlink).context("Couldn't remove symlink")?;
+            execution.step(Step::RemoveSymlink { link: stable_symlink.clone() }, || {
+                std::fs::remove_file(&stable_symlink).context("Couldn't remove symlink")
+            })?;
         }
     }
 
     // If cleanup is interrumpted, then `midenup clean` can be used to clean
     // stale files.
     if let Ok(installed_channel_dir) = installed_channel_dir {
-        uninstall_components(&installed_channel_dir, &local_channel.components)?;
+        for component in local_channel.components.iter() {
+            execution.step(
+                Step::RemoveComponent { component: component.name.to_string() },
+                || {
+                    uninstall_components(&installed_channel_dir, core::slice::from_ref(component))
+                        .map_err(Into::into)
+                },
+            )?;
+        }
 
         // We now remove the install directory with all the remaining files.
-        std::fs::remove_dir_all(&installed_channel_dir).map_err(|e| {
-            UninstallError::FailedToRemoveToolchainDirectory(
-                e.to_string(),
-                installed_channel_dir.to_path_buf(),
-            )
+        execution.step(Step::RemoveDirectory { path: installed_channel_dir.clone() }, || {
+            std::fs::remove_dir_all(&installed_channel_dir).map_err(|e| {
+                UninstallError::FailedToRemoveToolchainDirectory(
+                    e.to_string(),
+                    installed_channel_dir.to_path_buf(),
+                )
+                .into()
+            })
         })?;
     }
 
-    // We remove the symlink, thus making the channel unaccesible.
-    if toolchain_symlink.exists() {
-        std::fs::remove_file(&toolchain_symlink)?;
+    // We remove the symlink, thus making the channel unaccesible. By now, it no longer points to
+    // an existing directory.
+    if std::fs::symlink_metadata(&toolchain_symlink).is_ok() {
+        execution.step(Step::RemoveSymlink { link: toolchain_symlink.clone() }, || {
+            std::fs::remove_file(&toolchain_symlink).map_err(Into::into)
+        })?;
     }
 
     // We remove the channel from the local manifest.
     // This is what *REALLY* marks the channel as uninstalled.
-    {
+    execution.step(Step::RemoveChannel { channel: local_channel.name.to_string() }, || {
         local_manifest.remove_channel(local_channel.name.clone());
 
-        let local_manifest_path = config.midenup_home.join("manifest").with_extension("json");
-        let mut local_manifest_file =
-            std::fs::File::create(&local_manifest_path).with_context(|| {
-                format!(
-                    "failed to create file for install script at '{}'",
-                    local_manifest_path.display()
-                )
-            })?;
-        local_manifest_file
-            .write_all(
-                serde_json::to_string_pretty(&local_manifest)
-                    .context("Couldn't serialize local manifest")?
-                    .as_bytes(),
-            )
-            .context("Couldn't create local manifest file")?;
+        commands::save_local_manifest(config, local_manifest)
+    })
+}
+
+/// Uninstalls every toolchain in the local manifest, one by one with [uninstall].
+///
+/// Afterwards, the `stable`, `default` and `opt` symlinks are removed along with the
+/// [DEFAULT_TOOLCHAIN_FILE_NAME] file, since they can only point to toolchains that are gone.
+///
+/// Unless `yes` is set, the user is asked for confirmation before anything gets removed.
+pub fn uninstall_all(
+    config: &Config,
+    local_manifest: &mut Manifest,
+    yes: bool,
+) -> anyhow::Result<()> {
+    let channels = local_manifest.get_channels().cloned().collect::<Vec<_>>();
+    if channels.is_empty() && toolchain_selection_files(config).is_empty() {
+        println!("{}: no toolchains are installed, nothing to uninstall", "info".white().bold());
+        return Ok(());
     }
 
+    println!("The following toolchains will be uninstalled:");
+    for channel in channels.iter() {
+        println!("  {}", channel.name);
+    }
+
+    if !yes {
+        println!("Proceed? (y/N)");
+        let mut input = String::new();
+        std::io::stdin().read_line(&mut input).context("Failed to read input")?;
+        if !input.trim().eq_ignore_ascii_case("y") {
+            println!("Cancelling, no changes will be applied.");
+            return Ok(());
+        }
+    }
+
+    execute_all(config, local_manifest, &mut Execution::Live)?;
+
+    println!("{}: uninstalled every toolchain", "info".white().bold());
+
     Ok(())
 }
 
+/// Computes the [Plan] of [uninstall_all], without changing anything.
+pub fn plan_all(config: &Config, local_manifest: &Manifest) -> anyhow::Result<Plan> {
+    let mut execution = Execution::new(true);
+    execute_all(config, &mut local_manifest.clone(), &mut execution)?;
+    Ok(execution.into_plan())
+}
+
+/// Goes through the steps of [uninstall_all], without asking for confirmation.
+fn execute_all(
+    config: &Config,
+    local_manifest: &mut Manifest,
+    execution: &mut Execution,
+) -> anyhow::Result<()> {
+    let channels = local_manifest.get_channels().cloned().collect::<Vec<_>>();
+    for channel in channels.iter() {
+        execute(config, channel, local_manifest, execution)?;
+    }
+
+    // Some of these, e.g. the `stable` symlink, were already removed along with their toolchain.
+    // On a dry run they are still around, but [Execution] doesn't record a step twice.
+    for path in toolchain_selection_files(config) {
+        let step = if path.is_symlink() {
+            Step::RemoveSymlink { link: path.clone() }
+        } else {
+            Step::RemoveFile { path: path.clone() }
+        };
+        execution.step(step, || {
+            std::fs::remove_file(&path)
+                .with_context(|| format!("failed to remove '{}'", path.display()))
+        })?;
+    }
+
+    Ok(())
+}
+
+/// The files which select a toolchain, and which exist: the `stable`, `default` and `opt`
+/// symlinks, and the [DEFAULT_TOOLCHAIN_FILE_NAME] file.
+fn toolchain_selection_files(config: &Config) -> Vec<PathBuf> {
+    let toolchains_dir = config.midenup_home.join("toolchains");
+    [
+        toolchains_dir.join("stable"),
+        toolchains_dir.join("default"),
+        config.midenup_home.join("opt"),
+        config.midenup_home.join(DEFAULT_TOOLCHAIN_FILE_NAME),
+    ]
+    .into_iter()
+    .filter(|path| std::fs::symlink_metadata(path).is_ok())
+    .collect()
+}
+
+/// Whether `stable_symlink` and `toolchain_symlink` resolve to the same toolchain directory.
+fn stable_symlink_points_to(stable_symlink: &Path, toolchain_symlink: &Path) -> bool {
+    stable_symlink
+        .canonicalize()
+        .ok()
+        .zip(toolchain_symlink.canonicalize().ok())
+        .map(|(a, b)| a == b)
+        .unwrap_or(false)
+}
+
+/// Uninstalls a single component from an installed toolchain, leaving the rest of the toolchain
+/// in place. The toolchain is then marked as partially installed in the local manifest.
+///
+/// Fails if any other installed component of the toolchain requires the component.
+pub fn uninstall_component(
+    config: &Config,
+    upstream_channel: &Channel,
+    component_name: &str,
+    local_manifest: &mut Manifest,
+) -> anyhow::Result<()> {
+    execute_component(
+        config,
+        upstream_channel,
+        component_name,
+        local_manifest,
+        &mut Execution::Live,
+    )
+}
+
+/// Computes the [Plan] of [uninstall_component], without changing anything.
+pub fn plan_component(
+    config: &Config,
+    upstream_channel: &Channel,
+    component_name: &str,
+    local_manifest: &Manifest,
+) -> anyhow::Result<Plan> {
+    let mut execution = Execution::new(true);
+    execute_component(
+        config,
+        upstream_channel,
+        component_name,
+        &mut local_manifest.clone(),
+        &mut execution,
+    )?;
+    Ok(execution.into_plan())
+}
+
+/// Goes through the steps of [uninstall_component], carrying them out or only recording them
+/// depending on `execution`.
+fn execute_component(
+    config: &Config,
+    upstream_channel: &Channel,
+    component_name: &str,
+    local_manifest: &mut Manifest,
+    execution: &mut Execution,
+) -> anyhow::Result<()> {
+    let Some(local_channel) = local_manifest.get_channel_by_name_mut(&upstream_channel.name) else {
+        bail!(
+            "Channel {} is not in the local manifest, nothing to uninstall.",
+            upstream_channel.name
+        );
+    };
+
+    let component = removable_component(local_channel, component_name)?;
+
+    let toolchain_symlink = local_channel.get_channel_dir(config);
+    execution.step(Step::RemoveComponent { component: component.name.to_string() }, || {
+        let installed_channel_dir = toolchain_symlink.canonicalize().with_context(|| {
+            format!("failed to resolve toolchain directory '{}'", toolchain_symlink.display())
+        })?;
+        uninstall_components(&installed_channel_dir, core::slice::from_ref(&component))
+            .map_err(Into::into)
+    })?;
+
+    // Once the component's files are gone, we drop it from the local manifest.
+    let channel_name = local_channel.name.clone();
+    execution.step(Step::UpdateChannel { channel: channel_name.to_string() }, || {
+        if let Some(local_channel) = local_manifest.get_channel_by_name_mut(&channel_name) {
+            local_channel.components.retain(|c| c.name != component.name);
+            if !local_channel.is_partially_installed() {
+                local_channel.tags.push(Tags::Partial);
+            }
+        }
+
+        commands::save_local_manifest(config, local_manifest)
+    })
+}
+
+/// Returns the component named `component_name` of `local_channel`, failing if it isn't installed
+/// or if another installed component requires it.
+fn removable_component(local_channel: &Channel, component_name: &str) -> anyhow::Result<Component> {
+    let Some(component) = local_channel.get_component(component_name).cloned() else {
+        bail!(
+            "Component {component_name} is not installed in toolchain {}, nothing to uninstall.",
+            local_channel.name
+        );
+    };
+
+    let dependents = local_channel
+        .components
+        .iter()
+        .filter(|c| c.requires.iter().any(|required| required == component_name))
+        .map(|c| c.name.as_ref())
+        .collect::<Vec<_>>();
+    if !dependents.is_empty() {
+        bail!(
+            "Component {component_name} can't be uninstalled, since it is required by: {}",
+            dependents.join(", ")
+        );
+    }
+
+    Ok(component)
+}
+
 pub fn uninstall_components(
     install_dir: &Path,
     components: &[Component],
@@ -152,30 +374,26 @@ pub fn uninstall_components(
                     .map_err(|err| UninstallError::FailedToDeleteFile(bin_path, err.to_string()))?;
             }
         } else {
-            match &exe.version {
-                Authority::Cargo { package, .. } => {
-                    let package_name = package.as_deref().unwrap_or(exe.name.as_ref());
-                    uninstall_executable(package_name, install_dir)?;
-                },
-                Authority::Git { crate_name, .. } => {
-                    uninstall_executable(crate_name, install_dir)?;
-                },
-                Authority::Path { crate_name, .. } => {
-                    uninstall_executable(crate_name, install_dir)?;
-                },
-            }
+            uninstall_executable(exe.get_cargo_package(), exe.bin.as_deref(), install_dir)?;
         }
     }
 
     Ok(())
 }
 
-pub fn uninstall_executable(name: &str, root_dir: impl AsRef<OsStr>) -> Result<(), UninstallError> {
-    let output = std::process::Command::new("cargo")
-        .arg("uninstall")
-        .arg(name)
-        .arg("--root")
-        .arg(&root_dir)
+pub fn uninstall_executable(
+    name: &str,
+    bin: Option<&str>,
+    root_dir: impl AsRef<OsStr>,
+) -> Result<(), UninstallError> {
+    let mut command = crate::external::cargo_command();
+    command.arg("uninstall").arg(name).arg("--root").arg(&root_dir);
+    // Other binaries of the package may belong to other components, see
+    // [Channel::package_collisions].
+    if let Some(bin) = bin {
+        command.arg("--bin").arg(bin);
+    }
+    let output = command
         .output()
         .map_err(|err| UninstallError::InternalCargoError(err.to_string()))?;
 
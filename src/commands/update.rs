@@ -1,25 +1,26 @@
-use std::{
-    collections::HashSet,
-    hash::{Hash, Hasher},
-};
+use std::{collections::HashSet, io::BufRead};
 
-use anyhow::Context;
+use anyhow::{Context, bail};
 use colored::Colorize;
 
 use crate::{
     channel::{
-        Channel, Component, InstalledFile, MigrationStrategy, UpstreamChannel, UpstreamMatch,
-        UserChannel,
+        Channel, Component, ComponentChange, InstalledFile, MigrationStrategy, UpstreamChannel,
+        UpstreamMatch, UserChannel,
     },
-    commands::{self},
+    commands::{self, plan::Execution},
     config::Config,
     manifest::Manifest,
-    options::{InstallationOptions, PathUpdate, UpdateOptions},
+    message::Message,
+    options::{InstallationOptions, PathUpdate, UpdateOptions, registry_from_env},
     profile::Profile,
     version::Authority,
 };
 
 /// Updates installed toolchains
+///
+/// With [UpdateOptions::check], the available updates are only reported, and the process exits
+/// with [commands::show::OUTDATED_EXIT_CODE] if there are any.
 pub fn update(
     config: &Config,
     channel_type: Option<&UserChannel>,
@@ -27,6 +28,7 @@ pub fn update(
     options: &UpdateOptions,
 ) -> anyhow::Result<()> {
     let last_updated = local_manifest.last_updated();
+    let mut updates_available = false;
     match channel_type {
         Some(UserChannel::Stable) => {
             let local_stable = local_manifest.get_latest_stable().context(
@@ -34,10 +36,10 @@ pub fn update(
 midenup install stable
 ",
             )?;
-            println!(
+            config.status(format!(
                 "syncing channel updates for stable (last update was {last_updated} as {})",
                 &local_stable.name
-            );
+            ));
             let upstream_stable = config
                 .manifest
                 .get_latest_stable()
@@ -47,13 +49,20 @@ midenup install stable
                 // probably means there's an error in midenup's parsing.
                 .context("ERROR: No stable channel found in upstream")?;
 
-            println!(
+            config.status(format!(
                 "latest stable is version {} (upstream last updated on {})",
                 &upstream_stable.name,
                 config.manifest.last_updated()
-            );
-
-            if upstream_stable.name > local_stable.name {
+            ));
+
+            if upstream_stable.name > local_stable.name && options.check {
+                config.emit(&Message::UpdateAvailable { channel: local_stable.name.to_string() });
+                config.status(format!(
+                    "stable can be updated from {} to {}",
+                    local_stable.name, upstream_stable.name
+                ));
+                updates_available = true;
+            } else if upstream_stable.name > local_stable.name {
                 let component_subset: Option<HashSet<_>> = if local_stable.is_partially_installed()
                 {
                     Some(local_stable.components.iter().map(|comp| comp.name.clone()).collect())
@@ -81,36 +90,56 @@ midenup install stable
                         alias: upstream_stable.alias.clone(),
                         tags: local_stable.tags.clone(),
                         components,
+                        post_install: upstream_stable.post_install.clone(),
                     }
                 };
 
+                if !options.dry_run {
+                    config.emit(&Message::Updating { channel: local_stable.name.to_string() });
+                }
                 let install_options = InstallationOptions::from(*options);
                 commands::install(config, &channel_to_install, local_manifest, &install_options)?
             } else {
-                println!("Nothing to update, you are all up to date");
+                config.emit(&Message::UpToDate { channel: local_stable.name.to_string() });
+                config.status("Nothing to update, you are all up to date");
             }
         },
-        Some(UserChannel::Version(version)) => {
-            // Check if any individual component changed since the last the manifest was synced
+        Some(
+            channel @ (UserChannel::Version(_) | UserChannel::Other(_) | UserChannel::Nightly),
+        ) => {
+            // Check if any individual component changed since the last the manifest was synced.
+            //
+            // NOTE: `nightly` refers to the latest installed nightly toolchain.
             let local_channel = local_manifest
-                .get_channel(&UserChannel::Version(version.clone()))
-                .context(format!("ERROR: No installed channel found with version {version}"))?
+                .get_channel(channel)
+                .with_context(|| match channel {
+                    UserChannel::Nightly => {
+                        String::from("ERROR: No nightly toolchain is installed")
+                    },
+                    _ => format!("ERROR: No installed channel found with version {channel}"),
+                })?
                 .clone();
 
-            println!(
+            config.status(format!(
                 "syncing channel updates for {} (last update was {last_updated})",
                 &local_channel.name
-            );
+            ));
 
             let upstream_counterpart =
                 local_channel.find_upstream_counterpart(config).context(format!(
-                    "ERROR: Couldn't find a channel upstream with version {version}. Maybe it got \
+                    "ERROR: Couldn't find a channel upstream with version {channel}. Maybe it got \
                      removed."
                 ))?;
 
-            println!("upstream last updated on {}", config.manifest.last_updated());
+            config.status(format!("upstream last updated on {}", config.manifest.last_updated()));
 
-            update_channel(config, &local_channel, &upstream_counterpart, local_manifest, options)?
+            updates_available = update_channel(
+                config,
+                &local_channel,
+                &upstream_counterpart,
+                local_manifest,
+                options,
+            )?;
         },
         None => {
             // Update all toolchains
@@ -131,17 +160,53 @@ midenup install stable
                 channels_to_update.push((local_channel.clone(), upstream_channel.clone()));
             }
 
+            let mut failures = Vec::new();
+            let attempted = channels_to_update.len();
             for (local_channel, upstream_channel) in channels_to_update {
-                println!(
+                config.status(format!(
                     "syncing channel updates for {} (last update was {last_updated})",
                     &local_channel.name
+                ));
+                config
+                    .status(format!("upstream last updated on {}", config.manifest.last_updated()));
+                let result = update_channel(
+                    config,
+                    &local_channel,
+                    &upstream_channel,
+                    local_manifest,
+                    options,
+                );
+                match result {
+                    Ok(pending) => updates_available |= pending,
+                    Err(err) if options.keep_going => {
+                        eprintln!(
+                            "{}: failed to update {}, continuing with the remaining toolchains: \
+                             {err:#}",
+                            "error".red().bold(),
+                            local_channel.name
+                        );
+                        failures.push((local_channel.name, err));
+                    },
+                    Err(err) => return Err(err),
+                }
+            }
+
+            if !failures.is_empty() {
+                let summary = failures
+                    .iter()
+                    .map(|(channel, err)| format!("  {channel}: {err:#}"))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                bail!(
+                    "failed to update {} out of {attempted} toolchains:\n{summary}",
+                    failures.len()
                 );
-                println!("upstream last updated on {}", config.manifest.last_updated());
-                update_channel(config, &local_channel, &upstream_channel, local_manifest, options)?;
             }
         },
-        Some(UserChannel::Nightly) => todo!(),
-        Some(UserChannel::Other(_)) => todo!(),
+    }
+
+    if options.check && updates_available {
+        return Err(commands::ExitCode(commands::show::OUTDATED_EXIT_CODE).into());
     }
     Ok(())
 }
@@ -167,28 +232,75 @@ midenup install stable
 ///   These files, as per the documentation, are not to be edited manually. So,
 ///   we take these extra precautions in order for midenup's local [[Manifest]]
 ///   and `cargo`'s to be synced.
+///
+/// With [UpdateOptions::check], the pending changes are only reported. Returns whether the
+/// toolchain had any pending changes.
 fn update_channel(
     config: &Config,
     local_channel: &Channel,
     upstream_channel: &UpstreamChannel,
     local_manifest: &mut Manifest,
     options: &UpdateOptions,
-) -> anyhow::Result<()> {
-    let update = match compute_update(local_channel, upstream_channel, options)? {
+) -> anyhow::Result<bool> {
+    // Pinned components are compared against their pinned version, so that pinning or unpinning
+    // a component triggers an update.
+    let mut upstream_channel = upstream_channel.clone();
+    upstream_channel.channel.apply_pins(local_manifest.pins());
+    upstream_channel.channel.resolve_versions(config.offline)?;
+
+    // Components installed from a path in place of the channel's source (see `midenup install
+    // --override`) keep being installed from that path, and are compared against its current
+    // contents.
+    for local_component in local_channel.components.iter() {
+        if !matches!(local_component.version, Authority::Path { .. }) {
+            continue;
+        }
+        if let Some(upstream_component) =
+            upstream_channel.channel.get_component_mut(&local_component.name)
+            && !matches!(upstream_component.version, Authority::Path { .. })
+        {
+            let mut synced_component = local_component.clone();
+            synced_component.sync(config);
+            *upstream_component = synced_component;
+        }
+    }
+
+    // A check doesn't ask about components installed from a path, it reports them as updatable.
+    let options = &match options.path_update {
+        PathUpdate::Interactive if options.check => {
+            UpdateOptions { path_update: PathUpdate::All, ..*options }
+        },
+        _ => *options,
+    };
+
+    // NOTE: stdin is only locked while the user is asked about the update, not while it gets
+    // installed.
+    let plan =
+        compute_update(local_channel, &upstream_channel, options, &mut std::io::stdin().lock())?;
+    let update = match plan {
         UpdatePlan::Abort => {
-            println!("Aborting update of {} due to user input/configuration", local_channel);
-            return Ok(());
+            config.status(format!(
+                "Aborting update of {local_channel} due to user input/configuration"
+            ));
+            return Ok(false);
         },
         UpdatePlan::Skip => {
-            println!("Toolchain {} is up to date", local_channel);
-            return Ok(());
+            config.emit(&Message::UpToDate { channel: local_channel.name.to_string() });
+            config.status(format!("Toolchain {local_channel} is up to date"));
+            return Ok(false);
         },
         UpdatePlan::Pending(update) => update,
     };
 
-    display_warnings(&update, options);
+    if options.check || options.dry_run {
+        display_pending_changes(config, local_channel, &update);
+    }
+    if options.check {
+        config.emit(&Message::UpdateAvailable { channel: local_channel.name.to_string() });
+        return Ok(true);
+    }
 
-    println!("Updating toolchain {}..", &local_channel.name);
+    display_warnings(config, &update, options);
 
     let Update {
         channel_to_install,
@@ -199,19 +311,54 @@ fn update_channel(
     let install_options = InstallationOptions {
         profile: Profile::Minimal,
         verbose: options.verbose,
+        quiet: options.quiet,
+        jobs: None,
+        components: Vec::new(),
+        overrides: Vec::new(),
+        features: Vec::new(),
+        no_default_features: Vec::new(),
+        dry_run: options.dry_run,
+        force: false,
+        timings: false,
+        registry: registry_from_env(),
+        frozen: false,
         components_to_uninstall,
+        ignore_pins: false,
+        replace_install: false,
     };
 
-    commands::install(config, &channel_to_install, local_manifest, &install_options)?;
+    let mut execution = Execution::new(options.dry_run);
+    if !execution.is_dry_run() {
+        config.emit(&Message::Updating { channel: local_channel.name.to_string() });
+        config.status(format!("Updating toolchain {}..", &local_channel.name));
+    }
+
+    // NOTE: The updated toolchain is installed into a new directory, and the channel's symlink is
+    // only swapped once the install succeeds. So if the install fails, the previous install (and
+    // the local manifest) are left untouched, and its components keep working.
+    commands::install::execute(
+        config,
+        &channel_to_install,
+        local_manifest,
+        &install_options,
+        &mut execution,
+    )
+    .with_context(|| {
+        format!(
+            "failed to update toolchain {}, the previous installation was left untouched",
+            local_channel.name
+        )
+    })?;
 
     if let Some(channel_to_install) = channel_to_uninstall {
         // If the update were to be interrupted before the uninstall finishes,
         // re-running `midenup update` would finish the process.
         // This does mean that channel migration is a non-atomic operation.
-        commands::uninstall(config, &channel_to_install, local_manifest)?;
+        commands::uninstall::execute(config, &channel_to_install, local_manifest, &mut execution)?;
     };
 
-    Ok(())
+    execution.finish(config);
+    Ok(true)
 }
 
 enum InteractiveResult {
@@ -221,8 +368,14 @@ enum InteractiveResult {
     DontUpdateComponent,
 }
 
-fn handle_path_uninstall_interactive(component: &Component) -> anyhow::Result<InteractiveResult> {
+fn handle_path_uninstall_interactive(
+    component: &Component,
+    input: &mut dyn BufRead,
+) -> anyhow::Result<InteractiveResult> {
     let component_name = &component.name;
+    if let Authority::Path { path, .. } = &component.version {
+        println!("{component_name} changed since it was installed from {}.", path.display());
+    }
     println!(
         "Would you like to update this component? (N/y/c)
    - N: no, skip this component
@@ -230,10 +383,10 @@ fn handle_path_uninstall_interactive(component: &Component) -> anyhow::Result<In
    - c: cancel the update all-together (no changes will be applied)"
     );
 
-    let mut input = String::new();
-    std::io::stdin().read_line(&mut input).context("Failed to read input")?;
-    let input = input.trim().to_ascii_lowercase();
-    match input.as_str() {
+    let mut answer = String::new();
+    input.read_line(&mut answer).context("Failed to read input")?;
+    let answer = answer.trim().to_ascii_lowercase();
+    match answer.as_str() {
         "y" => {
             println!("Updating {component_name}");
             Ok(InteractiveResult::UpdateComponent)
@@ -263,27 +416,6 @@ pub enum UpdateStatus {
     UpToDate,
 }
 
-/// Wrapper around `&Component` that defines `Hash`/`Eq` by name only, so we can
-/// use `HashSet` set operations (difference, intersection, contains) keyed on
-/// names. This is not a property of component themselves, hence the wrapper
-/// type.
-///
-/// See https://stackoverflow.com/a/65671830 as a reference.
-#[derive(Debug, Clone)]
-struct ComponentByName<'a>(&'a Component);
-
-impl PartialEq for ComponentByName<'_> {
-    fn eq(&self, other: &Self) -> bool {
-        self.0.name == other.0.name
-    }
-}
-impl Eq for ComponentByName<'_> {}
-impl Hash for ComponentByName<'_> {
-    fn hash<H: Hasher>(&self, state: &mut H) {
-        self.0.name.hash(state);
-    }
-}
-
 #[derive(Debug, Clone)]
 pub struct ComponentUpdate {
     pub component: Component,
@@ -354,10 +486,14 @@ impl Update {
 ///
 /// There is one notable exception to this rule which is when a channel is migrated into a different
 /// channel. In that case, every component is marked for update.
+///
+/// Components installed from a path are only updated according to [UpdateOptions::path_update].
+/// In [PathUpdate::Interactive] mode, the user's answers are read from `input`.
 fn compute_update(
     older: &Channel,
     newer: &UpstreamChannel,
     options: &UpdateOptions,
+    input: &mut dyn BufRead,
 ) -> anyhow::Result<UpdatePlan> {
     struct MigrationEffects<'a> {
         strategy: Option<&'a MigrationStrategy>,
@@ -422,71 +558,68 @@ fn compute_update(
         }
     }
 
-    // Compute the set of components in the old and new channels, in order to determine the effects
-    // to apply to each component (i.e. install if present in the new channel, but not old;
-    // uninstall if present in the old channel, but not new; and update if present in both sets).
-    let new_channel: HashSet<ComponentByName> =
-        newer.channel.components.iter().map(ComponentByName).collect();
-    let current: HashSet<ComponentByName> = older.components.iter().map(ComponentByName).collect();
+    // Determine the effects to apply to each component: install if present in the new channel,
+    // but not old; uninstall if present in the old channel, but not new; and update if present in
+    // both but changed.
+    let diff = older.components_to_update(&newer.channel);
 
-    // Extract the set of components to add (present in the new channel, not in the old)
-    let new_components = new_channel
-        .difference(&current)
+    let new_components = diff
+        .added
+        .into_iter()
         // If the channel is partially installed, then we explicitely don't want new components.
         .filter(|_| !older.is_partially_installed())
-        .map(|&ComponentByName(comp)| ComponentUpdate::new(comp.clone(), UpdateStatus::Added));
+        .map(|comp| ComponentUpdate::new(comp, UpdateStatus::Added));
+
+    let mut components_to_install = Vec::from_iter(new_components);
+    let mut components_to_uninstall = diff.removed;
 
-    // Extract the set of components to remove (present in the old channel, not in the new)
-    let old_components = current
-        .difference(&new_channel)
-        .map(|&ComponentByName(comp)| ComponentUpdate::new(comp.clone(), UpdateStatus::Removed));
+    // If the channel got marked as migrated, then every single installed component is due for an
+    // update.
+    let migrated = match &newer.upstream_match {
+        UpstreamMatch::Migrated(strategy) => Some(strategy),
+        UpstreamMatch::UpstreamCounterpart => None,
+    };
 
-    // Extract the set of components to update (present in both old and new channels)
-    let changed_components = current.intersection(&new_channel);
+    for component in diff.up_to_date {
+        let update_status = match migrated {
+            Some(strategy) => UpdateStatus::Migrated { strategy: strategy.clone() },
+            None => UpdateStatus::UpToDate,
+        };
+        components_to_install.push(ComponentUpdate::new(component, update_status));
+    }
 
-    let mut components_to_install = Vec::from_iter(new_components);
-    let mut components_to_uninstall = Vec::from_iter(old_components.map(|cu| cu.component));
+    for ComponentChange {
+        installed: current_component,
+        upstream: new_component,
+    } in diff.version_changed.into_iter().chain(diff.revision_changed)
+    {
+        if let Some(strategy) = migrated {
+            let update_status = UpdateStatus::Migrated { strategy: strategy.clone() };
+            components_to_install.push(ComponentUpdate::new(new_component, update_status));
+            continue;
+        }
 
-    for component_by_name in changed_components {
-        let new_component = new_channel.get(component_by_name).unwrap().0;
-        let current_component = current.get(component_by_name).unwrap().0;
         // NOTE: that some components might ignore this update, such as components that were
         // installed via the filesystem.
-        let update_status = {
-            // If the channel got marked as migrated, then every single installed component is due
-            // for an update.
-            if let UpstreamMatch::Migrated(strategy) = &newer.upstream_match {
-                UpdateStatus::Migrated { strategy: strategy.clone() }
-            } else if !current_component.is_up_to_date(new_component) {
+        match should_skip_component_update(&current_component, options, older, input)? {
+            ComponentUpdateDecision::Abort => return Ok(UpdatePlan::Abort),
+            ComponentUpdateDecision::Keep(preserved_component) => {
+                // Do not update this component - add it to the set of components to install
+                // using the current component manifest, but do not add it to the set of
+                // components to uninstall.
+                //
+                // NOTE: This decision only occurs for components installed via path, in cases
+                // where the user explicitly does not want to install the version defined in
+                // the upstream manifest. Since it stays as is, it doesn't require an update.
+                components_to_install
+                    .push(ComponentUpdate::new(preserved_component, UpdateStatus::UpToDate));
+            },
+            ComponentUpdateDecision::Update => {
                 // When a component needs an update, we must first uninstall the old component
-                UpdateStatus::NeedsUpdate
-            } else {
-                UpdateStatus::UpToDate
-            }
-        };
-        if matches!(update_status, UpdateStatus::NeedsUpdate) {
-            match should_skip_component_update(current_component, options, older)? {
-                ComponentUpdateDecision::Abort => return Ok(UpdatePlan::Abort),
-                ComponentUpdateDecision::Keep(preserved_component) => {
-                    // Do not update this component - add it to the set of components to install
-                    // using the current component manifest, but do not add it to the set of
-                    // components to uninstall.
-                    //
-                    // NOTE: This decision only occurs for components installed via path, in cases
-                    // where the user explicitly does not want to install the version defined in
-                    // the upstream manifest
-                    components_to_install
-                        .push(ComponentUpdate::new(preserved_component, update_status));
-                },
-                ComponentUpdateDecision::Update => {
-                    // We need to reinstall this component
-                    components_to_uninstall.push(current_component.clone());
-                    components_to_install
-                        .push(ComponentUpdate::new(new_component.clone(), update_status));
-                },
-            }
-        } else {
-            components_to_install.push(ComponentUpdate::new(new_component.clone(), update_status));
+                components_to_uninstall.push(current_component);
+                components_to_install
+                    .push(ComponentUpdate::new(new_component, UpdateStatus::NeedsUpdate));
+            },
         }
     }
 
@@ -545,18 +678,23 @@ fn should_skip_component_update(
     component: &Component,
     options: &UpdateOptions,
     local_channel: &Channel,
+    input: &mut dyn BufRead,
 ) -> anyhow::Result<ComponentUpdateDecision> {
     let skip_update = match component.get_installed_file() {
         InstalledFile::Library { .. } => false,
         InstalledFile::Executable { .. } => match component.version {
-            Authority::Cargo { .. } | Authority::Git { .. } => false,
+            Authority::Cargo { .. }
+            | Authority::CargoRequirement { .. }
+            | Authority::Git { .. } => false,
             // Since uninstalling a component from the filesystem is potentially
             // irreversible, we take special precautions before uninstalling them.
             Authority::Path { .. } => match options.path_update {
-                PathUpdate::Interactive => match handle_path_uninstall_interactive(component)? {
-                    InteractiveResult::Cancel => return Ok(ComponentUpdateDecision::Abort),
-                    InteractiveResult::UpdateComponent => false,
-                    InteractiveResult::DontUpdateComponent => true,
+                PathUpdate::Interactive => {
+                    match handle_path_uninstall_interactive(component, input)? {
+                        InteractiveResult::Cancel => return Ok(ComponentUpdateDecision::Abort),
+                        InteractiveResult::UpdateComponent => false,
+                        InteractiveResult::DontUpdateComponent => true,
+                    }
                 },
                 PathUpdate::All => false,
                 PathUpdate::Off => true,
@@ -571,7 +709,23 @@ fn should_skip_component_update(
     }
 }
 
-fn display_warnings(update: &Update, options: &UpdateOptions) {
+/// Lists the components that `update` adds, updates or removes from `local_channel`, for
+/// `midenup update --check` and `--dry-run`.
+fn display_pending_changes(config: &Config, local_channel: &Channel, update: &Update) {
+    let updated = &update.channel_to_install;
+    if updated.name != local_channel.name {
+        config.status(format!("{local_channel} can be migrated to {updated}:"));
+    } else {
+        config.status(format!("{local_channel} can be updated:"));
+    }
+
+    let diff = local_channel.components_to_update(updated);
+    if !diff.is_empty() {
+        config.status(diff);
+    }
+}
+
+fn display_warnings(config: &Config, update: &Update, options: &UpdateOptions) {
     // Warning for components installed from a PATH.
     {
         let components_from_path: Vec<String> = update
@@ -587,14 +741,14 @@ fn display_warnings(update: &Update, options: &UpdateOptions) {
             })
             .collect();
         if !components_from_path.is_empty() {
-            println!(
+            config.status(format!(
                 "\n{}: The following elements are installed from a specific path in the \
                  filesystem.",
                 "WARNING".yellow().bold(),
-            );
+            ));
 
             if matches!(options.path_update, PathUpdate::Off) {
-                println!(
+                config.status(
                     "
 To make midenup update them all, pass the '--path-update=all' flag to `midenup update`.
 Alternatively, pass the '--path-update=interactive' flag to interactively select which \
@@ -602,7 +756,7 @@ Alternatively, pass the '--path-update=interactive' flag to interactively select
                 );
             }
             for component_message in components_from_path {
-                println!("{}", component_message);
+                config.status(component_message);
             }
         }
     }
@@ -624,15 +778,91 @@ Alternatively, pass the '--path-update=interactive' flag to interactively select
                 })
                 .collect();
             if !migrated_components.is_empty() {
-                println!(
+                config.status(format!(
                     "{}: The following elements are going to be migrated.",
                     "WARNING".yellow().bold(),
-                );
+                ));
 
                 for component_message in migrated_components {
-                    println!("{}", component_message);
+                    config.status(component_message);
                 }
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::Cursor,
+        path::PathBuf,
+        time::{Duration, SystemTime},
+    };
+
+    use super::{UpdatePlan, compute_update};
+    use crate::{
+        channel::{Channel, Component, UpstreamChannel, UpstreamMatch},
+        options::{PathUpdate, UpdateOptions},
+        version::Authority,
+    };
+
+    /// Returns a channel with a single component, installed from a path that was last modified
+    /// at `last_modification`.
+    fn path_channel(last_modification: SystemTime) -> Channel {
+        let component = Component::new(
+            "vm",
+            Authority::Path {
+                path: PathBuf::from("/home/me/miden-vm"),
+                crate_name: String::from("miden-vm"),
+                last_modification: Some(last_modification),
+            },
+        );
+        Channel::new(semver::Version::new(0, 15, 0), None, vec![component], vec![])
+    }
+
+    /// Computes the update of a path component whose directory changed since it was installed,
+    /// answering `answer` if prompted.
+    fn update_changed_path_component(path_update: PathUpdate, answer: &str) -> UpdatePlan {
+        let installed = SystemTime::UNIX_EPOCH + Duration::from_secs(1000);
+        let older = path_channel(installed);
+        let newer = UpstreamChannel {
+            channel: path_channel(installed + Duration::from_secs(60)),
+            upstream_match: UpstreamMatch::UpstreamCounterpart,
+        };
+        let options = UpdateOptions { path_update, ..Default::default() };
+
+        compute_update(&older, &newer, &options, &mut Cursor::new(answer)).unwrap()
+    }
+
+    fn reinstalls_component(plan: &UpdatePlan) -> bool {
+        matches!(plan, UpdatePlan::Pending(update) if update.components_to_uninstall.len() == 1)
+    }
+
+    #[test]
+    fn path_update_off_skips_changed_path_components() {
+        let plan = update_changed_path_component(PathUpdate::Off, "");
+        assert!(matches!(plan, UpdatePlan::Skip));
+    }
+
+    #[test]
+    fn path_update_all_reinstalls_changed_path_components() {
+        let plan = update_changed_path_component(PathUpdate::All, "");
+        assert!(reinstalls_component(&plan));
+    }
+
+    #[test]
+    fn path_update_interactive_follows_answer() {
+        let plan = update_changed_path_component(PathUpdate::Interactive, "y\n");
+        assert!(reinstalls_component(&plan));
+
+        let plan = update_changed_path_component(PathUpdate::Interactive, "n\n");
+        assert!(matches!(plan, UpdatePlan::Skip));
+
+        // Anything but `y` or `c` skips the component.
+        let plan = update_changed_path_component(PathUpdate::Interactive, "\n");
+        assert!(matches!(plan, UpdatePlan::Skip));
+
+        let plan = update_changed_path_component(PathUpdate::Interactive, "c\n");
+        assert!(matches!(plan, UpdatePlan::Abort));
+    }
+}
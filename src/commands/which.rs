@@ -0,0 +1,137 @@
+use std::path::PathBuf;
+
+use anyhow::{Context, bail};
+use serde::Serialize;
+
+use crate::{
+    channel::{Channel, InstalledFile, resolve_command},
+    config::Config,
+    manifest::Manifest,
+    toolchain::Toolchain,
+};
+
+/// Something that `miden <name>` runs, as printed by `midenup which`.
+#[derive(Serialize)]
+struct Dispatch {
+    /// The name passed to `miden`.
+    name: String,
+    /// Whether `name` is a component or an alias.
+    kind: DispatchKind,
+    /// The component whose executable gets run.
+    component: String,
+    /// The program that gets run.
+    program: PathBuf,
+    /// The arguments passed to `program` before the user's own.
+    args: Vec<String>,
+}
+
+#[derive(Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+enum DispatchKind {
+    Component,
+    Alias,
+}
+
+impl Dispatch {
+    /// The program along with its arguments, as they would be typed in a shell.
+    fn command_line(&self) -> String {
+        std::iter::once(self.program.display().to_string())
+            .chain(self.args.iter().cloned())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// Prints what `miden <name>` runs in the active toolchain, or every executable and alias it
+/// provides if `name` is `None`.
+///
+/// Nothing is installed: the active toolchain must already be installed.
+pub fn which(
+    config: &Config,
+    local_manifest: &Manifest,
+    name: Option<&str>,
+    json: bool,
+) -> anyhow::Result<()> {
+    let (toolchain, _) = Toolchain::current(config)?;
+    let Some(channel) = local_manifest.get_channel(&toolchain.channel) else {
+        bail!(
+            "the active toolchain '{}' is not installed, run `midenup install {}`",
+            toolchain.channel,
+            toolchain.channel
+        );
+    };
+
+    let mut dispatches = dispatches(config, channel)?;
+    if let Some(name) = name {
+        // Aliases take precedence over components, like in `miden <name>`.
+        let Some(index) = dispatches
+            .iter()
+            .position(|dispatch| dispatch.name == name && dispatch.kind == DispatchKind::Alias)
+            .or_else(|| dispatches.iter().position(|dispatch| dispatch.name == name))
+        else {
+            bail!("'{name}' is neither an alias nor a component of toolchain {}", channel.name);
+        };
+        dispatches = vec![dispatches.swap_remove(index)];
+    }
+
+    if json {
+        let json = match name {
+            Some(_) => serde_json::to_string_pretty(&dispatches[0]),
+            None => serde_json::to_string_pretty(&dispatches),
+        };
+        println!("{}", json.context("Couldn't serialize executables")?);
+    } else if name.is_some() {
+        println!("{}", dispatches[0].command_line());
+    } else {
+        for dispatch in &dispatches {
+            println!("{}: {}", dispatch.name, dispatch.command_line());
+        }
+    }
+
+    Ok(())
+}
+
+/// Resolves every component executable and alias of `channel`, components first, each sorted by
+/// name.
+fn dispatches(config: &Config, channel: &Channel) -> anyhow::Result<Vec<Dispatch>> {
+    let mut components = Vec::new();
+    let mut aliases = Vec::new();
+    for component in channel.components.iter() {
+        let mut resolutions = Vec::new();
+        if matches!(
+            component.get_installed_file(),
+            InstalledFile::Executable { alias_only: false, .. }
+        ) {
+            resolutions.push((
+                component.name.to_string(),
+                DispatchKind::Component,
+                component.get_call_format(),
+            ));
+        }
+        for (alias, commands) in component.aliases.iter() {
+            resolutions.push((alias.clone(), DispatchKind::Alias, commands.clone()));
+        }
+
+        for (name, kind, commands) in resolutions {
+            let mut command = resolve_command(&commands, channel, component, config)?
+                .into_iter()
+                .map(|word| word.to_string_lossy().into_owned());
+            let dispatch = Dispatch {
+                name,
+                component: component.name.to_string(),
+                // Every alias and call format has at least one word.
+                program: PathBuf::from(command.next().unwrap_or_default()),
+                args: command.collect(),
+                kind,
+            };
+            match dispatch.kind {
+                DispatchKind::Component => components.push(dispatch),
+                DispatchKind::Alias => aliases.push(dispatch),
+            }
+        }
+    }
+    components.sort_by(|a, b| a.name.cmp(&b.name));
+    aliases.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Ok(components.into_iter().chain(aliases).collect())
+}
@@ -1,18 +1,27 @@
 use std::{
     ffi::{OsStr, OsString},
-    path::PathBuf,
+    num::NonZeroUsize,
+    path::{Path, PathBuf},
 };
 
-use anyhow::{Context, bail};
+use anyhow::{Context, anyhow, bail};
+use clap::ValueEnum;
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
 
 use crate::{
     artifact::TargetTriple,
     channel::Channel,
     manifest::{Manifest, ManifestError},
+    message::Message,
+    options::{InstallationOptions, MessageFormat},
     toolchain::Toolchain,
     utils,
 };
 
+/// The name of the file in `MIDENUP_HOME` which holds the [Settings].
+pub const SETTINGS_FILE_NAME: &str = "config.toml";
+
 /// This struct holds contextual information about the environment in which midenup/miden will
 /// operate under. This meant to be a *read-only* data structure.
 #[derive(Debug)]
@@ -26,6 +35,8 @@ pub struct Config {
     /// specified via the `MIDENUP_HOME` environment variable, like so:
     ///
     /// `MIDENUP_HOME=/path/to/custom/home midenup`
+    ///
+    /// See [Config::resolve_home].
     pub midenup_home: PathBuf,
     /// The path to `$CARGO_HOME`
     pub cargo_home: PathBuf,
@@ -33,7 +44,8 @@ pub struct Config {
     /// toolchains with their respective components.
     ///
     /// It is usually going to be obtained from `curl`ing the URI present in
-    /// [`crate::manifest::Manifest::PUBLISHED_MANIFEST_URI`], although it could also be obtained
+    /// [`crate::manifest::Manifest::PUBLISHED_MANIFEST_URI`], or from the copy of it cached in
+    /// `$MIDENUP_HOME/cache` (see [Manifest::load_cached]), although it could also be obtained
     /// from a different source (be it a local file or a different URL) for debugging purposes. The
     /// source can be specified via the `MIDENUP_MANIFEST_URI` environment variable. For example:
     ///
@@ -41,6 +53,8 @@ pub struct Config {
     ///
     /// For more information about the Manifest's fields and format, see [Manifest].
     pub manifest: Manifest,
+    /// The URI from which [Config::manifest] was loaded.
+    pub manifest_uri: String,
     /// This flag is used to detect/distinguish when midenup is being used in tests.
     ///
     /// At the time of writing, this is mostly done to install debug builds of the various miden
@@ -52,17 +66,169 @@ pub struct Config {
     /// be rare), we fail to obtain the system's target triple, then we leave it as `None`. In
     /// those cases, we will simply install everything from source.
     pub target: TargetTriple,
+    /// When set, midenup does not access the network. Manifests must be loaded from `file://`
+    /// URIs, and components can't be installed from remote artifacts.
+    ///
+    /// NOTE: This does not affect `cargo`, which needs to be configured to work offline separately
+    /// (e.g. via `CARGO_NET_OFFLINE=true`).
+    pub offline: bool,
+    /// The format used to report the progress of installs and updates.
+    ///
+    /// Defaults to [MessageFormat::Human]. With [MessageFormat::Json], [Config::emit] prints
+    /// events to stdout and [Config::status] prints to stderr.
+    pub message_format: MessageFormat,
+    /// The defaults set with `midenup config set`, read from `$MIDENUP_HOME/config.toml`.
+    pub settings: Settings,
+}
+
+/// Persistent defaults for options that would otherwise have to be passed to every command,
+/// managed with `midenup config set/get/unset`.
+///
+/// Every setting is overridden by its command line flag, and for those that have one, by its
+/// environment variable. Unset settings fall back to midenup's built-in defaults.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Settings {
+    /// The default verbosity of installs, like passing `--verbose` that many times.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verbose: Option<u8>,
+    /// The default number of components installed concurrently, see `--jobs`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jobs: Option<NonZeroUsize>,
+    /// The timeout of HTTP transfers, in seconds. Overridden by `MIDENUP_HTTP_TIMEOUT`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub http_timeout: Option<u64>,
+    /// The URI of the upstream manifest. Overridden by `--manifest-uri` and by
+    /// `MIDENUP_MANIFEST_URI`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub manifest_uri: Option<String>,
+}
+
+/// The name of a [Settings] field, as used by `midenup config` and in `config.toml`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SettingKey {
+    Verbose,
+    Jobs,
+    HttpTimeout,
+    ManifestUri,
+}
+
+impl Settings {
+    /// Loads the settings from `midenup_home`. If the file doesn't exist, every setting is unset.
+    pub fn load(midenup_home: &Path) -> anyhow::Result<Settings> {
+        let path = midenup_home.join(SETTINGS_FILE_NAME);
+        let contents = match std::fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                return Ok(Settings::default());
+            },
+            Err(err) => {
+                return Err(err)
+                    .with_context(|| format!("failed to read settings at '{}'", path.display()));
+            },
+        };
+
+        toml::from_str(&contents)
+            .with_context(|| format!("invalid settings at '{}'", path.display()))
+    }
+
+    /// Writes the settings to `midenup_home`.
+    pub fn save(&self, midenup_home: &Path) -> anyhow::Result<()> {
+        std::fs::create_dir_all(midenup_home).with_context(|| {
+            format!("failed to create midenup's home at '{}'", midenup_home.display())
+        })?;
+        let path = midenup_home.join(SETTINGS_FILE_NAME);
+        let contents = toml::to_string_pretty(self).context("failed to serialize settings")?;
+        crate::utils::fs::write_atomically(&path, contents.as_bytes())
+            .with_context(|| format!("failed to write settings at '{}'", path.display()))
+    }
+
+    /// Returns the value of `key`, if set.
+    pub fn get(&self, key: SettingKey) -> Option<String> {
+        match key {
+            SettingKey::Verbose => self.verbose.map(|verbose| verbose.to_string()),
+            SettingKey::Jobs => self.jobs.map(|jobs| jobs.to_string()),
+            SettingKey::HttpTimeout => self.http_timeout.map(|timeout| timeout.to_string()),
+            SettingKey::ManifestUri => self.manifest_uri.clone(),
+        }
+    }
+
+    /// Sets `key` to `value`, which must be valid for that setting.
+    pub fn set(&mut self, key: SettingKey, value: &str) -> anyhow::Result<()> {
+        let invalid = || format!("invalid value '{value}' for setting '{}'", key.name());
+        match key {
+            SettingKey::Verbose => self.verbose = Some(value.parse().with_context(invalid)?),
+            SettingKey::Jobs => self.jobs = Some(value.parse().with_context(invalid)?),
+            SettingKey::HttpTimeout => {
+                self.http_timeout = Some(value.parse().with_context(invalid)?)
+            },
+            SettingKey::ManifestUri => self.manifest_uri = Some(value.to_string()),
+        }
+        Ok(())
+    }
+
+    /// Unsets `key`, so that its built-in default is used instead.
+    pub fn unset(&mut self, key: SettingKey) {
+        match key {
+            SettingKey::Verbose => self.verbose = None,
+            SettingKey::Jobs => self.jobs = None,
+            SettingKey::HttpTimeout => self.http_timeout = None,
+            SettingKey::ManifestUri => self.manifest_uri = None,
+        }
+    }
+
+    /// Fills in the options of `options` which were not passed on the command line.
+    pub fn apply(&self, options: &mut InstallationOptions) {
+        if options.verbose == 0 {
+            options.verbose = self.verbose.unwrap_or_default();
+        }
+        options.jobs = options.jobs.or(self.jobs);
+    }
+}
+
+impl SettingKey {
+    /// The name of the setting, e.g. `http-timeout`.
+    pub fn name(&self) -> String {
+        self.to_possible_value()
+            .expect("settings are never skipped")
+            .get_name()
+            .to_string()
+    }
+}
+
+/// An exclusive lock on `MIDENUP_HOME`, see [Config::lock]. Released when dropped.
+#[derive(Debug)]
+pub struct MidenupLock {
+    _file: std::fs::File,
 }
 
 impl Config {
+    /// Loads the [Settings] from `midenup_home`, and then the upstream manifest from
+    /// `manifest_uri`. If no `manifest_uri` is given, the one in the settings is used, falling
+    /// back to [Manifest::PUBLISHED_MANIFEST_URI].
+    ///
+    /// Invalid settings are ignored with a warning rather than failing, so that they can still be
+    /// fixed with `midenup config set/unset`.
     pub fn init(
         working_directory: PathBuf,
         midenup_home: PathBuf,
         cargo_home: PathBuf,
-        manifest_uri: impl AsRef<str>,
+        manifest_uri: Option<String>,
         debug: bool,
+        offline: bool,
+        refresh: bool,
     ) -> anyhow::Result<Config> {
-        let manifest = Manifest::load_from(manifest_uri)?;
+        let settings = Settings::load(&midenup_home).unwrap_or_else(|err| {
+            eprintln!("{}: {err:#}, ignoring them", "warn".yellow());
+            Settings::default()
+        });
+        crate::external::set_default_http_timeout(settings.http_timeout.unwrap_or_default());
+
+        let manifest_uri = manifest_uri
+            .or_else(|| settings.manifest_uri.clone())
+            .unwrap_or_else(|| Manifest::PUBLISHED_MANIFEST_URI.to_string());
+        let manifest_cache = midenup_home.join("cache").join("channel-manifest.json");
+        let manifest = Manifest::load_cached(&manifest_uri, offline, &manifest_cache, refresh)?;
 
         let target = {
             let target = env!("TARGET");
@@ -74,13 +240,110 @@ impl Config {
             midenup_home,
             cargo_home,
             manifest,
+            manifest_uri,
             debug,
             target,
+            offline,
+            message_format: MessageFormat::default(),
+            settings,
         };
 
         Ok(config)
     }
 
+    /// Resolves the path to midenup's home, see [Config::midenup_home]. Both `midenup` and
+    /// `miden` resolve it this way.
+    ///
+    /// `midenup_home` is the path given on the command line, if any. Otherwise, the first of the
+    /// following is used:
+    ///
+    /// * `$MIDENUP_HOME`
+    /// * `$XDG_DATA_HOME/midenup`
+    /// * The OS's data directory (see [dirs::data_dir]), followed by `midenup`
+    /// * `~/.local/share/midenup`
+    ///
+    /// `midenup doctor` warns when the `miden` symlink points to another midenup executable,
+    /// which resolves a different home.
+    pub fn resolve_home(midenup_home: Option<PathBuf>) -> anyhow::Result<PathBuf> {
+        if let Some(midenup_home) =
+            midenup_home.or_else(|| std::env::var_os("MIDENUP_HOME").map(PathBuf::from))
+        {
+            return Ok(midenup_home);
+        }
+
+        // Always respect XDG dirs if set
+        std::env::var_os("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .or_else(dirs::data_dir)
+            // If for whatever reason, we can't access the data dir, we fall back to .local/share
+            .or_else(|| dirs::home_dir().map(|home| home.join(".local").join("share")))
+            .map(|dir| dir.join("midenup"))
+            .ok_or_else(|| {
+                anyhow!(
+                    "Failed to set midenup directory. Consider setting a value for XDG_DATA_HOME \
+                     in your shell's profile"
+                )
+            })
+    }
+
+    /// Prints a human-readable status message.
+    ///
+    /// When emitting JSON, these are printed to stderr so that stdout only contains JSON events.
+    pub fn status(&self, msg: impl core::fmt::Display) {
+        match self.message_format {
+            MessageFormat::Human => println!("{msg}"),
+            MessageFormat::Json => eprintln!("{msg}"),
+        }
+    }
+
+    /// Prints a machine-readable event, if JSON output was requested.
+    pub fn emit(&self, message: &Message) {
+        if self.message_format == MessageFormat::Json {
+            println!("{}", serde_json::to_string(message).expect("messages are serializable"));
+        }
+    }
+
+    /// Acquires an exclusive lock on `MIDENUP_HOME`, which must be held by every operation that
+    /// modifies the installed toolchains or the local manifest. If another midenup process holds
+    /// the lock, this waits for it to be released.
+    ///
+    /// The lock is released once the returned [MidenupLock] is dropped.
+    ///
+    /// NOTE: The local manifest might have been modified while waiting, so it should be (re)loaded
+    /// *after* acquiring the lock.
+    pub fn lock(&self) -> anyhow::Result<MidenupLock> {
+        std::fs::create_dir_all(&self.midenup_home).with_context(|| {
+            format!("failed to create midenup's home at '{}'", self.midenup_home.display())
+        })?;
+        let lock_path = self.midenup_home.join(".lock");
+        let lock_file = std::fs::OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&lock_path)
+            .with_context(|| format!("failed to open lock file '{}'", lock_path.display()))?;
+
+        match lock_file.try_lock() {
+            Ok(()) => {},
+            Err(std::fs::TryLockError::WouldBlock) => {
+                eprintln!(
+                    "{}: another midenup operation is in progress, waiting for it to finish",
+                    "info".white().bold()
+                );
+                lock_file.lock().with_context(|| {
+                    format!("failed to acquire lock on '{}'", lock_path.display())
+                })?;
+            },
+            Err(std::fs::TryLockError::Error(err)) => {
+                return Err(err).with_context(|| {
+                    format!("failed to acquire lock on '{}'", lock_path.display())
+                });
+            },
+        }
+
+        Ok(MidenupLock { _file: lock_file })
+    }
+
     /// Get the [Manifest] for locally installed toolchains
     pub fn local_manifest(&self) -> anyhow::Result<Manifest> {
         let local_manifest_path = self.midenup_home.join("manifest").with_extension("json");
@@ -88,7 +351,7 @@ impl Config {
             "file://{}",
             local_manifest_path.to_str().context("Couldn't convert miden directory")?,
         );
-        match Manifest::load_from(local_manifest_uri) {
+        match Manifest::load_from(local_manifest_uri, self.offline) {
             Ok(manifest) => Ok(manifest),
             Err(ManifestError::Empty | ManifestError::Missing(_)) => Ok(Manifest::default()),
             Err(err) => Err(err),
@@ -96,16 +359,23 @@ impl Config {
         .context("unable to load local manifest")
     }
 
-    pub fn update_opt_symlinks(&self, config: &Config) -> anyhow::Result<()> {
+    /// Returns the upstream [Channel] of the currently active toolchain.
+    pub fn active_channel(&self) -> anyhow::Result<&Channel> {
         let (current_toolchain, _) = Toolchain::current(self)?;
 
-        // Directory which point to the directory where symlinks are stored
-        let opt_dir = self.midenup_home.join("opt");
-
         let Some(active_channel) = self.manifest.get_channel(&current_toolchain.channel) else {
             bail!("channel '{}' doesn't exist or is unavailable", current_toolchain.channel);
         };
 
+        Ok(active_channel)
+    }
+
+    pub fn update_opt_symlinks(&self, config: &Config) -> anyhow::Result<()> {
+        let active_channel = self.active_channel()?;
+
+        // Directory which point to the directory where symlinks are stored
+        let opt_dir = self.midenup_home.join("opt");
+
         // If the currently active channel doesn't exist, then there's nothing to update regarding
         // the opt/ symlink.
         if !active_channel.get_channel_dir(config).exists() {
@@ -145,12 +415,22 @@ impl Config {
         Ok(())
     }
 
-    /// Executes a command.
+    /// Executes a command. If given, `arg0` is passed to it as `argv[0]` instead of `target_exe`.
+    ///
+    /// Components' executables are run from their channel's `bin` directory (see
+    /// [crate::channel::resolve_command]), and are given the name of their symlink in `opt/` (e.g.
+    /// `miden vm`) as `arg0`, since that is the name clap displays in help messages.
+    ///
+    /// `env` holds additional environment variables, usually those of the component being run
+    /// (see [crate::channel::Component::env]). They can't override the variables set by midenup
+    /// itself, like `MIDEN_SYSROOT` or `PATH`.
     pub fn execute_command(
         &self,
         active_toolchain: &Channel,
         target_exe: &OsStr,
         args: &[OsString],
+        arg0: Option<&OsStr>,
+        env: &[(String, OsString)],
     ) -> Result<std::process::Child, std::io::Error> {
         let toolchain_name = active_toolchain.name.to_string();
         let sysroot = self.midenup_home.join("toolchains").join(&toolchain_name);
@@ -165,7 +445,16 @@ impl Config {
             None => toolchain_opt.into_os_string(),
         };
 
-        std::process::Command::new(target_exe)
+        let mut command = std::process::Command::new(target_exe);
+        // Elsewhere, the program can't be given another name than its own.
+        #[cfg(unix)]
+        if let Some(arg0) = arg0 {
+            std::os::unix::process::CommandExt::arg0(&mut command, arg0);
+        }
+        #[cfg(not(unix))]
+        let _ = arg0;
+        command
+            .envs(env.iter().map(|(var, value)| (var, value)))
             .env("MIDENUP_HOME", &self.midenup_home)
             .env("MIDENUP_TOOLCHAIN", &toolchain_name)
             .env("MIDEN_SYSROOT", &sysroot)
@@ -176,3 +465,80 @@ impl Config {
             .spawn()
     }
 }
+
+/// A [Config] for unit tests, with every directory in `home`.
+#[cfg(test)]
+pub(crate) fn test_config(home: PathBuf) -> Config {
+    Config {
+        working_directory: home.clone(),
+        midenup_home: home.clone(),
+        cargo_home: home,
+        manifest: Manifest::default(),
+        manifest_uri: String::new(),
+        debug: true,
+        target: TargetTriple::Custom(String::from("x86_64-unknown-linux-gnu")),
+        offline: false,
+        message_format: MessageFormat::Human,
+        settings: Settings::default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroUsize;
+
+    use super::{SettingKey, Settings};
+    use crate::options::InstallationOptions;
+
+    /// Settings only fill in the options that were not passed on the command line.
+    #[test]
+    fn settings_fill_in_unset_options() {
+        let settings = Settings {
+            verbose: Some(2),
+            jobs: NonZeroUsize::new(4),
+            ..Default::default()
+        };
+
+        let mut options = InstallationOptions::default();
+        settings.apply(&mut options);
+        assert_eq!(options.verbose, 2);
+        assert_eq!(options.jobs, NonZeroUsize::new(4));
+
+        let mut options = InstallationOptions {
+            verbose: 1,
+            jobs: NonZeroUsize::new(8),
+            ..Default::default()
+        };
+        settings.apply(&mut options);
+        assert_eq!(options.verbose, 1);
+        assert_eq!(options.jobs, NonZeroUsize::new(8));
+
+        let mut options = InstallationOptions::default();
+        Settings::default().apply(&mut options);
+        assert_eq!(options.verbose, 0);
+        assert_eq!(options.jobs, None);
+    }
+
+    /// Settings are validated when set, and written to `config.toml` under their kebab-case names.
+    #[test]
+    fn settings_round_trip() {
+        let home = tempdir::TempDir::new("settings_round_trip").unwrap();
+        assert_eq!(Settings::load(home.path()).unwrap(), Settings::default());
+
+        let mut settings = Settings::default();
+        settings.set(SettingKey::HttpTimeout, "60").unwrap();
+        settings.set(SettingKey::ManifestUri, "file:///tmp/manifest.json").unwrap();
+        let err = settings.set(SettingKey::Jobs, "0").unwrap_err();
+        assert_eq!(err.to_string(), "invalid value '0' for setting 'jobs'");
+        settings.save(home.path()).unwrap();
+
+        let contents = std::fs::read_to_string(home.path().join("config.toml")).unwrap();
+        assert!(contents.contains("http-timeout = 60"), "{contents}");
+        let settings = Settings::load(home.path()).unwrap();
+        assert_eq!(settings.get(SettingKey::HttpTimeout).as_deref(), Some("60"));
+        assert_eq!(settings.get(SettingKey::Jobs), None);
+
+        std::fs::write(home.path().join("config.toml"), "timeout = 60\n").unwrap();
+        assert!(Settings::load(home.path()).is_err());
+    }
+}
@@ -6,44 +6,265 @@
 // over importing, in order to avoid duplicate `use` declarations.
 
 const HTTP_ERROR_CODES: std::ops::Range<u32> = 400..500;
+const HTTP_SERVER_ERROR_CODES: std::ops::Range<u32> = 500..600;
 
+/// Environment variable used to override how many times a failed HTTP transfer is retried.
+pub const HTTP_RETRIES_ENV: &str = "MIDENUP_HTTP_RETRIES";
+const DEFAULT_HTTP_RETRIES: u32 = 3;
+
+/// Environment variable used to override the timeout of HTTP transfers, in seconds.
+pub const HTTP_TIMEOUT_ENV: &str = "MIDENUP_HTTP_TIMEOUT";
+const DEFAULT_HTTP_TIMEOUT: u64 = 30;
+
+/// The timeout used when [HTTP_TIMEOUT_ENV] is not set, if not zero. See
+/// [set_default_http_timeout].
+static HTTP_TIMEOUT_OVERRIDE: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+
+/// Environment variable set by midenup when running in offline mode, in which case remote
+/// artifacts are not downloaded. See [offline_from_env].
+pub const OFFLINE_ENV: &str = "MIDENUP_OFFLINE";
+
+/// Environment variable used to override the `cargo` executable that midenup and the install
+/// script run, e.g. when `cargo` is not on `PATH`. See [cargo_command].
+pub const CARGO_ENV: &str = "MIDENUP_CARGO";
+
+/// The minimum time between two progress reports of a download, see [describe_progress].
+const PROGRESS_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Whether [OFFLINE_ENV] enables offline mode. midenup, `miden` and the install script all read it
+/// the same way, see [flag_from_env].
+pub fn offline_from_env() -> bool {
+    flag_from_env(OFFLINE_ENV)
+}
+
+/// Whether the environment variable `var` is set to a value that enables a flag: any value other
+/// than an empty one, `0`, `false`, `no`, `off`, `n` or `f` (in any case).
+pub fn flag_from_env(var: &str) -> bool {
+    std::env::var(var).is_ok_and(|value| {
+        !matches!(
+            value.trim().to_ascii_lowercase().as_str(),
+            "" | "0" | "false" | "no" | "off" | "n" | "f"
+        )
+    })
+}
+
+/// Returns a command running the `cargo` executable named by [CARGO_ENV], or the one on `PATH`
+/// if it is unset or empty.
+pub fn cargo_command() -> std::process::Command {
+    match std::env::var_os(CARGO_ENV) {
+        Some(cargo) if !cargo.is_empty() => std::process::Command::new(cargo),
+        _ => std::process::Command::new("cargo"),
+    }
+}
+
+/// Why an HTTP transfer failed, which determines whether it is worth retrying. See
+/// [retry_with_backoff].
+#[allow(dead_code)]
+pub enum TransferError<E> {
+    /// Connection errors and 5xx responses, which might go away on their own.
+    Transient(E),
+    /// Everything else, e.g. 4xx responses. These are not retried.
+    Permanent(E),
+}
+
+#[allow(dead_code)]
+impl<E> TransferError<E> {
+    /// Classifies a failed `curl` transfer, wrapping `err` accordingly.
+    pub fn from_curl(error: &curl::Error, err: E) -> Self {
+        let transient = error.is_couldnt_connect()
+            || error.is_couldnt_resolve_host()
+            || error.is_couldnt_resolve_proxy()
+            || error.is_operation_timedout()
+            || error.is_send_error()
+            || error.is_recv_error()
+            || error.is_partial_file()
+            || error.is_got_nothing();
+        if transient {
+            TransferError::Transient(err)
+        } else {
+            TransferError::Permanent(err)
+        }
+    }
+}
+
+/// Returns the timeout of HTTP transfers: `$MIDENUP_HTTP_TIMEOUT` seconds, or the timeout set with
+/// [set_default_http_timeout] (30 by default).
+#[allow(dead_code)]
+pub fn http_timeout() -> std::time::Duration {
+    let timeout = std::env::var(HTTP_TIMEOUT_ENV)
+        .ok()
+        .and_then(|timeout| timeout.trim().parse::<u64>().ok())
+        .unwrap_or_else(|| {
+            match HTTP_TIMEOUT_OVERRIDE.load(std::sync::atomic::Ordering::Relaxed) {
+                0 => DEFAULT_HTTP_TIMEOUT,
+                timeout => timeout,
+            }
+        });
+    std::time::Duration::from_secs(timeout)
+}
+
+/// Makes [http_timeout] default to `timeout` seconds, e.g. from midenup's settings. A timeout of
+/// zero restores the built-in default.
+#[allow(dead_code)]
+pub fn set_default_http_timeout(timeout: u64) {
+    HTTP_TIMEOUT_OVERRIDE.store(timeout, std::sync::atomic::Ordering::Relaxed);
+}
+
+/// Makes transfers done with `handle` fail, instead of hanging, if connecting takes longer than
+/// [http_timeout] or if the transfer stalls for that long. Every `curl` handle must be configured
+/// with this.
 #[allow(dead_code)]
-pub fn install_artifact(uri: &str, to: impl AsRef<std::path::Path>) -> Result<(), String> {
+pub fn configure_timeouts(handle: &mut curl::easy::Easy) -> Result<(), curl::Error> {
+    let timeout = http_timeout();
+    handle.connect_timeout(timeout)?;
+    // Slow transfers are fine, as long as they make progress.
+    handle.low_speed_limit(1)?;
+    handle.low_speed_time(timeout)?;
+    Ok(())
+}
+
+/// Runs `attempt` until it succeeds or fails permanently. Transient failures are retried up to
+/// `$MIDENUP_HTTP_RETRIES` times (3 by default), doubling the delay between attempts each time.
+#[allow(dead_code)]
+pub fn retry_with_backoff<T, E>(
+    mut attempt: impl FnMut() -> Result<T, TransferError<E>>,
+) -> Result<T, E> {
+    let retries = std::env::var(HTTP_RETRIES_ENV)
+        .ok()
+        .and_then(|retries| retries.trim().parse::<u32>().ok())
+        .unwrap_or(DEFAULT_HTTP_RETRIES);
+
+    let mut delay = std::time::Duration::from_millis(500);
+    let mut retried = 0;
+    loop {
+        match attempt() {
+            Ok(value) => return Ok(value),
+            Err(TransferError::Transient(_)) if retried < retries => {
+                std::thread::sleep(delay);
+                delay *= 2;
+                retried += 1;
+            },
+            Err(TransferError::Transient(err) | TransferError::Permanent(err)) => return Err(err),
+        }
+    }
+}
+
+/// Describes how much of `component`'s artifact has been downloaded so far. `total` is 0 when the
+/// size of the artifact is unknown, i.e. when the server sent no `Content-Length`.
+#[allow(dead_code)]
+pub fn describe_progress(component: &str, downloaded: f64, total: f64) -> String {
+    const MIB: f64 = 1024.0 * 1024.0;
+
+    if total > 0.0 {
+        format!(
+            "downloading {component}: {:.1} MiB / {:.1} MiB ({:.0}%)",
+            downloaded / MIB,
+            total / MIB,
+            100.0 * downloaded / total
+        )
+    } else {
+        format!("downloading {component}: {:.1} MiB", downloaded / MIB)
+    }
+}
+
+/// Downloads (or copies) the artifact at `uri` into `to`.
+///
+/// If `sha256` is non-empty, the artifact's contents are checked against it before being put in
+/// place. See [verify_sha256].
+///
+/// If `progress` is set, the progress of downloads that take longer than [PROGRESS_INTERVAL] is
+/// printed to stderr, at most once per interval.
+#[allow(dead_code)]
+pub fn install_artifact(
+    component: &str,
+    uri: &str,
+    sha256: &str,
+    to: impl AsRef<std::path::Path>,
+    progress: bool,
+) -> Result<(), String> {
     use std::io::Write;
 
     let to = to.as_ref();
     if let Some(binary_path) = uri.strip_prefix("file://") {
+        if !sha256.is_empty() {
+            let data = std::fs::read(binary_path)
+                .map_err(|err| format!("failed to read artifact {binary_path}: {err}"))?;
+            verify_sha256(component, &data, sha256);
+        }
         std::fs::copy(binary_path, to)
             .map_err(|err| format!("failed to copy {binary_path} -> {}: {err}", to.display()))?;
     } else if uri.starts_with("https://") {
-        let mut data = Vec::new();
-        {
+        if offline_from_env() {
+            return Err(format!("cannot download '{uri}' in offline mode"));
+        }
+
+        let data = retry_with_backoff(|| {
+            let mut data = Vec::new();
             let mut handle = curl::easy::Easy::new();
-            handle.follow_location(true).map_err(|_| String::from("failed to setup curl"))?;
+            handle
+                .follow_location(true)
+                .and_then(|_| handle.progress(progress))
+                .and_then(|_| configure_timeouts(&mut handle))
+                .map_err(|_| TransferError::Permanent(String::from("failed to setup curl")))?;
             handle.url(uri).map_err(|error| {
-                format!("invalid artifact uri '{uri}': {}", error.description())
+                TransferError::Permanent(format!(
+                    "invalid artifact uri '{uri}': {}",
+                    error.description()
+                ))
+            })?;
+
+            {
+                let mut last_report = std::time::Instant::now();
+                let mut transfer = handle.transfer();
+                transfer
+                    .write_function(|new_data| {
+                        data.extend_from_slice(new_data);
+                        Ok(new_data.len())
+                    })
+                    .unwrap();
+                // NOTE: This is only called if progress reporting was enabled on the handle.
+                transfer
+                    .progress_function(|total, downloaded, _, _| {
+                        if last_report.elapsed() >= PROGRESS_INTERVAL {
+                            last_report = std::time::Instant::now();
+                            eprintln!("{}", describe_progress(component, downloaded, total));
+                        }
+                        true
+                    })
+                    .unwrap();
+                transfer.perform().map_err(|error| {
+                    let err = if error.is_operation_timedout() {
+                        format!("transfer timed out for '{uri}': {error}")
+                    } else {
+                        format!("transfer failed for '{uri}': {error}")
+                    };
+                    TransferError::from_curl(&error, err)
+                })?;
+            }
+
+            // NOTE: The response code is only known once the transfer is performed.
+            let response_code = handle.response_code().map_err(|err| {
+                TransferError::Permanent(format!(
+                    "request failed for '{uri}' with unknown status: {err}"
+                ))
             })?;
-            let response_code = handle
-                .response_code()
-                .map_err(|err| format!("request failed for '{uri}' with unknown status: {err}"))?;
             if HTTP_ERROR_CODES.contains(&response_code) {
-                return Err(format!("request failed for '{uri}' with status {response_code}"));
+                return Err(TransferError::Permanent(format!(
+                    "request failed for '{uri}' with status {response_code}"
+                )));
+            }
+            if HTTP_SERVER_ERROR_CODES.contains(&response_code) {
+                return Err(TransferError::Transient(format!(
+                    "request failed for '{uri}' with status {response_code}"
+                )));
             }
 
-            let mut transfer = handle.transfer();
-            transfer
-                .write_function(|new_data| {
-                    data.extend_from_slice(new_data);
-                    Ok(new_data.len())
-                })
-                .unwrap();
-            transfer
-                .perform()
-                .map_err(|error| format!("transfer failed for '{uri}': {error}"))?
-        }
+            Ok(data)
+        })?;
         if data.is_empty() {
             return Err(format!("invalid artifact: content downloaded from '{uri}' is empty"));
         }
+        verify_sha256(component, &data, sha256);
         let tmp = to.with_extension("tmp");
         let mut file = std::fs::File::create(&tmp).map_err(|error| {
             format!("failed to create temporary file '{}' for artifact: {error}", to.display())
@@ -65,39 +286,110 @@ pub fn install_artifact(uri: &str, to: impl AsRef<std::path::Path>) -> Result<()
     Ok(())
 }
 
+/// Checks that the SHA-256 digest of `data` matches the hex-encoded `expected` digest. An empty
+/// `expected` digest means that the manifest provides no checksum, in which case nothing is
+/// checked.
+///
+/// A mismatch means the artifact was corrupted or tampered with, so rather than falling back to
+/// building from source, we abort the installation entirely.
+#[allow(dead_code)]
+pub fn verify_sha256(component: &str, data: &[u8], expected: &str) {
+    use std::fmt::Write;
+
+    if expected.is_empty() {
+        return;
+    }
+
+    let digest = <sha2::Sha256 as sha2::Digest>::digest(data);
+    let mut actual = String::with_capacity(64);
+    for byte in digest.iter() {
+        write!(&mut actual, "{byte:02x}").expect("failed to format artifact digest");
+    }
+
+    if !actual.eq_ignore_ascii_case(expected.trim()) {
+        panic!(
+            "checksum mismatch for the artifact of component '{component}': expected sha256 \
+             {expected}, got {actual}"
+        );
+    }
+}
+
+/// Copies everything read from `reader` into both `writer` and `log`, until `reader` is closed.
+///
+/// Failing to write is ignored, so that the install script never blocks on a full pipe. Returns how
+/// many bytes were read.
+pub fn tee(
+    mut reader: impl std::io::Read,
+    mut writer: impl std::io::Write,
+    log: &std::sync::Mutex<std::fs::File>,
+) -> usize {
+    use std::io::Write;
+
+    let mut buffer = [0; 8192];
+    let mut total = 0;
+    loop {
+        let read = match reader.read(&mut buffer) {
+            Ok(0) => return total,
+            Ok(read) => read,
+            Err(err) if err.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(_) => return total,
+        };
+        total += read;
+        let _ = writer.write_all(&buffer[..read]).and_then(|_| writer.flush());
+        let _ = log.lock().unwrap().write_all(&buffer[..read]);
+    }
+}
+
+/// Runs `cargo install` for a single component into `root_directory`.
+///
+/// cargo's stderr is still printed, and is also written to `log_path`, so that the reason a
+/// component failed to build is not lost among the output of the other ones.
 #[allow(dead_code)]
 pub fn install_from_source(
     toolchain_flag: &str,
     chosen_profile: &[&str],
-    verbosity_flag: &str,
+    verbosity_flags: &[&str],
+    cargo_jobs_flags: &[&str],
     args: &[&str],
     root_directory: impl AsRef<std::path::Path>,
+    log_path: &std::path::Path,
 ) -> Result<(), String> {
     let root_directory = root_directory.as_ref();
-    let mut command = std::process::Command::new("cargo");
+    let log = std::fs::File::create(log_path)
+        .map_err(|error| format!("failed to create log '{}': {error}", log_path.display()))?;
+    let log = std::sync::Mutex::new(log);
+    let mut command = cargo_command();
     command
                 .arg(toolchain_flag)
                 .arg("install")
                 .arg("--locked")
                 .args(chosen_profile)
-                .arg(verbosity_flag)
+                .args(verbosity_flags)
+                .args(cargo_jobs_flags)
                 .args(args)
                 // Force the install target directory to be $MIDEN_SYSROOT/bin
                 .arg("--root")
                 .arg(root_directory)
                 // Spawn command
-                .stderr(std::process::Stdio::inherit())
+                .stderr(std::process::Stdio::piped())
                 .stdout(std::process::Stdio::inherit());
     let argv = command.get_args().map(|arg| arg.display().to_string()).collect::<Vec<_>>();
     let mut child = command.spawn().map_err(|error| error.to_string())?;
 
     // Await results
-    let status = child
-        .wait()
-        .map_err(|error| format!("failed to execute `cargo {}`: {error}", argv.join(" ")))?;
+    let stderr = child.stderr.take().expect("cargo's stderr is piped");
+    let status = std::thread::scope(|scope| {
+        scope.spawn(|| tee(stderr, std::io::stderr(), &log));
+        child.wait()
+    })
+    .map_err(|error| format!("failed to execute `cargo {}`: {error}", argv.join(" ")))?;
 
     if !status.success() {
-        return Err(format!("command `cargo {}` exited with non-zero status", argv.join(" ")));
+        return Err(format!(
+            "command `cargo {}` exited with non-zero status, see '{}' for its output",
+            argv.join(" "),
+            log_path.display()
+        ));
     }
 
     Ok(())
@@ -6,6 +6,7 @@ pub mod commands;
 pub mod config;
 mod external;
 pub mod manifest;
+pub mod message;
 pub mod miden_wrapper;
 pub mod migration;
 pub mod options;
@@ -1,5 +1,5 @@
 use clap::FromArgMatches;
-use midenup::commands::Midenup;
+use midenup::commands::{ExitCode, Midenup};
 
 fn main() -> anyhow::Result<()> {
     curl::init();
@@ -7,8 +7,15 @@ fn main() -> anyhow::Result<()> {
     let cli = <Midenup as clap::CommandFactory>::command();
     let matches = cli.get_matches();
     let cli = Midenup::from_arg_matches(&matches).map_err(|err| err.exit()).unwrap();
+    cli.color().apply();
 
     let config = cli.config()?;
 
-    cli.execute(&config)
+    let result = cli.execute(&config);
+    if let Err(err) = &result
+        && let Some(ExitCode(code)) = err.downcast_ref()
+    {
+        std::process::exit(*code);
+    }
+    result
 }
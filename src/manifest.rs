@@ -1,12 +1,27 @@
-use std::path::Path;
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, hash_map::Entry},
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
+use colored::Colorize;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
-use crate::channel::{Channel, ChannelAlias, UserChannel};
+use crate::{
+    channel::{Channel, ChannelAlias, UserChannel},
+    external::{TransferError, configure_timeouts, retry_with_backoff},
+    version::Authority,
+};
 
 const MANIFEST_VERSION: semver::Version = semver::Version::new(1, 0, 1);
 const HTTP_ERROR_CODES: std::ops::Range<u32> = 400..500;
+const HTTP_SERVER_ERROR_CODES: std::ops::Range<u32> = 500..600;
+
+/// Environment variable used to override for how long (in seconds) a cached upstream manifest is
+/// used before fetching it again. See [Manifest::load_cached].
+pub const MANIFEST_TTL_ENV: &str = "MIDENUP_MANIFEST_TTL";
+const DEFAULT_MANIFEST_TTL: Duration = Duration::from_secs(60 * 60);
 
 /// The global manifest of all known channels and their toolchains
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -17,6 +32,13 @@ pub struct Manifest {
     date: i64,
     /// The channels described in this manifest
     channels: Vec<Channel>,
+    /// Versions of components pinned via `midenup pin`, keyed by component name.
+    ///
+    /// These are only present in the local manifest, and take precedence over the versions of
+    /// the upstream channels. See [Channel::apply_pins].
+    #[serde(default)]
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pins: BTreeMap<String, semver::Version>,
 }
 
 impl Default for Manifest {
@@ -26,10 +48,21 @@ impl Default for Manifest {
             manifest_version: MANIFEST_VERSION,
             date,
             channels: vec![],
+            pins: BTreeMap::new(),
         }
     }
 }
 
+/// An upstream manifest cached by [Manifest::load_cached].
+#[derive(Serialize, Deserialize)]
+struct CachedManifest {
+    /// The URI the manifest was fetched from.
+    uri: String,
+    /// The UTC timestamp at which the manifest was fetched.
+    fetched_at: i64,
+    manifest: Manifest,
+}
+
 #[derive(Error, Debug)]
 pub enum ManifestError {
     #[error("Manifest file is empty")]
@@ -46,6 +79,31 @@ pub enum ManifestError {
     InternalCurlError(String),
     #[error("unsupported channel manifest URI: `{0}`")]
     Unsupported(String),
+    #[error(
+        "cannot fetch `{0}` in offline mode. Use a `file://` URI for the manifest, e.g. via the \
+         `MIDENUP_MANIFEST_URI` environment variable"
+    )]
+    Offline(String),
+    #[error("invalid crates.io index entry: {0}")]
+    InvalidIndexEntry(String),
+    #[error(
+        "unsupported channel manifest version {found}, this version of midenup only supports \
+         version {supported}. Run `midenup self update` to upgrade midenup"
+    )]
+    UnsupportedVersion {
+        found: semver::Version,
+        supported: semver::Version,
+    },
+}
+
+/// The outcome of [Manifest::update].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct UpdateResult {
+    /// The crates.io packages whose version was bumped, in any channel.
+    pub changed_packages: BTreeSet<String>,
+    /// The bumped packages whose artifacts lost their SHA-256 digest, which has to be added back
+    /// for the new version.
+    pub dropped_checksums: BTreeSet<String>,
 }
 
 impl Manifest {
@@ -54,7 +112,24 @@ impl Manifest {
         "https://0xmiden.github.io/midenup/channel-manifest.json";
 
     /// Parses a [Manifest] from `content`, and returns it in canonical form
+    ///
+    /// Manifests whose `manifest_version` has a different major version than [MANIFEST_VERSION]
+    /// are rejected before being parsed, since their format is not compatible.
     pub fn parse_str(content: &str) -> Result<Manifest, ManifestError> {
+        #[derive(Deserialize)]
+        struct Versioned {
+            manifest_version: semver::Version,
+        }
+        // Manifests without a valid version are reported by the full parse below.
+        if let Ok(Versioned { manifest_version }) = serde_json::from_str::<Versioned>(content)
+            && manifest_version.major != MANIFEST_VERSION.major
+        {
+            return Err(ManifestError::UnsupportedVersion {
+                found: manifest_version,
+                supported: MANIFEST_VERSION,
+            });
+        }
+
         let mut manifest = serde_json::from_str::<Manifest>(content)
             .map_err(|err| ManifestError::Invalid(format!("failed to parse manifest: {err}")))?;
 
@@ -86,48 +161,100 @@ impl Manifest {
         Self::parse_str(&manifest_contents)
     }
 
+    /// Loads a [Manifest] from the given URI, like [Manifest::load_from], caching remote
+    /// manifests at `cache_path`.
+    ///
+    /// A cached manifest fetched from `uri` is used instead of fetching it again, as long as it is
+    /// younger than `$MIDENUP_MANIFEST_TTL` seconds (one hour by default) and `refresh` is not set.
+    /// It is also used, regardless of its age, in offline mode and when fetching the manifest
+    /// fails due to network errors.
+    pub fn load_cached(
+        uri: impl AsRef<str>,
+        offline: bool,
+        cache_path: &Path,
+        refresh: bool,
+    ) -> Result<Manifest, ManifestError> {
+        let uri = uri.as_ref();
+
+        // Local manifests are cheap to load, and might change at any time.
+        if !uri.starts_with("https://") {
+            return Self::load_from(uri, offline);
+        }
+
+        let ttl = std::env::var(MANIFEST_TTL_ENV)
+            .ok()
+            .and_then(|ttl| ttl.trim().parse::<u64>().ok())
+            .map(Duration::from_secs)
+            .unwrap_or(DEFAULT_MANIFEST_TTL);
+
+        let cached = std::fs::read_to_string(cache_path)
+            .ok()
+            .and_then(|contents| serde_json::from_str::<CachedManifest>(&contents).ok())
+            .filter(|cached| cached.uri == uri);
+        if let Some(cached) = &cached {
+            let age = chrono::Utc::now().timestamp().saturating_sub(cached.fetched_at);
+            let fresh = u64::try_from(age).is_ok_and(|age| age < ttl.as_secs());
+            if offline || (fresh && !refresh) {
+                return Ok(cached.manifest.clone());
+            }
+        }
+
+        match Self::load_from(uri, offline) {
+            Ok(manifest) => {
+                let cached = CachedManifest {
+                    uri: uri.to_string(),
+                    fetched_at: chrono::Utc::now().timestamp(),
+                    manifest,
+                };
+                // The cache is only an optimization, so failing to write it is not an error.
+                if let Some(cache_dir) = cache_path.parent()
+                    && std::fs::create_dir_all(cache_dir).is_ok()
+                    && let Ok(contents) = serde_json::to_string(&cached)
+                {
+                    let _ = std::fs::write(cache_path, contents);
+                }
+                Ok(cached.manifest)
+            },
+            Err(err @ (ManifestError::InternalCurlError(_) | ManifestError::WebpageError(_))) => {
+                match cached {
+                    Some(cached) => {
+                        eprintln!(
+                            "{}: {err}. Using the manifest cached on {}",
+                            "warn".yellow(),
+                            chrono::DateTime::from_timestamp(cached.fetched_at, 0)
+                                .unwrap_or_default()
+                        );
+                        Ok(cached.manifest)
+                    },
+                    None => Err(err),
+                }
+            },
+            Err(err) => Err(err),
+        }
+    }
+
     /// Loads a [Manifest] from the given URI.
-    pub fn load_from(uri: impl AsRef<str>) -> Result<Manifest, ManifestError> {
+    ///
+    /// The path of a `file://` URI can start with `~`, which is expanded to the home directory, and
+    /// can be relative to the current working directory.
+    ///
+    /// If `offline` is set, only `file://` URIs are allowed.
+    pub fn load_from(uri: impl AsRef<str>, offline: bool) -> Result<Manifest, ManifestError> {
         let uri = uri.as_ref();
 
         if let Some(manifest_path) = uri.strip_prefix("file://") {
-            return Self::load_from_file(manifest_path);
+            return Self::load_from_file(file_uri_path(manifest_path));
         }
 
         if !uri.starts_with("https://") {
             return Err(ManifestError::Unsupported(uri.to_string()));
         }
 
-        let mut data = Vec::new();
-        let mut handle = curl::easy::Easy::new();
-        handle.url(uri).map_err(|error| {
-            let mut err = format!("Error code {}: ", error.code());
-            err.push_str(error.description());
-            ManifestError::InternalCurlError(err)
-        })?;
-        {
-            let response_code = handle.response_code().map_err(|_| {
-                ManifestError::InternalCurlError(String::from(
-                    "Failed to get response code; despite HTTP protocol supporting it.",
-                ))
-            })?;
-            if HTTP_ERROR_CODES.contains(&response_code) {
-                return Err(ManifestError::WebpageError(uri.to_string()));
-            }
-
-            let mut transfer = handle.transfer();
-            transfer
-                .write_function(|new_data| {
-                    data.extend_from_slice(new_data);
-                    Ok(new_data.len())
-                })
-                .unwrap();
-            transfer.perform().map_err(|error| {
-                let mut err = format!("Error code {}: ", error.code());
-                err.push_str(error.description());
-                ManifestError::InternalCurlError(err)
-            })?
+        if offline {
+            return Err(ManifestError::Offline(uri.to_string()));
         }
+
+        let data = download(uri)?;
         if data.is_empty() {
             return Err(ManifestError::EmptyWebpage(uri.to_string()));
         }
@@ -138,6 +265,22 @@ impl Manifest {
         Self::parse_str(manifest_data)
     }
 
+    /// Checks every channel with [Channel::validate]. All the problems found are listed in the
+    /// error.
+    pub fn validate(&self) -> Result<(), ManifestError> {
+        let problems: Vec<String> = self.channels.iter().flat_map(Channel::problems).collect();
+        if problems.is_empty() {
+            return Ok(());
+        }
+
+        Err(ManifestError::Invalid(format!(
+            "found invalid references between components:\n{}",
+            problems.join("\n")
+        )))
+    }
+
+    /// Returns the `date` of this manifest: when it was generated for the upstream manifest, and
+    /// when it was last written for the local manifest.
     pub fn last_updated(&self) -> chrono::DateTime<chrono::Utc> {
         chrono::DateTime::from_timestamp(self.date, 0).expect("manifest has invalid timestamp")
     }
@@ -178,10 +321,11 @@ impl Manifest {
     /// This can only be determined by the [Manifest], since this definition is dependant on all the
     /// other present [Channel]s
     pub fn is_latest_stable(&self, channel: &Channel) -> bool {
-        self.channels.iter().filter(|c| c.is_stable()).all(|c| {
-            let comparison = channel.name.cmp_precedence(&c.name);
-            matches!(comparison, std::cmp::Ordering::Greater | std::cmp::Ordering::Equal)
-        })
+        channel.is_stable()
+            && self.channels.iter().filter(|c| c.is_stable()).all(|c| {
+                let comparison = channel.name.cmp_precedence(&c.name);
+                matches!(comparison, std::cmp::Ordering::Greater | std::cmp::Ordering::Equal)
+            })
     }
 
     /// Attempts to fetch the version corresponding to the `stable` [Channel].
@@ -282,34 +426,510 @@ impl Manifest {
         }
     }
 
+    /// Returns the pinned component versions, keyed by component name.
+    pub fn pins(&self) -> &BTreeMap<String, semver::Version> {
+        &self.pins
+    }
+
+    /// Pins `component` to `version`, returning the version it was previously pinned to, if any.
+    pub fn pin(&mut self, component: String, version: semver::Version) -> Option<semver::Version> {
+        self.pins.insert(component, version)
+    }
+
+    /// Removes the pin of `component`, returning the version it was pinned to, if any.
+    pub fn unpin(&mut self, component: &str) -> Option<semver::Version> {
+        self.pins.remove(component)
+    }
+
     pub fn get_channels(&self) -> impl Iterator<Item = &Channel> {
         self.channels.iter()
     }
+
+    /// Bumps every component installed from crates.io to the latest published version of its
+    /// package that is semver-compatible with the current one, e.g. from `0.15.0` to `0.15.3` but
+    /// not to `0.16.0`. Yanked versions are never picked, and neither are pre-releases unless the
+    /// component is already on a pre-release of the same version.
+    ///
+    /// The URIs of the component's artifacts are updated to point to the new version. Their
+    /// SHA-256 digests are dropped, see [UpdateResult::dropped_checksums].
+    pub fn update(&mut self) -> Result<UpdateResult, ManifestError> {
+        self.update_with(fetch_published_versions)
+    }
+
+    /// Like [Manifest::update], but obtains the published versions of each package from
+    /// `published_versions` rather than from crates.io.
+    fn update_with(
+        &mut self,
+        mut published_versions: impl FnMut(&str) -> Result<Vec<semver::Version>, ManifestError>,
+    ) -> Result<UpdateResult, ManifestError> {
+        // The same package is usually present in several channels, so it is only looked up once.
+        let mut known_versions: HashMap<String, Vec<semver::Version>> = HashMap::new();
+        let mut result = UpdateResult::default();
+
+        for component in self.channels.iter_mut().flat_map(|channel| channel.components.iter_mut())
+        {
+            let Authority::Cargo { package, version } = &mut component.version else {
+                continue;
+            };
+            let package = package.clone().unwrap_or_else(|| component.name.to_string());
+            let versions = match known_versions.entry(package.clone()) {
+                Entry::Occupied(entry) => entry.into_mut(),
+                Entry::Vacant(entry) => entry.insert(published_versions(&package)?),
+            };
+            let Some(latest) = latest_compatible(version, versions) else {
+                continue;
+            };
+            if latest <= *version {
+                continue;
+            }
+
+            let previous = core::mem::replace(version, latest.clone());
+            if let Some(artifacts) = component.artifacts.as_mut()
+                && artifacts.replace_version(&previous, &latest)
+            {
+                result.dropped_checksums.insert(package.clone());
+            }
+            result.changed_packages.insert(package);
+        }
+
+        Ok(result)
+    }
+}
+
+/// Returns the greatest of `versions` that is semver-compatible with `current`.
+fn latest_compatible(
+    current: &semver::Version,
+    versions: &[semver::Version],
+) -> Option<semver::Version> {
+    let requirement = semver::VersionReq::parse(&format!("^{current}")).ok()?;
+    versions.iter().filter(|version| requirement.matches(version)).max().cloned()
+}
+
+/// The crates.io sparse index, see <https://doc.rust-lang.org/cargo/reference/registry-index.html>.
+const CRATES_IO_INDEX: &str = "https://index.crates.io";
+
+/// Fetches the versions of `package` published on crates.io, excluding yanked versions.
+pub(crate) fn fetch_published_versions(
+    package: &str,
+) -> Result<Vec<semver::Version>, ManifestError> {
+    let uri = format!("{CRATES_IO_INDEX}/{}", index_path(package));
+    let data = download(&uri)?;
+    let entries = String::from_utf8(data)
+        .map_err(|_| ManifestError::InvalidIndexEntry(format!("{uri} is not valid UTF-8")))?;
+    parse_index_entries(&entries)
+}
+
+/// Resolves the path of a `file://` URI: a leading `~` is expanded to the home directory, and
+/// relative paths are made absolute by joining them to the current working directory.
+fn file_uri_path(path: &str) -> PathBuf {
+    let path = match path.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') => dirs::home_dir()
+            .map(|home| home.join(rest.trim_start_matches('/')))
+            .unwrap_or_else(|| PathBuf::from(path)),
+        _ => PathBuf::from(path),
+    };
+    if path.is_relative()
+        && let Ok(working_directory) = std::env::current_dir()
+    {
+        return working_directory.join(path);
+    }
+
+    path
+}
+
+/// Returns the path of `package` within a sparse registry index.
+fn index_path(package: &str) -> String {
+    let package = package.to_lowercase();
+    match package.len() {
+        1 => format!("1/{package}"),
+        2 => format!("2/{package}"),
+        3 => format!("3/{}/{package}", &package[..1]),
+        _ => format!("{}/{}/{package}", &package[..2], &package[2..4]),
+    }
+}
+
+/// A single line of a package's file in the sparse index. Only the fields used by midenup are
+/// deserialized.
+#[derive(Deserialize)]
+struct IndexEntry {
+    vers: semver::Version,
+    #[serde(default)]
+    yanked: bool,
+}
+
+/// Parses the versions listed in a package's file from the sparse index, skipping yanked versions.
+fn parse_index_entries(entries: &str) -> Result<Vec<semver::Version>, ManifestError> {
+    let mut versions = Vec::new();
+    for line in entries.lines().filter(|line| !line.trim().is_empty()) {
+        let entry: IndexEntry = serde_json::from_str(line)
+            .map_err(|err| ManifestError::InvalidIndexEntry(err.to_string()))?;
+        if !entry.yanked {
+            versions.push(entry.vers);
+        }
+    }
+    Ok(versions)
+}
+
+/// Downloads the contents of `uri`, retrying transient failures. See [retry_with_backoff].
+fn download(uri: &str) -> Result<Vec<u8>, ManifestError> {
+    retry_with_backoff(|| {
+        let mut data = Vec::new();
+        let mut handle = curl::easy::Easy::new();
+        configure_timeouts(&mut handle).and_then(|_| handle.url(uri)).map_err(|error| {
+            let mut err = format!("Error code {}: ", error.code());
+            err.push_str(error.description());
+            TransferError::Permanent(ManifestError::InternalCurlError(err))
+        })?;
+
+        {
+            let mut transfer = handle.transfer();
+            transfer
+                .write_function(|new_data| {
+                    data.extend_from_slice(new_data);
+                    Ok(new_data.len())
+                })
+                .unwrap();
+            transfer.perform().map_err(|error| {
+                let err = if error.is_operation_timedout() {
+                    format!("Timed out fetching {uri}: {}", error.description())
+                } else {
+                    format!("Error code {}: {}", error.code(), error.description())
+                };
+                TransferError::from_curl(&error, ManifestError::InternalCurlError(err))
+            })?;
+        }
+
+        // NOTE: The response code is only known once the transfer is performed.
+        let response_code = handle.response_code().map_err(|_| {
+            TransferError::Permanent(ManifestError::InternalCurlError(String::from(
+                "Failed to get response code; despite HTTP protocol supporting it.",
+            )))
+        })?;
+        if HTTP_ERROR_CODES.contains(&response_code) {
+            return Err(TransferError::Permanent(ManifestError::WebpageError(uri.to_string())));
+        }
+        // Server errors are usually temporary, so they are worth retrying.
+        if HTTP_SERVER_ERROR_CODES.contains(&response_code) {
+            return Err(TransferError::Transient(ManifestError::WebpageError(uri.to_string())));
+        }
+
+        Ok(data)
+    })
 }
 
 #[cfg(test)]
 mod tests {
-    use std::borrow::Cow;
+    use std::{borrow::Cow, path::PathBuf};
 
-    use super::Manifest;
-    use crate::{channel::UserChannel, manifest::ChannelAlias, version::Authority};
+    use super::{
+        CachedManifest, MANIFEST_VERSION, Manifest, ManifestError, download, file_uri_path,
+        index_path, parse_index_entries,
+    };
+    use crate::{
+        artifact::TargetTriple,
+        channel::{Channel, UserChannel},
+        manifest::ChannelAlias,
+        version::Authority,
+    };
+
+    /// A made up URI, so that no test reaches the network.
+    const CACHED_URI: &str = "https://midenup.invalid/channel-manifest.json";
+
+    /// Writes a copy of the current channel manifest into a cache at `cache_path`, as if it had
+    /// been fetched from [CACHED_URI] `age` seconds ago.
+    fn write_cache(cache_path: &std::path::Path, age: i64) -> Manifest {
+        let manifest = Manifest::load_from("file://manifest/channel-manifest.json", false)
+            .expect("Couldn't load manifest");
+        let cached = CachedManifest {
+            uri: String::from(CACHED_URI),
+            fetched_at: chrono::Utc::now().timestamp() - age,
+            manifest: manifest.clone(),
+        };
+        std::fs::write(cache_path, serde_json::to_string(&cached).unwrap()).unwrap();
+        manifest
+    }
 
     /// Validates that the current channel manifest is parseable.
     #[test]
     fn validate_current_channel_manifest() {
-        let manifest = Manifest::load_from("file://manifest/channel-manifest.json")
+        let manifest = Manifest::load_from("file://manifest/channel-manifest.json", false)
             .expect("Couldn't load manifest");
 
         let _stable = manifest
             .get_channel(&UserChannel::Stable)
             .expect("Could not convert UserChannel to internal channel representation");
+
+        manifest.validate().expect("current channel manifest has invalid references");
+    }
+
+    /// Validates that every unresolvable reference between components is reported.
+    #[test]
+    fn validate_reports_dangling_references() {
+        let manifest = Manifest::parse_str(
+            r#"{
+                "manifest_version": "1.0.1",
+                "date": 1745931671,
+                "channels": [
+                    {
+                        "name": "0.16.0",
+                        "components": [
+                            {
+                                "name": "midenc",
+                                "package": "midenc",
+                                "version": "0.1.0",
+                                "requires": ["std", "base"]
+                            },
+                            {
+                                "name": "std",
+                                "package": "miden-stdlib",
+                                "version": "0.1.0",
+                                "installed_library": "std.masp",
+                                "library_struct": "miden_stdlib::StdLibrary",
+                                "aliases": { "std": ["executable"] }
+                            }
+                        ]
+                    }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let Err(ManifestError::Invalid(message)) = manifest.validate() else {
+            panic!("manifest with dangling references passed validation");
+        };
+        assert!(message.contains("component 'midenc' requires 'base'"), "{message}");
+        assert!(!message.contains("requires 'std'"), "{message}");
+        assert!(message.contains("alias 'std'"), "{message}");
+    }
+
+    /// Validates that aliases with the same name as a component are reported.
+    #[test]
+    fn validate_reports_alias_collisions() {
+        let manifest = Manifest::load_from(
+            "file://tests/data/alias_collision_test/channel-manifest.json",
+            false,
+        )
+        .expect("Couldn't load manifest");
+
+        let Err(ManifestError::Invalid(message)) = manifest.validate() else {
+            panic!("manifest with an alias shadowing a component passed validation");
+        };
+        assert!(
+            message.contains("alias 'vm' of component 'client' shadows component 'vm'"),
+            "{message}"
+        );
+        assert!(!message.contains("alias 'account'"), "{message}");
+    }
+
+    /// Validates that components installed under the same symlink are reported.
+    #[test]
+    fn validate_reports_symlink_collisions() {
+        let manifest = Manifest::parse_str(
+            r#"{
+                "manifest_version": "1.0.1",
+                "date": 1745931671,
+                "channels": [
+                    {
+                        "name": "0.16.0",
+                        "components": [
+                            {
+                                "name": "vm",
+                                "package": "miden-vm",
+                                "version": "0.16.0",
+                                "installed_executable": "miden-vm"
+                            },
+                            {
+                                "name": "debugger",
+                                "package": "miden-debug",
+                                "version": "0.1.0",
+                                "installed_executable": "miden-debug",
+                                "symlink_name": "miden vm"
+                            },
+                            {
+                                "name": "std",
+                                "package": "miden-stdlib",
+                                "version": "0.16.0",
+                                "installed_library": "std.masp",
+                                "library_struct": "miden_stdlib::StdLibrary",
+                                "symlink_name": "miden vm"
+                            }
+                        ]
+                    }
+                ]
+            }"#,
+        )
+        .expect("Couldn't parse manifest");
+
+        let Err(ManifestError::Invalid(message)) = manifest.validate() else {
+            panic!("manifest with components sharing a symlink passed validation");
+        };
+        assert!(
+            message.contains("0.16.0: components 'debugger', 'vm' are all installed as 'miden vm'"),
+            "{message}"
+        );
+        assert!(!message.contains("'std'"), "{message}");
+    }
+
+    /// Validates that executables installed from the same package are reported, since
+    /// `cargo install` would only keep one of them.
+    #[test]
+    fn validate_reports_package_collisions() {
+        let manifest = Manifest::parse_str(
+            r#"{
+                "manifest_version": "1.0.1",
+                "date": 1745931671,
+                "channels": [
+                    {
+                        "name": "0.16.0",
+                        "components": [
+                            {
+                                "name": "client",
+                                "package": "miden-client-cli",
+                                "version": "0.10.0",
+                                "installed_executable": "miden-client"
+                            },
+                            {
+                                "name": "faucet",
+                                "repository_url": "https://github.com/0xMiden/miden-client",
+                                "crate_name": "miden-client-cli",
+                                "branch": "main",
+                                "installed_executable": "miden-faucet"
+                            },
+                            {
+                                "name": "vm",
+                                "package": "miden-vm",
+                                "version": "0.16.0",
+                                "installed_executable": "miden-vm"
+                            }
+                        ]
+                    }
+                ]
+            }"#,
+        )
+        .expect("Couldn't parse manifest");
+
+        let Err(ManifestError::Invalid(message)) = manifest.validate() else {
+            panic!("manifest with components sharing a package passed validation");
+        };
+        assert!(
+            message.contains(
+                "0.16.0: components 'client', 'faucet' are all installed from package \
+                 'miden-client-cli'"
+            ),
+            "{message}"
+        );
+        assert!(!message.contains("'vm'"), "{message}");
+    }
+
+    /// Validates that components installing different binaries of the same package don't collide,
+    /// unlike a component installing every binary of it.
+    #[test]
+    fn validate_allows_components_selecting_binaries_of_a_package() {
+        let channel = r#"{
+            "name": "0.16.0",
+            "components": [
+                {
+                    "name": "client",
+                    "package": "miden-client-cli",
+                    "version": "0.10.0",
+                    "bin": "miden-client"
+                },
+                {
+                    "name": "faucet",
+                    "package": "miden-client-cli",
+                    "version": "0.10.0",
+                    "bin": "miden-faucet"
+                }
+            ]
+        }"#;
+        let channel: Channel = serde_json::from_str(channel).expect("invalid channel");
+        assert!(channel.package_collisions().is_empty(), "{:?}", channel.package_collisions());
+
+        let mut channel = channel;
+        let mut everything = channel.get_component("client").unwrap().clone();
+        everything.name = "everything".into();
+        everything.bin = None;
+        channel.components.push(everything);
+        assert_eq!(
+            channel.package_collisions(),
+            [
+                "0.16.0: components 'client', 'faucet', 'everything' are all installed from \
+                 package 'miden-client-cli'"
+            ]
+        );
+    }
+
+    /// Validates that manifests of an incompatible version are rejected, even if the rest of their
+    /// contents can't be parsed.
+    #[test]
+    fn parse_rejects_unsupported_versions() {
+        let err = Manifest::parse_str(
+            r#"{
+                "manifest_version": "2.0.0",
+                "date": 1745931671,
+                "toolchains": {}
+            }"#,
+        )
+        .expect_err("manifest of an unsupported version was parsed");
+        let ManifestError::UnsupportedVersion { found, supported } = &err else {
+            panic!("unexpected error: {err}");
+        };
+        assert_eq!(found, &semver::Version::new(2, 0, 0));
+        assert_eq!(supported, &MANIFEST_VERSION);
+        assert!(err.to_string().contains("midenup self update"), "{err}");
+
+        // Minor versions are compatible with each other.
+        let manifest = Manifest::parse_str(
+            r#"{
+                "manifest_version": "1.7.0",
+                "date": 1745931671,
+                "channels": []
+            }"#,
+        )
+        .expect("manifest of a newer minor version was rejected");
+        assert_eq!(manifest.manifest_version, semver::Version::new(1, 7, 0));
+    }
+
+    /// Components can disable the default features of their crate, which they keep otherwise.
+    #[test]
+    fn parse_default_features() {
+        let manifest = Manifest::parse_str(
+            r#"{
+                "manifest_version": "1.0.1",
+                "date": 1745931671,
+                "channels": [
+                    {
+                        "name": "0.16.0",
+                        "components": [
+                            {
+                                "name": "vm",
+                                "package": "miden-vm",
+                                "version": "0.16.0",
+                                "default_features": false,
+                                "features": ["executable"]
+                            },
+                            {
+                                "name": "client",
+                                "package": "miden-client-cli",
+                                "version": "0.10.0"
+                            }
+                        ]
+                    }
+                ]
+            }"#,
+        )
+        .expect("Couldn't parse manifest");
+
+        let channel = manifest.get_channels().next().unwrap();
+        assert!(!channel.get_component("vm").unwrap().default_features);
+        assert!(channel.get_component("client").unwrap().default_features);
     }
 
     /// Validates that the *published* channel manifest is parseable.
     /// NOTE: This test is mainly intended for backwards compatibilty reasons.
     #[test]
     fn validate_published_channel_manifest() {
-        let manifest = Manifest::load_from(Manifest::PUBLISHED_MANIFEST_URI)
+        let manifest = Manifest::load_from(Manifest::PUBLISHED_MANIFEST_URI, false)
             .expect("Failed to parse upstream manifest.");
 
         let _ = manifest
@@ -317,6 +937,50 @@ mod tests {
             .expect("Could not convert UserChannel to internal channel representation");
     }
 
+    /// Validates that a cached manifest is used while it is fresh, and that offline mode uses it
+    /// regardless of its age.
+    #[test]
+    fn load_cached_uses_cache() {
+        let dir = tempdir::TempDir::new("manifest_cache").unwrap();
+        let cache_path = dir.path().join("channel-manifest.json");
+
+        let manifest = write_cache(&cache_path, 60);
+        let loaded = Manifest::load_cached(CACHED_URI, false, &cache_path, false)
+            .expect("fresh cached manifest was not used");
+        assert_eq!(loaded.get_channels().count(), manifest.get_channels().count());
+
+        write_cache(&cache_path, 60 * 60 * 24);
+        let loaded = Manifest::load_cached(CACHED_URI, true, &cache_path, true)
+            .expect("cached manifest was not used in offline mode");
+        assert_eq!(loaded.get_channels().count(), manifest.get_channels().count());
+    }
+
+    /// Validates that a manifest cached from a different URI is ignored.
+    #[test]
+    fn load_cached_ignores_other_uris() {
+        let dir = tempdir::TempDir::new("manifest_cache_uri").unwrap();
+        let cache_path = dir.path().join("channel-manifest.json");
+        write_cache(&cache_path, 60);
+
+        let result = Manifest::load_cached(
+            "https://midenup.invalid/other-manifest.json",
+            true,
+            &cache_path,
+            false,
+        );
+        assert!(matches!(result, Err(ManifestError::Offline(_))));
+    }
+
+    /// Validates that remote manifests are refused in offline mode, while local ones still load.
+    #[test]
+    fn offline_refuses_remote_manifest() {
+        let result = Manifest::load_from(Manifest::PUBLISHED_MANIFEST_URI, true);
+        assert!(matches!(result, Err(ManifestError::Offline(_))));
+
+        Manifest::load_from("file://manifest/channel-manifest.json", true)
+            .expect("Couldn't load local manifest in offline mode");
+    }
+
     /// Validates that non-standard manifest features are parsed correctly, these include:
     ///
     /// - Non stable channels (custom tags, nightly)
@@ -325,7 +989,7 @@ mod tests {
     fn unit_test_manifest_additional() {
         const FILE: &str =
             "file://tests/data/unit_test_manifest_additional/manifest-non-stable.json";
-        let manifest = Manifest::load_from(FILE).unwrap();
+        let manifest = Manifest::load_from(FILE, false).unwrap();
         {
             let custom_build = manifest
                 .get_channel(&UserChannel::Other(Cow::Borrowed("custom-dev-build")))
@@ -368,5 +1032,289 @@ mod tests {
                 assert!(matches!(client.version, Authority::Git { .. }));
             }
         }
+        {
+            let named_nightly = "nightly-foo".parse::<UserChannel>().unwrap();
+            let nightly = manifest.get_channel(&named_nightly).unwrap_or_else(|| {
+                panic!(
+                    "Could not convert UserChannel to internal channel representation from {FILE}",
+                )
+            });
+            assert_eq!(nightly.alias, Some(ChannelAlias::Nightly(Some(Cow::Borrowed("foo")))));
+            assert_eq!(nightly.name, semver::Version::parse("0.14.0-nightly").unwrap());
+        }
+    }
+
+    /// A manifest with two channels sharing a package, one component installed from git and one
+    /// on a pre-release.
+    const UPDATE_MANIFEST: &str = r#"{
+        "manifest_version": "1.0.1",
+        "date": 1745931671,
+        "channels": [
+            {
+                "name": "0.15.0",
+                "components": [
+                    {
+                        "name": "vm",
+                        "package": "miden-vm",
+                        "version": "0.15.0",
+                        "installed_executable": "miden-vm",
+                        "artifacts": [
+                            "https://github.com/0xMiden/miden-vm/releases/download/v0.15.0/miden-vm-aarch64-apple-darwin"
+                        ]
+                    },
+                    {
+                        "name": "midenc",
+                        "version": "0.1.0-rc.1"
+                    }
+                ]
+            },
+            {
+                "name": "0.16.0",
+                "components": [
+                    {
+                        "name": "vm",
+                        "package": "miden-vm",
+                        "version": "0.16.1",
+                        "installed_executable": "miden-vm"
+                    },
+                    {
+                        "name": "client",
+                        "repository_url": "https://github.com/0xMiden/miden-client.git",
+                        "crate_name": "miden-client-cli",
+                        "branch": "next",
+                        "installed_executable": "miden-client"
+                    }
+                ]
+            }
+        ]
+    }"#;
+
+    /// A mocked registry, with the versions published for each package.
+    fn registry(package: &str) -> Result<Vec<semver::Version>, ManifestError> {
+        let versions: &[&str] = match package {
+            "miden-vm" => &["0.15.0", "0.15.2", "0.16.1", "0.16.3", "0.17.0", "0.16.4-rc.1"],
+            "midenc" => &["0.1.0-rc.1", "0.1.0-rc.2", "0.1.0", "0.2.0"],
+            _ => panic!("unexpected registry lookup for {package}"),
+        };
+        Ok(versions
+            .iter()
+            .map(|version| semver::Version::parse(version).unwrap())
+            .collect())
+    }
+
+    fn cargo_version(manifest: &Manifest, channel: &str, component: &str) -> semver::Version {
+        let channel = manifest
+            .get_channel_by_name(&semver::Version::parse(channel).unwrap())
+            .expect("channel should exist");
+        match &channel.get_component(component).expect("component should exist").version {
+            Authority::Cargo { version, .. } => version.clone(),
+            authority => panic!("unexpected authority {authority}"),
+        }
+    }
+
+    /// Validates that components are bumped to the latest compatible version, once per package.
+    #[test]
+    fn update_bumps_compatible_versions() {
+        let mut manifest = Manifest::parse_str(UPDATE_MANIFEST).unwrap();
+        let mut lookups = Vec::new();
+        let result = manifest
+            .update_with(|package| {
+                lookups.push(package.to_string());
+                registry(package)
+            })
+            .unwrap();
+
+        lookups.sort();
+        assert_eq!(lookups, ["miden-vm", "midenc"]);
+        assert_eq!(result.changed_packages.into_iter().collect::<Vec<_>>(), ["miden-vm", "midenc"]);
+        assert_eq!(cargo_version(&manifest, "0.15.0", "vm").to_string(), "0.15.2");
+        assert_eq!(cargo_version(&manifest, "0.15.0", "midenc").to_string(), "0.1.0");
+        assert_eq!(cargo_version(&manifest, "0.16.0", "vm").to_string(), "0.16.3");
+
+        let vm = manifest
+            .get_channel_by_name(&semver::Version::new(0, 15, 0))
+            .and_then(|channel| channel.get_component("vm"))
+            .unwrap();
+        let serialized = serde_json::to_string(vm).unwrap();
+        assert!(serialized.contains("/v0.15.2/miden-vm-aarch64-apple-darwin"));
+    }
+
+    /// Validates that bumping a component drops the checksums of its artifacts, which are those of
+    /// the previous version.
+    #[test]
+    fn update_drops_checksums_of_bumped_artifacts() {
+        let mut manifest = Manifest::parse_str(
+            r#"{
+                "manifest_version": "1.0.1",
+                "date": 1745931671,
+                "channels": [
+                    {
+                        "name": "0.16.0",
+                        "components": [
+                            {
+                                "name": "vm",
+                                "package": "miden-vm",
+                                "version": "0.16.1",
+                                "installed_executable": "miden-vm",
+                                "artifacts": [
+                                    {
+                                        "uri": "https://github.com/0xMiden/miden-vm/releases/download/v0.16.1/miden-vm-x86_64-unknown-linux-gnu",
+                                        "sha256": "0000000000000000000000000000000000000000000000000000000000000000"
+                                    }
+                                ]
+                            }
+                        ]
+                    }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let result = manifest.update_with(registry).unwrap();
+        assert_eq!(result.dropped_checksums.into_iter().collect::<Vec<_>>(), ["miden-vm"]);
+
+        let artifacts = manifest
+            .get_channel_by_name(&semver::Version::new(0, 16, 0))
+            .and_then(|channel| channel.get_component("vm"))
+            .and_then(|vm| vm.artifacts.as_ref())
+            .unwrap();
+        let target = TargetTriple::Custom(String::from("x86_64-unknown-linux-gnu"));
+        assert!(artifacts.get_uri_for(&target).unwrap().contains("/v0.16.3/"));
+        assert_eq!(artifacts.get_sha256_for(&target), None);
+    }
+
+    /// Validates that an up to date manifest is left untouched.
+    #[test]
+    fn update_without_new_versions() {
+        let mut manifest = Manifest::parse_str(UPDATE_MANIFEST).unwrap();
+        manifest.update_with(registry).unwrap();
+        let updated = serde_json::to_string(&manifest).unwrap();
+
+        let result = manifest.update_with(registry).unwrap();
+        assert!(result.changed_packages.is_empty());
+        assert_eq!(serde_json::to_string(&manifest).unwrap(), updated);
+    }
+
+    /// Validates that registry errors are propagated.
+    #[test]
+    fn update_propagates_registry_errors() {
+        let mut manifest = Manifest::parse_str(UPDATE_MANIFEST).unwrap();
+        let result = manifest.update_with(|package| {
+            Err(ManifestError::WebpageError(format!("https://index.crates.io/{package}")))
+        });
+        assert!(matches!(result, Err(ManifestError::WebpageError(_))));
+    }
+
+    #[test]
+    fn sparse_index_paths() {
+        assert_eq!(index_path("a"), "1/a");
+        assert_eq!(index_path("ab"), "2/ab");
+        assert_eq!(index_path("abc"), "3/a/abc");
+        assert_eq!(index_path("miden-vm"), "mi/de/miden-vm");
+        assert_eq!(index_path("Midenc"), "mi/de/midenc");
+    }
+
+    /// Validates parsing a response from the sparse index, in which yanked versions are skipped.
+    #[test]
+    fn sparse_index_entries() {
+        let entries = r#"{"name":"midenc","vers":"0.1.0","deps":[],"cksum":"00","features":{},"yanked":false}
+{"name":"midenc","vers":"0.1.1","deps":[],"cksum":"01","features":{},"yanked":true}
+{"name":"midenc","vers":"0.2.0","deps":[],"cksum":"02","features":{},"yanked":false,"v":2}
+"#;
+        let versions = parse_index_entries(entries).unwrap();
+        assert_eq!(versions, [semver::Version::new(0, 1, 0), semver::Version::new(0, 2, 0)]);
+
+        assert!(matches!(
+            parse_index_entries("not json"),
+            Err(ManifestError::InvalidIndexEntry(_))
+        ));
+    }
+
+    /// Serves a single HTTP request on a local port, answering with `status`. Returns the URI to
+    /// request.
+    fn serve_once(status: &'static str) -> String {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            use std::io::{Read, Write};
+
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0; 1024];
+            let _ = stream.read(&mut request);
+            let body = "{ \"error\": \"not found\" }";
+            let _ = write!(
+                stream,
+                "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+        });
+        format!("http://{address}/channel-manifest.json")
+    }
+
+    /// Validates that error responses are reported as such, rather than returning the body of the
+    /// response.
+    #[test]
+    fn download_reports_missing_pages() {
+        let uri = serve_once("404 Not Found");
+        assert!(
+            matches!(download(&uri), Err(ManifestError::WebpageError(webpage)) if webpage == uri)
+        );
+    }
+
+    /// Validates that pre-releases without the `stable` alias are never considered the latest
+    /// stable channel, even if they have a greater version.
+    #[test]
+    fn pre_releases_are_not_stable() {
+        let release = Channel::new(semver::Version::new(0, 15, 0), None, vec![], vec![]);
+        let custom_build = Channel::new(
+            semver::Version::parse("0.16.0-custom-build").unwrap(),
+            None,
+            vec![],
+            vec![],
+        );
+        assert!(release.is_stable());
+        assert!(!custom_build.is_stable());
+
+        let mut manifest = Manifest::default();
+        manifest.add_channel(release.clone());
+        manifest.add_channel(custom_build.clone());
+        assert!(manifest.is_latest_stable(&release));
+        assert!(!manifest.is_latest_stable(&custom_build));
+        assert_eq!(manifest.get_latest_stable().map(|channel| &channel.name), Some(&release.name));
+
+        // Unless they are explicitly marked as stable.
+        let mut stable_custom_build = custom_build;
+        stable_custom_build.alias = Some(ChannelAlias::Stable);
+        assert!(stable_custom_build.is_stable());
+        manifest.add_channel(stable_custom_build.clone());
+        assert_eq!(
+            manifest.get_latest_stable().map(|channel| &channel.name),
+            Some(&stable_custom_build.name)
+        );
+    }
+
+    /// Validates that `file://` URIs can be relative to the current working directory, or start
+    /// with `~`, and that the resolved path is reported when they are missing.
+    #[test]
+    fn file_uris_are_resolved() {
+        let working_directory = std::env::current_dir().unwrap();
+        assert_eq!(
+            file_uri_path("./manifest/channel-manifest.json"),
+            working_directory.join("./manifest/channel-manifest.json")
+        );
+        assert!(Manifest::load_from("file://./manifest/channel-manifest.json", true).is_ok());
+
+        let home = dirs::home_dir().unwrap();
+        assert_eq!(file_uri_path("~/m.json"), home.join("m.json"));
+        assert_eq!(file_uri_path("~"), home);
+        // Only the current user's home is expanded.
+        assert_eq!(file_uri_path("~other/m.json"), working_directory.join("~other/m.json"));
+        assert_eq!(file_uri_path("/tmp/m.json"), PathBuf::from("/tmp/m.json"));
+
+        let missing = home.join("midenup-nonexistent-manifest.json");
+        assert!(matches!(
+            Manifest::load_from("file://~/midenup-nonexistent-manifest.json", true),
+            Err(ManifestError::Missing(path)) if path == missing.display().to_string()
+        ));
     }
 }
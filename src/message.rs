@@ -0,0 +1,80 @@
+use serde::Serialize;
+
+use crate::{
+    channel::Component,
+    version::{Authority, GitTarget},
+};
+
+/// Machine-readable events emitted by `install` and `update` when running with
+/// `--message-format json`. Each event is printed to stdout as a single line of JSON.
+#[derive(Serialize, Debug)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Message {
+    /// A toolchain is about to be updated.
+    Updating { channel: String },
+    /// A toolchain has no updates available.
+    UpToDate { channel: String },
+    /// A toolchain has updates available, see `midenup update --check`.
+    UpdateAvailable { channel: String },
+    /// A component is about to be installed.
+    Installing {
+        component: String,
+        /// How the component is obtained, either `cargo`, `git` or `path`.
+        authority: &'static str,
+        /// The crate version, git branch/tag/revision, or path the component is installed from.
+        version: String,
+    },
+    /// A toolchain was installed successfully.
+    Installed { channel: String },
+}
+
+impl Message {
+    pub fn installing(component: &Component) -> Self {
+        let (authority, version) = match &component.version {
+            Authority::Cargo { version, .. } => ("cargo", version.to_string()),
+            Authority::CargoRequirement { requirement, .. } => ("cargo", requirement.to_string()),
+            Authority::Git { target, .. } => {
+                let target = match target {
+                    GitTarget::Branch { name, .. } => name,
+                    GitTarget::Revision { hash } => hash,
+                    GitTarget::Tag { name } => name,
+                };
+                ("git", target.clone())
+            },
+            Authority::Path { path, .. } => ("path", path.display().to_string()),
+        };
+
+        Message::Installing {
+            component: component.name.to_string(),
+            authority,
+            version,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Message;
+    use crate::{channel::Component, version::Authority};
+
+    #[test]
+    fn messages_serialize_as_tagged_events() {
+        let vm = Component::new(
+            "vm",
+            Authority::Cargo {
+                package: Some(String::from("miden-vm")),
+                version: semver::Version::new(0, 16, 2),
+            },
+        );
+        assert_eq!(
+            serde_json::to_string(&Message::installing(&vm)).unwrap(),
+            r#"{"event":"installing","component":"vm","authority":"cargo","version":"0.16.2"}"#
+        );
+
+        let installed = Message::Installed { channel: String::from("0.15.0") };
+        assert_eq!(
+            serde_json::to_string(&installed).unwrap(),
+            r#"{"event":"installed","channel":"0.15.0"}"#
+        );
+    }
+}
@@ -1,15 +1,20 @@
-use std::{ffi::OsString, fmt::Display, string::ToString};
+use std::{ffi::OsString, fmt::Display, str::FromStr, string::ToString};
 
 use anyhow::{Context, anyhow, bail};
 use colored::Colorize;
 
 pub use crate::config::Config;
 use crate::{
-    channel::{Channel, CliCommands, Component, InstalledFile, resolve_command},
+    channel::{Channel, CliCommands, Component, InstalledFile, UserChannel, resolve_command},
     manifest::Manifest,
-    toolchain::Toolchain,
+    options::ColorChoice,
+    toolchain::{Toolchain, ToolchainJustification},
 };
 
+/// Environment variable that lets `miden` run aliases and components from any installed toolchain
+/// when the active one doesn't have them. See [ToolchainEnvironment::with_other_channels].
+pub const SEARCH_ALL_TOOLCHAINS_ENV: &str = "MIDENUP_SEARCH_ALL_TOOLCHAINS";
+
 /// These are the know help messages variants that midenup is aware of.
 enum HelpMessage {
     /// Show the default help message, similar to the one you would get with clap's "--help" flag.
@@ -78,6 +83,13 @@ struct ToolchainEnvironment<'a> {
     /// This *might* differ slightly from the original upstream channel equivalent in some
     /// scenarios, e.g. the user only selected a subset of components for downloads.
     active_channel: Option<Channel>,
+    /// The other installed channels, newest first, searched as a last resort when neither
+    /// [`ToolchainEnvironment::active_channel`] nor
+    /// [`ToolchainEnvironment::installed_channel`] have a requested alias or component.
+    ///
+    /// Only populated when opted into with [SEARCH_ALL_TOOLCHAINS_ENV], since running something
+    /// from another toolchain can be surprising.
+    other_channels: Vec<&'a Channel>,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -87,7 +99,27 @@ enum ChannelType {
 }
 impl<'a> ToolchainEnvironment<'a> {
     fn new(installed_channel: &'a Channel, active_channel: Option<Channel>) -> Self {
-        ToolchainEnvironment { installed_channel, active_channel }
+        ToolchainEnvironment {
+            installed_channel,
+            active_channel,
+            other_channels: Vec::new(),
+        }
+    }
+
+    /// Searches the channels installed in `local_manifest` as a last resort, newest first, if
+    /// [SEARCH_ALL_TOOLCHAINS_ENV] is set.
+    fn with_other_channels(mut self, local_manifest: &'a Manifest) -> Self {
+        if !crate::external::flag_from_env(SEARCH_ALL_TOOLCHAINS_ENV) {
+            return self;
+        }
+
+        let mut other_channels = local_manifest
+            .get_channels()
+            .filter(|channel| channel.name != self.installed_channel.name)
+            .collect::<Vec<_>>();
+        other_channels.sort_by(|a, b| b.name.cmp(&a.name));
+        self.other_channels = other_channels;
+        self
     }
 
     /// This is the channel that is currently active.
@@ -105,9 +137,26 @@ impl<'a> ToolchainEnvironment<'a> {
     /// Parses the user's input and returns the required [ExecutionEnvironment] to execute the
     /// requested command.
     fn resolve(&self, argument: String) -> Result<ExecutionEnvironment<'_>, EnvironmentError> {
+        self.resolve_with(argument, resolve_argument)
+    }
+
+    /// Like [ToolchainEnvironment::resolve], but `argument` is only ever resolved to a
+    /// [Component], ignoring aliases.
+    fn resolve_component(
+        &self,
+        argument: String,
+    ) -> Result<ExecutionEnvironment<'_>, EnvironmentError> {
+        self.resolve_with(argument, resolve_component_argument)
+    }
+
+    fn resolve_with(
+        &self,
+        argument: String,
+        resolver: fn(&Channel, &str) -> Result<MidenArgument, EnvironmentError>,
+    ) -> Result<ExecutionEnvironment<'_>, EnvironmentError> {
         // Local function that tries to parse an argument given a channel's state.
         let fallback_motive = if let Some(active_channel) = self.active_channel.as_ref() {
-            match resolve_argument(active_channel, &argument) {
+            match resolver(active_channel, &argument) {
                 Ok(arg) => return Ok(ExecutionEnvironment { argument: arg, active_channel }),
                 Err(EnvironmentError::UnknownArgument(_)) => {
                     FallbackMotive::ArgumentNotInActiveChannel
@@ -120,7 +169,14 @@ impl<'a> ToolchainEnvironment<'a> {
 
         // We know try to resolve the argument with the installed channel.
         {
-            let miden_argument = resolve_argument(self.installed_channel, &argument)?;
+            let miden_argument = match resolver(self.installed_channel, &argument) {
+                Err(EnvironmentError::UnknownArgument(err)) => {
+                    return self
+                        .resolve_in_other_channels(&argument, resolver)
+                        .unwrap_or(Err(EnvironmentError::UnknownArgument(err)));
+                },
+                resolution => resolution?,
+            };
 
             let not_found_in_active =
                 matches!(fallback_motive, FallbackMotive::ArgumentNotInActiveChannel);
@@ -151,6 +207,36 @@ impl<'a> ToolchainEnvironment<'a> {
         }
     }
 
+    /// Resolves `argument` with the first of [`ToolchainEnvironment::other_channels`] that knows
+    /// it, if any.
+    fn resolve_in_other_channels(
+        &self,
+        argument: &str,
+        resolver: fn(&Channel, &str) -> Result<MidenArgument, EnvironmentError>,
+    ) -> Option<Result<ExecutionEnvironment<'_>, EnvironmentError>> {
+        for channel in self.other_channels.iter().copied() {
+            match resolver(channel, argument) {
+                Ok(miden_argument) => {
+                    println!(
+                        "{}: {} is not part of the current active toolchain, using the one from \
+                         installed toolchain {} instead.",
+                        "WARNING".yellow().bold(),
+                        argument,
+                        channel.name,
+                    );
+                    return Some(Ok(ExecutionEnvironment {
+                        argument: miden_argument,
+                        active_channel: channel,
+                    }));
+                },
+                Err(EnvironmentError::UnknownArgument(_)) => continue,
+                Err(err) => return Some(Err(err)),
+            }
+        }
+
+        None
+    }
+
     fn get_executables_display(&self) -> String {
         self.get_active_channel()
             .0
@@ -187,6 +273,27 @@ impl<'a> ToolchainEnvironment<'a> {
         keys.sort();
         keys.iter().map(|alias| format!("  {}\n", alias.bold())).collect::<String>()
     }
+
+    /// Lists the aliases, components and libraries available in the active channel.
+    fn get_contents_display(&self) -> String {
+        let available_aliases_text = "Available aliases:".bold().underline();
+        let available_aliases: String = self.get_aliases_display();
+
+        let available_components_text = "Available components:".bold().underline();
+        let available_components: String = self.get_executables_display();
+
+        let available_libraries_text = "Available libraries:".bold().underline();
+        let available_libraries: String = self.get_libraries_display();
+
+        format!(
+            "{available_aliases_text}
+{available_aliases}
+{available_components_text}
+{available_components}
+{available_libraries_text}
+{available_libraries}"
+        )
+    }
 }
 
 /// These are the possible types of subcommands that `miden` is aware of.
@@ -222,6 +329,10 @@ const CLAP_HELP_SUBCMD: &str = "help";
 const CLAP_HELP_COMPONENT_ARG: &str = "alias_component";
 /// Identifies the `--version` flag argument in clap
 const CLAP_VERSION_FLAG: &str = "version";
+/// Identifies the `--manifest-uri` argument in clap
+const CLAP_MANIFEST_URI_ARG: &str = "manifest-uri";
+/// Identifies the `--color` argument in clap
+const CLAP_COLOR_ARG: &str = "color";
 
 /// Builds the clap [Command] definition for the `miden` binary.
 fn build_miden_command() -> clap::Command {
@@ -244,6 +355,51 @@ fn build_miden_command() -> clap::Command {
         )
         // This adds support for --version.
         .arg(clap::Arg::new(CLAP_VERSION_FLAG).long("version").action(clap::ArgAction::SetTrue))
+        // This adds support for `miden --manifest-uri <URI> <alias/component>`, which overrides
+        // `MIDENUP_MANIFEST_URI`.
+        .arg(clap::Arg::new(CLAP_MANIFEST_URI_ARG).long("manifest-uri").value_name("URI"))
+        // This adds support for `miden --color <WHEN> <alias/component>`, like `midenup --color`.
+        .arg(
+            clap::Arg::new(CLAP_COLOR_ARG)
+                .long("color")
+                .value_name("WHEN")
+                .value_parser(clap::value_parser!(ColorChoice)),
+        )
+}
+
+/// Splits the `+<channel>` argument off `argv`, if it directly follows `miden`, e.g. in
+/// `miden +0.15.0 vm prove`. Like `cargo +<toolchain>`, it selects the toolchain for a single
+/// invocation.
+fn split_channel_argument(argv: &[OsString]) -> (Option<String>, Vec<OsString>) {
+    let channel = argv.get(1).and_then(|arg| arg.to_str()).and_then(|arg| arg.strip_prefix('+'));
+    match channel {
+        Some(channel) => {
+            let rest = argv.iter().take(1).chain(argv.iter().skip(2)).cloned().collect();
+            (Some(channel.to_string()), rest)
+        },
+        None => (None, argv.to_vec()),
+    }
+}
+
+/// Returns the URI passed to `miden` with `--manifest-uri`, if any.
+///
+/// Invalid arguments are ignored here, they are reported once `miden` is run.
+pub fn manifest_uri_argument(argv: &[OsString]) -> Option<String> {
+    let (_, argv) = split_channel_argument(argv);
+    let matches = build_miden_command().try_get_matches_from(argv).ok()?;
+    matches.get_one::<String>(CLAP_MANIFEST_URI_ARG).cloned()
+}
+
+/// Returns the [ColorChoice] passed to `miden` with `--color`, or the default one.
+///
+/// Invalid arguments are ignored here, they are reported once `miden` is run.
+pub fn color_argument(argv: &[OsString]) -> ColorChoice {
+    let (_, argv) = split_channel_argument(argv);
+    build_miden_command()
+        .try_get_matches_from(argv)
+        .ok()
+        .and_then(|matches| matches.get_one::<ColorChoice>(CLAP_COLOR_ARG).copied())
+        .unwrap_or_default()
 }
 
 /// Converts clap [ArgMatches] into a [MidenSubcommand].
@@ -277,13 +433,21 @@ pub fn miden_wrapper(
     config: &Config,
     local_manifest: &mut Manifest,
 ) -> anyhow::Result<()> {
+    // Used in error messages further down.
+    let user_input = argv.iter().map(|s| s.to_string_lossy()).collect::<Vec<_>>().join(" ");
+
+    let (channel_argument, argv) = split_channel_argument(argv);
+    let channel_argument = channel_argument
+        .map(|channel| {
+            UserChannel::from_str(&channel)
+                .with_context(|| format!("invalid toolchain '+{channel}'"))
+        })
+        .transpose()?;
+
     let matches = build_miden_command().get_matches_from(argv);
 
     let parsed_subcommand = parse_matches(&matches);
 
-    // Used in error messages further down.
-    let user_input = argv.iter().map(|s| s.to_string_lossy()).collect::<Vec<_>>().join(" ");
-
     // NOTE: We handle these case first to avoid triggering an install when help related commands
     // are run.
     match parsed_subcommand {
@@ -298,9 +462,17 @@ pub fn miden_wrapper(
         _ => (),
     }
 
-    // Make sure we know the current toolchain so we can modify the PATH appropriately
-    let (toolchain, _justification, partial_channel) =
-        Toolchain::ensure_current_is_installed(config, local_manifest)?;
+    // Make sure we know the current toolchain so we can modify the PATH appropriately. A
+    // `+<channel>` argument takes precedence over every other way of selecting it.
+    let (toolchain, _justification, partial_channel) = match channel_argument {
+        Some(channel) => Toolchain::ensure_installed(
+            Toolchain::new(channel, None, vec![]),
+            ToolchainJustification::CommandLine,
+            config,
+            local_manifest,
+        )?,
+        None => Toolchain::ensure_current_is_installed(config, local_manifest)?,
+    };
 
     let toolchain_environment = {
         let installed_channel = local_manifest
@@ -308,6 +480,7 @@ pub fn miden_wrapper(
             .context("Couldn't find active toolchain in the manifest.")?;
 
         ToolchainEnvironment::new(installed_channel, partial_channel)
+            .with_other_channels(local_manifest)
     };
 
     // Whether the user requested help for a specific alias or component (e.g. `miden help
@@ -326,7 +499,7 @@ pub fn miden_wrapper(
     };
 
     // We obtain the target executable and prefixes that are associated with the passed subcommand.
-    let (target_exe, prefix_args, active_channel) = match parsed_subcommand {
+    let (target_exe, prefix_args, active_channel, component) = match parsed_subcommand {
         MidenSubcommand::Version
         | MidenSubcommand::Help(HelpMessage::Default)
         | MidenSubcommand::Help(HelpMessage::Toolchain) => unreachable!(),
@@ -347,7 +520,7 @@ pub fn miden_wrapper(
                     let command = commands.pop_front().unwrap();
                     let aliased_arguments = commands;
 
-                    (command, aliased_arguments, active_channel)
+                    (command, aliased_arguments, active_channel, component)
                 },
                 Ok(ExecutionEnvironment {
                     argument: MidenArgument::Component(component),
@@ -365,7 +538,7 @@ pub fn miden_wrapper(
                     let command = call_convention.pop_front().unwrap();
                     let args = call_convention;
 
-                    (command, args, active_channel)
+                    (command, args, active_channel, component)
                 },
                 Err(err) => {
                     let help_message = toolchain_help(&toolchain_environment);
@@ -398,19 +571,98 @@ pub fn miden_wrapper(
 
     let args = prefix_args.into_iter().chain(remaining_args).collect::<Vec<_>>();
 
+    run(config, active_channel, &component, &target_exe, &args, &user_input)
+}
+
+/// Runs `component`'s executable from the currently active [Toolchain] with `args`, without
+/// resolving aliases. This backs `midenup run`.
+///
+/// NOTE: This *could* trigger an install if the active [Toolchain] is not installed.
+pub fn run_component(
+    component: &str,
+    args: &[OsString],
+    config: &Config,
+    local_manifest: &mut Manifest,
+) -> anyhow::Result<()> {
+    let (toolchain, _justification, partial_channel) =
+        Toolchain::ensure_current_is_installed(config, local_manifest)?;
+
+    let installed_channel = local_manifest
+        .get_channel(&toolchain.channel)
+        .context("Couldn't find active toolchain in the manifest.")?;
+    let toolchain_environment = ToolchainEnvironment::new(installed_channel, partial_channel)
+        .with_other_channels(local_manifest);
+
+    let (component, active_channel) =
+        match toolchain_environment.resolve_component(component.to_string()) {
+            Ok(ExecutionEnvironment {
+                argument: MidenArgument::Component(component),
+                active_channel,
+            }) => (component, active_channel),
+            Ok(ExecutionEnvironment { argument: MidenArgument::Alias(..), .. }) => {
+                unreachable!("aliases are not resolved by resolve_component")
+            },
+            Err(err) => {
+                let help_message = toolchain_help(&toolchain_environment);
+                bail!("{err}\n\n{help_message}");
+            },
+        };
+
+    let mut call_convention = std::collections::VecDeque::from(resolve_command(
+        &component.get_call_format(),
+        active_channel,
+        &component,
+        config,
+    )?);
+    // SAFETY: Safe under the assumption that every call_format has at least one argument
+    let target_exe = call_convention.pop_front().unwrap();
+
+    // Used in error messages.
+    let user_input = std::iter::once(component.name.to_string())
+        .chain(args.iter().map(|arg| arg.to_string_lossy().into_owned()))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let args = call_convention.into_iter().chain(args.iter().cloned()).collect::<Vec<_>>();
+
+    run(config, active_channel, &component, &target_exe, &args, &user_input)
+}
+
+/// Executes `target_exe` with `args` in the environment of `active_channel`, and waits for it to
+/// finish. `target_exe` was resolved from `component`, see [resolve_command]. `user_input` is only
+/// used in error messages.
+fn run(
+    config: &Config,
+    active_channel: &Channel,
+    component: &Component,
+    target_exe: &OsString,
+    args: &[OsString],
+    user_input: &str,
+) -> anyhow::Result<()> {
+    let executable = component
+        .get_installed_file()
+        .get_path_from(&active_channel.get_channel_dir(config));
+    let arg0 = (*target_exe == executable.into_os_string())
+        .then(|| OsString::from(component.get_symlink_name()));
+    let env = component.resolve_env(active_channel, config)?;
+
     let mut command = config
-        .execute_command(active_channel, &target_exe, &args)
+        .execute_command(active_channel, target_exe, args, arg0.as_deref(), &env)
         .with_context(|| format!("failed to run '{user_input}'"))?;
 
-    let status = command.wait().with_context(|| {
-        format!("error occurred while waiting for '{user_input}' to finish executing")
-    })?;
+    let status =
+        crate::utils::process::wait_forwarding_signals(&mut command).with_context(|| {
+            format!("error occurred while waiting for '{user_input}' to finish executing")
+        })?;
 
     if status.success() {
-        Ok(())
-    } else {
-        bail!("'{}' failed with status {}", user_input, status.code().unwrap_or(1))
+        return Ok(());
+    }
+    #[cfg(unix)]
+    if let Some(signal) = std::os::unix::process::ExitStatusExt::signal(&status) {
+        bail!("'{}' was terminated by signal {}", user_input, signal)
     }
+    bail!("'{}' failed with status {}", user_input, status.code().unwrap_or(1))
 }
 
 pub fn display_version(config: &Config) -> String {
@@ -426,7 +678,7 @@ pub fn display_version(config: &Config) -> String {
          however, if not, it can be manually set using the `version` field in the Cargo.toml file"
     );
     let cargo_version = {
-        std::process::Command::new("cargo")
+        crate::external::cargo_command()
             .arg("--version")
             .output()
             .map_err(|err| anyhow::anyhow!("failed to run 'cargo --version' because of {err}"))
@@ -495,28 +747,16 @@ fn toolchain_help(toolchain_environment: &ToolchainEnvironment) -> String {
     let miden = "miden".bold();
     let asterisk = "*".bold();
 
-    let available_aliases_text = "Available aliases:".bold().underline();
-    let available_aliases: String = toolchain_environment.get_aliases_display();
-
-    let available_components_text = "Available components:".bold().underline();
-    let available_components: String = toolchain_environment.get_executables_display();
-
-    let available_libraries_text = "Available libraries:".bold().underline();
-    let available_libraries: String = toolchain_environment.get_libraries_display();
+    let contents = toolchain_environment.get_contents_display();
 
     let help = "Help:".bold().underline();
 
     format!(
         "The Miden toolchain porcelain
 
-{usage} {miden} <ALIAS|COMPONENT>
+{usage} {miden} [+<CHANNEL>] <ALIAS|COMPONENT>
 
-{available_aliases_text}
-{available_aliases}
-{available_components_text}
-{available_components}
-{available_libraries_text}
-{available_libraries}
+{contents}
 
 {help}
   help                   Print this help message
@@ -528,6 +768,12 @@ fn toolchain_help(toolchain_environment: &ToolchainEnvironment) -> String {
     )
 }
 
+/// Lists the aliases, components and libraries available in `channel`, in the same format as
+/// `miden help toolchain`.
+pub(crate) fn channel_contents_display(channel: &Channel) -> String {
+    ToolchainEnvironment::new(channel, None).get_contents_display()
+}
+
 fn default_help() -> String {
     let asterisk = "*".bold();
     let help = "Help:".bold().underline();
@@ -588,6 +834,33 @@ fn resolve_argument(channel: &Channel, argument: &str) -> Result<MidenArgument,
     resolution
 }
 
+/// Function that tries to resolve `argument` to a component inside the `channel`, ignoring
+/// aliases.
+///
+/// Unlike [resolve_argument], components which are only meant to be called by their aliases are
+/// resolved as well, since this is used to call a component's executable directly.
+fn resolve_component_argument(
+    channel: &Channel,
+    argument: &str,
+) -> Result<MidenArgument, EnvironmentError> {
+    let Some(comp) = channel.components.iter().find(|comp| comp.name == argument) else {
+        return Err(EnvironmentError::UnknownArgument(format!(
+            "Failed to resolve '{}': Not a known component.",
+            argument
+        )));
+    };
+
+    match comp.get_installed_file() {
+        InstalledFile::Executable { .. } => Ok(MidenArgument::Component(comp.clone())),
+        InstalledFile::Library { library_name, library_struct: _ } => {
+            Err(EnvironmentError::LibraryAsExecutable(format!(
+                "'{}' installs the {} library. It is not intended to be executed as a binary.",
+                comp.name, library_name
+            )))
+        },
+    }
+}
+
 /// Why the active channel falls back on the installed channel.
 enum FallbackMotive {
     /// There simply is no active channel.
@@ -595,3 +868,91 @@ enum FallbackMotive {
     /// There is an active channel, yet the argument wasn't found.
     ArgumentNotInActiveChannel,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        EnvironmentError, ExecutionEnvironment, MidenArgument, ToolchainEnvironment,
+        resolve_argument, resolve_component_argument,
+    };
+    use crate::{
+        channel::{Channel, CliCommand, Component, InstalledFile},
+        version::Authority,
+    };
+
+    fn component(name: &'static str) -> Component {
+        Component::new(
+            name,
+            Authority::Cargo {
+                package: None,
+                version: semver::Version::new(0, 15, 0),
+            },
+        )
+    }
+
+    /// `midenup run` must resolve to the component even when an alias of another component
+    /// shadows its name.
+    #[test]
+    fn resolve_component_argument_ignores_aliases() {
+        let vm = component("vm");
+        let mut client = component("client");
+        client.aliases.insert(String::from("vm"), vec![CliCommand::Executable]);
+        let mut std = component("std");
+        std.set_installed_file(Some(InstalledFile::Library {
+            library_name: String::from("std"),
+            library_struct: String::from("MidenStdLib"),
+        }));
+        let channel =
+            Channel::new(semver::Version::new(0, 15, 0), None, vec![client, vm, std], vec![]);
+
+        assert!(matches!(
+            resolve_argument(&channel, "vm"),
+            Ok(MidenArgument::Alias(comp, _)) if comp.name == "client"
+        ));
+        assert!(matches!(
+            resolve_component_argument(&channel, "vm"),
+            Ok(MidenArgument::Component(comp)) if comp.name == "vm"
+        ));
+        assert!(matches!(
+            resolve_component_argument(&channel, "std"),
+            Err(EnvironmentError::LibraryAsExecutable(_))
+        ));
+        assert!(matches!(
+            resolve_component_argument(&channel, "compile"),
+            Err(EnvironmentError::UnknownArgument(_))
+        ));
+    }
+
+    /// Aliases and components missing from the active toolchain are only looked up in the other
+    /// installed toolchains once opted into, newest toolchain first.
+    #[test]
+    fn resolve_searches_other_channels_last() {
+        let version = |minor| semver::Version::new(0, minor, 0);
+        let active = Channel::new(version(16), None, vec![component("vm")], vec![]);
+        let mut client = component("client");
+        client.aliases.insert(String::from("account"), vec![CliCommand::Executable]);
+        let older = Channel::new(version(14), None, vec![client.clone()], vec![]);
+        let newer = Channel::new(version(15), None, vec![client], vec![]);
+
+        let mut environment = ToolchainEnvironment::new(&active, None);
+        assert!(matches!(
+            environment.resolve(String::from("account")),
+            Err(EnvironmentError::UnknownArgument(_))
+        ));
+
+        environment.other_channels = vec![&newer, &older];
+        assert!(matches!(
+            environment.resolve(String::from("account")),
+            Ok(ExecutionEnvironment { argument: MidenArgument::Alias(comp, _), active_channel })
+                if comp.name == "client" && active_channel.name == version(15)
+        ));
+        assert!(matches!(
+            environment.resolve(String::from("vm")),
+            Ok(ExecutionEnvironment { active_channel, .. }) if active_channel.name == version(16)
+        ));
+        assert!(matches!(
+            environment.resolve(String::from("compile")),
+            Err(EnvironmentError::UnknownArgument(_))
+        ));
+    }
+}
@@ -1,32 +1,218 @@
-use clap::{Parser, ValueEnum};
+use std::{num::NonZeroUsize, path::PathBuf, str::FromStr};
+
+use clap::{ArgAction, Parser, ValueEnum};
 
 use crate::{channel::Component, profile::Profile};
 
 pub const DEFAULT_USER_DATA_DIR: &str = "XDG_DATA_HOME";
 
+/// Environment variable naming the cargo registry to install components from, see
+/// [InstallationOptions::registry].
+pub const CARGO_REGISTRY_ENV: &str = "MIDENUP_CARGO_REGISTRY";
+
+/// Returns the registry set by [CARGO_REGISTRY_ENV], for the commands which install components
+/// without a `--registry` flag.
+pub fn registry_from_env() -> Option<String> {
+    std::env::var(CARGO_REGISTRY_ENV).ok().filter(|registry| !registry.is_empty())
+}
+
 /// Optional installation settings.
 #[derive(Default, Debug, Parser, Clone)]
 pub struct InstallationOptions {
     /// The toolchain profile to install
     #[arg(long, short, default_value = "minimal")]
     pub profile: Profile,
-    /// Displays the entirety of cargo's output when performing installations.
-    #[arg(long, short, default_value = "false")]
-    pub verbose: bool,
+    /// Displays the entirety of cargo's output when performing installations. Pass it twice
+    /// (`-vv`) to run cargo with `--verbose` as well.
+    #[arg(long, short, action = ArgAction::Count)]
+    pub verbose: u8,
+    /// Don't report the progress of artifact downloads.
+    #[arg(long, short, action)]
+    pub quiet: bool,
+    /// The maximum number of components to install concurrently. Defaults to 1.
+    ///
+    /// When greater than 1, the CPUs are split among the concurrent `cargo install`s.
+    #[arg(long, short)]
+    pub jobs: Option<NonZeroUsize>,
+    /// Only install these components (plus the components they require), e.g. `vm,client`.
+    ///
+    /// When set, the profile is ignored.
+    #[arg(long, value_name = "COMPONENTS", value_delimiter = ',')]
+    pub components: Vec<String>,
+    /// Install a component from a local crate instead of the channel's source, e.g.
+    /// `vm=path:/home/me/miden-vm`. Can be passed multiple times.
+    #[arg(long = "override", value_name = "COMPONENT=path:DIR")]
+    pub overrides: Vec<ComponentOverride>,
+    /// Enable a cargo feature of a component on top of the features enabled by the channel, e.g.
+    /// `vm/concurrent`. Can be passed multiple times.
+    #[arg(long, value_name = "COMPONENT/FEATURE", value_delimiter = ',')]
+    pub features: Vec<ComponentFeature>,
+    /// Don't enable the default cargo features of these components, e.g. `vm,client`.
+    #[arg(long, value_name = "COMPONENTS", value_delimiter = ',')]
+    pub no_default_features: Vec<String>,
+    /// Print what would be installed, without changing anything.
+    #[arg(long, action)]
+    pub dry_run: bool,
+    /// Install every component from scratch, even if the toolchain is already installed. Useful
+    /// to repair a broken toolchain.
+    #[arg(long, action)]
+    pub force: bool,
+    /// Print how long installing each component took once the install finishes. The timings are
+    /// also written to `install-timings.json` in the toolchain's directory.
+    #[arg(long, action)]
+    pub timings: bool,
+    /// Install the components published on crates.io from this cargo registry instead, e.g. a
+    /// private mirror. The registry must be defined in cargo's configuration. Also set by
+    /// `MIDENUP_CARGO_REGISTRY`.
+    #[arg(long, value_name = "NAME", env = CARGO_REGISTRY_ENV)]
+    pub registry: Option<String>,
+    /// Run every `cargo install` with `--frozen`: the lockfiles of the components' crates must be
+    /// up to date, and their dependencies must already be available without network access.
+    #[arg(long, action)]
+    pub frozen: bool,
     /// These are the components that will be uninstalled before re-installation.
     #[arg(skip)]
     pub components_to_uninstall: Vec<Component>,
+    /// Install the channel's components at exactly the given versions, ignoring `midenup pin`.
+    /// Used by `midenup import`.
+    #[arg(skip)]
+    pub ignore_pins: bool,
+    /// Install into another directory than the one the toolchain is currently installed in, so
+    /// that the current install is left untouched until the new one is published. Used by
+    /// `midenup reinstall`.
+    #[arg(skip)]
+    pub replace_install: bool,
+}
+
+/// A cargo feature enabled via `midenup install --features`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ComponentFeature {
+    /// The name of the component whose feature is enabled.
+    pub component: String,
+    /// The name of the feature.
+    pub feature: String,
+}
+
+impl FromStr for ComponentFeature {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once('/') {
+            Some((component, feature)) if !component.is_empty() && !feature.is_empty() => {
+                Ok(ComponentFeature {
+                    component: component.to_string(),
+                    feature: feature.to_string(),
+                })
+            },
+            _ => Err(format!("expected COMPONENT/FEATURE, found '{s}'")),
+        }
+    }
+}
+
+/// A component whose source is overridden via `midenup install --override`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ComponentOverride {
+    /// The name of the overridden component.
+    pub component: String,
+    /// The directory of the crate to install the component from.
+    pub path: PathBuf,
+}
+
+impl FromStr for ComponentOverride {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (component, source) = s
+            .split_once('=')
+            .ok_or_else(|| format!("expected COMPONENT=path:DIR, found '{s}'"))?;
+        let path = source
+            .strip_prefix("path:")
+            .ok_or_else(|| format!("unsupported source '{source}', expected path:DIR"))?;
+        if component.is_empty() || path.is_empty() {
+            return Err(format!("expected COMPONENT=path:DIR, found '{s}'"));
+        }
+
+        Ok(ComponentOverride {
+            component: component.to_string(),
+            path: PathBuf::from(path),
+        })
+    }
 }
 
 /// Optional update settings.
 #[derive(Default, Debug, Parser, Clone, Copy)]
 pub struct UpdateOptions {
-    /// Displays the entirety of cargo's output when performing installations.
-    #[clap(long, short, default_value = "false")]
-    pub verbose: bool,
+    /// Displays the entirety of cargo's output when performing installations. Pass it twice
+    /// (`-vv`) to run cargo with `--verbose` as well.
+    #[clap(long, short, action = ArgAction::Count)]
+    pub verbose: u8,
+    /// Don't report the progress of artifact downloads.
+    #[clap(long, short, action)]
+    pub quiet: bool,
     /// Determines how midenup will handle updates for components installed from a path
     #[clap(value_enum, short, long, default_value = "off")]
     pub path_update: PathUpdate,
+    /// Print what would be updated, without changing anything.
+    #[clap(long, action)]
+    pub dry_run: bool,
+    /// Only report which toolchains have updates available, without installing anything. Exits
+    /// with code 100 if any update is available.
+    #[clap(long, action, conflicts_with = "dry_run")]
+    pub check: bool,
+    /// When updating every installed toolchain, keep updating the remaining toolchains after one
+    /// fails. The failures are reported at the end.
+    #[clap(long, action)]
+    pub keep_going: bool,
+}
+
+/// Filters for the channels listed by `midenup list`. If neither is set, every channel is listed.
+#[derive(Default, Debug, Parser, Clone, Copy)]
+pub struct ListOptions {
+    /// Only list stable releases
+    #[arg(long, action)]
+    pub stable: bool,
+    /// Only list nightly channels
+    #[arg(long, action)]
+    pub nightly: bool,
+}
+
+/// The format in which `midenup` reports what it is doing.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum MessageFormat {
+    /// Human-readable messages
+    #[default]
+    Human,
+    /// Newline-delimited JSON events on stdout, see [crate::message::Message]. Human-readable
+    /// messages are printed to stderr instead.
+    Json,
+}
+
+/// When `midenup` and `miden` color their output.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ColorChoice {
+    /// Color the output if stdout is a terminal, unless `NO_COLOR` is set
+    #[default]
+    Auto,
+    /// Always color the output
+    Always,
+    /// Never color the output
+    Never,
+}
+
+impl ColorChoice {
+    /// Makes every message printed from now on follow this choice.
+    pub fn apply(self) {
+        match self {
+            // `NO_COLOR` disables colors when set to anything but an empty string, see
+            // https://no-color.org.
+            Self::Auto if std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty()) => {
+                colored::control::set_override(false)
+            },
+            Self::Auto => colored::control::unset_override(),
+            Self::Always => colored::control::set_override(true),
+            Self::Never => colored::control::set_override(false),
+        }
+    }
 }
 
 /// Represents the behavior chosen when a component being updated was installed from a path
@@ -47,6 +233,8 @@ impl From<InstallationOptions> for UpdateOptions {
     fn from(value: InstallationOptions) -> Self {
         UpdateOptions {
             verbose: value.verbose,
+            quiet: value.quiet,
+            dry_run: value.dry_run,
             ..Default::default()
         }
     }
@@ -57,7 +245,48 @@ impl From<UpdateOptions> for InstallationOptions {
         InstallationOptions {
             profile: Profile::Minimal,
             verbose: value.verbose,
+            quiet: value.quiet,
+            jobs: None,
+            components: Vec::new(),
+            overrides: Vec::new(),
+            features: Vec::new(),
+            no_default_features: Vec::new(),
+            dry_run: value.dry_run,
+            force: false,
+            timings: false,
+            registry: registry_from_env(),
+            frozen: false,
             components_to_uninstall: Vec::new(),
+            ignore_pins: false,
+            replace_install: false,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::{ComponentFeature, ComponentOverride};
+
+    /// `--override` only accepts `COMPONENT=path:DIR`.
+    #[test]
+    fn component_override_parses_path_sources() {
+        let overridden: ComponentOverride = "vm=path:/home/me/miden-vm".parse().unwrap();
+        assert_eq!(overridden.component, "vm");
+        assert_eq!(overridden.path, PathBuf::from("/home/me/miden-vm"));
+        assert!("vm=/home/me/miden-vm".parse::<ComponentOverride>().is_err());
+        assert!("vm=path:".parse::<ComponentOverride>().is_err());
+        assert!("vm".parse::<ComponentOverride>().is_err());
+    }
+
+    /// `--features` only accepts `COMPONENT/FEATURE`.
+    #[test]
+    fn component_feature_parses_component_and_feature() {
+        let feature: ComponentFeature = "vm/concurrent".parse().unwrap();
+        assert_eq!(feature.component, "vm");
+        assert_eq!(feature.feature, "concurrent");
+        assert!("vm".parse::<ComponentFeature>().is_err());
+        assert!("vm/".parse::<ComponentFeature>().is_err());
+    }
+}
@@ -18,6 +18,13 @@ use crate::{
     profile::Profile,
 };
 
+/// Name of the file in `MIDENUP_HOME` that stores the system's default toolchain, as set by
+/// `midenup override`.
+///
+/// It is only read if the `toolchains/default` symlink is missing, which is the case on
+/// filesystems that don't support symlinks.
+pub(crate) const DEFAULT_TOOLCHAIN_FILE_NAME: &str = "default-toolchain.toml";
+
 /// Represents a `miden-toolchain.toml` file.
 ///
 /// These file contains the desired toolchain to be used.
@@ -35,6 +42,45 @@ impl ToolchainFile {
     fn into_toolchain(self) -> Toolchain {
         self.toolchain
     }
+
+    /// Parses a `miden-toolchain.toml` file, expanding `${VAR}` references to environment
+    /// variables in its channel (e.g. `channel = "${MIDEN_CHANNEL}"`). See [expand_env_vars].
+    fn parse(contents: &str) -> anyhow::Result<Self> {
+        let mut file: toml::Table = toml::from_str(contents)?;
+        if let Some(toml::Value::String(channel)) =
+            file.get_mut("toolchain").and_then(|toolchain| toolchain.get_mut("channel"))
+        {
+            *channel = expand_env_vars(channel, |var| std::env::var(var).ok())
+                .context("invalid channel")?;
+        }
+
+        Ok(file.try_into()?)
+    }
+}
+
+/// Replaces every `${VAR}` in `value` with the value of `VAR`, as returned by `lookup`. Fails if
+/// any of the referenced variables is not set.
+fn expand_env_vars(value: &str, lookup: impl Fn(&str) -> Option<String>) -> anyhow::Result<String> {
+    let mut expanded = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find("${") {
+        expanded.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find('}') else {
+            bail!("unterminated variable reference in '{value}'");
+        };
+        let var = &rest[start + 2..start + end];
+        if var.is_empty() {
+            bail!("empty variable reference in '{value}'");
+        }
+        let Some(var_value) = lookup(var) else {
+            bail!("environment variable '{var}' referenced in '{value}' is not set");
+        };
+        expanded.push_str(&var_value);
+        rest = &rest[start + end + 1..];
+    }
+    expanded.push_str(rest);
+
+    Ok(expanded)
 }
 
 /// The actual contents of the toolchain.
@@ -47,12 +93,15 @@ pub struct Toolchain {
 }
 
 /// Used to specify why Midenup believes the current toolchain is what it is.
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
 pub enum ToolchainJustification {
     /// There exists a miden toolchain file present at `path`
     MidenToolchainFile { path: PathBuf },
     /// The system's default toolchain was overriden (via `midenup set`).
     Override,
+    /// The toolchain was passed to `miden` as `+<channel>`, for a single invocation.
+    CommandLine,
     /// No toolchain was specified, fallback to stable.
     Default,
 }
@@ -66,23 +115,17 @@ impl Toolchain {
     ///
     /// 1. The toolchain specified by a `miden-toolchain.toml` file in the present working directory
     /// 2. The toolchain that has been set as the system's default. If set, a `default` symlink is
-    ///    added to the `midenup` directory.
+    ///    added to the `midenup` directory. If the symlink is missing, the
+    ///    [DEFAULT_TOOLCHAIN_FILE_NAME] file is used instead.
     ///
     /// If none of the previous conditions are met, then `stable` will be used.
     pub fn current(config: &Config) -> anyhow::Result<(Toolchain, ToolchainJustification)> {
         let local_toolchain = Self::toolchain_file(&config.working_directory);
         let global_toolchain = config.midenup_home.join("toolchains").join("default");
+        let global_toolchain_file = config.midenup_home.join(DEFAULT_TOOLCHAIN_FILE_NAME);
 
         if let Some(local_toolchain) = local_toolchain {
-            let toolchain_file_contents =
-                std::fs::read_to_string(&local_toolchain).with_context(|| {
-                    format!("unable to read toolchain file '{}'", local_toolchain.display())
-                })?;
-
-            let toolchain_file: ToolchainFile =
-                toml::from_str(&toolchain_file_contents).context("invalid toolchain file")?;
-
-            let current_toolchain = toolchain_file.into_toolchain();
+            let current_toolchain = Self::from_toolchain_file(&local_toolchain)?;
 
             Ok((
                 current_toolchain,
@@ -105,16 +148,51 @@ impl Toolchain {
             };
 
             Ok((toolchain, ToolchainJustification::Override))
+        } else if global_toolchain_file.exists() {
+            let toolchain_file_contents = std::fs::read_to_string(&global_toolchain_file)
+                .with_context(|| {
+                    format!(
+                        "unable to read default toolchain file '{}'",
+                        global_toolchain_file.display()
+                    )
+                })?;
+
+            let toolchain_file: ToolchainFile = toml::from_str(&toolchain_file_contents)
+                .context("invalid default toolchain file")?;
+
+            Ok((toolchain_file.into_toolchain(), ToolchainJustification::Override))
         } else {
             Ok((Toolchain::default(), ToolchainJustification::Default))
         }
     }
 
+    /// Reads the toolchain set by the `miden-toolchain.toml` file at `path`.
+    pub fn from_toolchain_file(path: &Path) -> anyhow::Result<Toolchain> {
+        let toolchain_file_contents = std::fs::read_to_string(path)
+            .with_context(|| format!("unable to read toolchain file '{}'", path.display()))?;
+
+        let toolchain_file = ToolchainFile::parse(&toolchain_file_contents)
+            .with_context(|| format!("invalid toolchain file '{}'", path.display()))?;
+
+        Ok(toolchain_file.into_toolchain())
+    }
+
     pub fn ensure_current_is_installed(
         config: &Config,
         local_manifest: &mut Manifest,
     ) -> anyhow::Result<(Self, ToolchainJustification, Option<Channel>)> {
         let (current_toolchain, justification) = Toolchain::current(config)?;
+        Self::ensure_installed(current_toolchain, justification, config, local_manifest)
+    }
+
+    /// Installs `current_toolchain` if it isn't already, like
+    /// [Toolchain::ensure_current_is_installed]. `justification` is why it was selected.
+    pub fn ensure_installed(
+        current_toolchain: Toolchain,
+        justification: ToolchainJustification,
+        config: &Config,
+        local_manifest: &mut Manifest,
+    ) -> anyhow::Result<(Self, ToolchainJustification, Option<Channel>)> {
         let desired_channel = &current_toolchain.channel;
 
         let Some(channel) = config.manifest.get_channel(desired_channel) else {
@@ -129,47 +207,53 @@ impl Toolchain {
                     },
                     ToolchainJustification::Override =>
                         Cow::Borrowed("it was set using 'midenup set'"),
+                    ToolchainJustification::CommandLine =>
+                        Cow::Owned(format!("it was passed as '+{desired_channel}'")),
                 }
             );
         };
 
+        channel.validate()?;
         let partial_channel = channel.create_subset(&current_toolchain, &justification);
         let channel_to_install = partial_channel.as_ref().unwrap_or(channel);
 
-        if let Some(installed_channel) =
-            local_manifest.get_channel_by_name(&channel_to_install.name)
-        {
-            let required_components: HashSet<&str> = HashSet::from_iter(
-                channel_to_install.components.iter().map(|comp| comp.name.as_ref()),
-            );
-
-            let installed_components: HashSet<&str> = HashSet::from_iter(
-                installed_channel.components.iter().map(|comp| comp.name.as_ref()),
+        if missing_components(channel_to_install, local_manifest).is_some_and(|m| m.is_empty()) {
+            println!(
+                "{}: current toolchain is {desired_channel} and is installed",
+                "info".white().bold()
             );
+            return Ok((current_toolchain, justification, partial_channel));
+        }
 
-            let missing_components: Vec<_> =
-                required_components.difference(&installed_components).collect();
+        // Installing modifies midenup's state, so other midenup processes (e.g. parallel `miden`
+        // invocations) have to be kept out. Those might have installed the toolchain while we
+        // waited for the lock, in which case there's nothing left to do.
+        let _lock = config.lock()?;
+        *local_manifest = config.local_manifest()?;
 
-            if missing_components.is_empty() {
+        match missing_components(channel_to_install, local_manifest) {
+            Some(missing_components) if missing_components.is_empty() => {
                 println!(
                     "{}: current toolchain is {desired_channel} and is installed",
                     "info".white().bold()
                 );
                 return Ok((current_toolchain, justification, partial_channel));
-            }
-
-            println!(
-                "{}: installing missing components of the current toolchain:",
-                "info".white().bold()
-            );
-            for component in missing_components {
-                println!("- {}", component.white().bold());
-            }
-        } else {
-            println!(
-                "{}: current toolchain is {desired_channel}, but not yet installed",
-                "info".white().bold()
-            );
+            },
+            Some(missing_components) => {
+                println!(
+                    "{}: installing missing components of the current toolchain:",
+                    "info".white().bold()
+                );
+                for component in missing_components {
+                    println!("- {}", component.white().bold());
+                }
+            },
+            None => {
+                println!(
+                    "{}: current toolchain is {desired_channel}, but not yet installed",
+                    "info".white().bold()
+                );
+            },
         }
 
         commands::install(
@@ -183,6 +267,35 @@ impl Toolchain {
         Ok((current_toolchain, justification, partial_channel))
     }
 
+    /// Checks whether this toolchain is installed, without installing anything. `justification`
+    /// is why it was selected, see [Toolchain::current].
+    ///
+    /// The toolchain is only installed if every component it selects is, see
+    /// [Channel::create_subset].
+    pub fn installation_status(
+        &self,
+        config: &Config,
+        local_manifest: &Manifest,
+        justification: &ToolchainJustification,
+    ) -> InstallationStatus {
+        // Channels which are no longer available upstream are only known by the local manifest.
+        let Some(channel) = config.manifest.get_channel(&self.channel) else {
+            return match local_manifest.get_channel(&self.channel) {
+                Some(_) => InstallationStatus::Installed,
+                None => InstallationStatus::NotInstalled,
+            };
+        };
+
+        let partial_channel = channel.create_subset(self, justification);
+        match missing_components(partial_channel.as_ref().unwrap_or(channel), local_manifest) {
+            None => InstallationStatus::NotInstalled,
+            Some(missing) if missing.is_empty() => InstallationStatus::Installed,
+            Some(missing) => InstallationStatus::MissingComponents(
+                missing.into_iter().map(String::from).collect(),
+            ),
+        }
+    }
+
     /// Returns the `miden-toolchain.toml` file, if it exists.
     ///
     /// It looks for the file from the present working directory upwards, until the root directory
@@ -203,3 +316,82 @@ impl Toolchain {
         toolchain_file
     }
 }
+
+/// Whether a [Toolchain] is installed, see [Toolchain::installation_status].
+#[derive(Debug, PartialEq, Eq)]
+pub enum InstallationStatus {
+    /// Every component of the toolchain is installed.
+    Installed,
+    /// The toolchain's channel is installed, but without these components.
+    MissingComponents(Vec<String>),
+    /// The toolchain's channel is not installed at all.
+    NotInstalled,
+}
+
+/// Returns the components of `channel` that are missing from its locally installed counterpart,
+/// or `None` if the channel is not installed at all.
+fn missing_components<'a>(channel: &'a Channel, local_manifest: &Manifest) -> Option<Vec<&'a str>> {
+    let installed_channel = local_manifest.get_channel_by_name(&channel.name)?;
+
+    let installed_components: HashSet<&str> =
+        installed_channel.components.iter().map(|comp| comp.name.as_ref()).collect();
+
+    Some(
+        channel
+            .components
+            .iter()
+            .map(|comp| comp.name.as_ref())
+            .filter(|name| !installed_components.contains(name))
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ToolchainFile, expand_env_vars};
+    use crate::channel::UserChannel;
+
+    fn lookup(var: &str) -> Option<String> {
+        match var {
+            "MIDEN_CHANNEL" => Some(String::from("0.16.0")),
+            "SUFFIX" => Some(String::from("foo")),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn expand_env_vars_replaces_references() {
+        assert_eq!(expand_env_vars("stable", lookup).unwrap(), "stable");
+        assert_eq!(expand_env_vars("${MIDEN_CHANNEL}", lookup).unwrap(), "0.16.0");
+        assert_eq!(expand_env_vars("nightly-${SUFFIX}", lookup).unwrap(), "nightly-foo");
+    }
+
+    #[test]
+    fn expand_env_vars_rejects_undefined_variables() {
+        let err = expand_env_vars("${MIDEN_UNDEFINED}", lookup).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "environment variable 'MIDEN_UNDEFINED' referenced in '${MIDEN_UNDEFINED}' is not set"
+        );
+        assert!(expand_env_vars("${MIDEN_CHANNEL", lookup).is_err());
+        assert!(expand_env_vars("${}", lookup).is_err());
+    }
+
+    /// Toolchain files without variable references are parsed as they are.
+    #[test]
+    fn parse_toolchain_file() {
+        let toolchain = ToolchainFile::parse(
+            r#"
+            [toolchain]
+            channel = "0.15.0"
+            components = ["vm"]
+            "#,
+        )
+        .unwrap()
+        .into_toolchain();
+        assert!(
+            matches!(toolchain.channel, UserChannel::Version(version) if version == semver::Version::new(0, 15, 0))
+        );
+        assert_eq!(toolchain.components, ["vm"]);
+    }
+}
@@ -3,7 +3,8 @@
 pub mod git {
     use std::path::Path;
 
-    use anyhow::Context;
+    use anyhow::{Context, bail};
+    use colored::Colorize;
 
     pub fn find_latest_hash(repository_url: &str, branch_name: &str) -> anyhow::Result<String> {
         let check_revision_hash = std::process::Command::new("git")
@@ -35,28 +36,65 @@ pub mod git {
         Ok(revision_hash)
     }
 
+    /// Clones `revision` of the repository at `repository_url` into `dir`, fetching only that
+    /// revision if possible.
+    ///
+    /// Some servers refuse to fetch a commit by its hash unless it is the tip of a branch. In that
+    /// case, the whole repository is fetched instead.
+    ///
+    /// If `submodules` is set, the submodules of the repository are checked out as well,
+    /// recursively.
     // Used in tests
     #[allow(dead_code)]
     pub fn clone_specific_revision(
         repository_url: &str,
         revision: &str,
         dir: &Path,
+        submodules: bool,
     ) -> anyhow::Result<()> {
-        std::process::Command::new("git")
-            .arg("clone")
-            .args(["--revision", revision])
-            .arg("--depth=1")
-            .arg("--")
-            .arg(repository_url)
-            .arg(dir)
-            .stderr(std::process::Stdio::inherit())
-            .stdout(std::process::Stdio::inherit())
-            .spawn()
-            .context("Failed to spawn shell for git command")?
-            .wait()
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("failed to create directory '{}'", dir.display()))?;
+
+        run(dir, &["init", "--quiet"])
+            .and_then(|_| run(dir, &["remote", "add", "origin", repository_url]))
+            .and_then(|_| {
+                if run(dir, &["fetch", "--depth=1", "origin", revision]).is_ok() {
+                    return run(dir, &["reset", "--hard", "FETCH_HEAD"]);
+                }
+                eprintln!(
+                    "{}: couldn't fetch {revision} on its own, fetching all of {repository_url}",
+                    "info".white().bold()
+                );
+                run(dir, &["fetch", "origin"])?;
+                run(dir, &["reset", "--hard", revision])
+            })
             .with_context(|| {
                 format!("failed to clone {revision} of {repository_url} to {}", dir.display())
             })?;
+
+        if submodules {
+            run(dir, &["submodule", "update", "--init", "--recursive", "--depth=1"]).with_context(
+                || format!("failed to check out the submodules of {repository_url}"),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Runs `git` with `args` inside `dir`.
+    fn run(dir: &Path, args: &[&str]) -> anyhow::Result<()> {
+        let status = std::process::Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(args)
+            .stderr(std::process::Stdio::inherit())
+            .stdout(std::process::Stdio::inherit())
+            .status()
+            .context("Failed to spawn shell for git command, is git installed?")?;
+        if !status.success() {
+            bail!("`git {}` failed with {status}", args.join(" "));
+        }
+
         Ok(())
     }
 }
@@ -82,15 +120,33 @@ pub mod fs {
 
     const ENTRY_LIMIT: u32 = u32::MAX;
 
+    /// Directories skipped by [latest_modification] when looking for changes in a crate: build
+    /// output and VCS metadata change without the sources changing.
+    pub const IGNORED_DIRECTORIES: &[&str] = &["target", ".git", "node_modules"];
+
     /// Returns the latest registered modification time inside a directory, including its
-    /// subdirectories.
+    /// subdirectories. Subdirectories whose file name appears in `ignore` are not traversed.
     ///
     /// This is intended as a "best effort" approximation, if it encounters any errors while reading
     /// an entry, it simply skips it. Additionally, as a safety net, the `ENTRY_LIMIT` sets an upper
     /// bound on the number of entries the function can check before returning.
-    pub fn latest_modification(dir: &Path) -> anyhow::Result<(SystemTime, PathBuf)> {
+    pub fn latest_modification(
+        dir: &Path,
+        ignore: &[&str],
+    ) -> anyhow::Result<(SystemTime, PathBuf)> {
+        latest_modification_bounded(dir, ignore, ENTRY_LIMIT)
+    }
+
+    /// Same as [latest_modification], but checks at most `entry_limit` entries.
+    pub(super) fn latest_modification_bounded(
+        dir: &Path,
+        ignore: &[&str],
+        entry_limit: u32,
+    ) -> anyhow::Result<(SystemTime, PathBuf)> {
         fn traverse_directories(
             dir: &Path,
+            ignore: &[&str],
+            entry_limit: u32,
             latest: Option<(SystemTime, PathBuf)>,
             current_entry: u32,
         ) -> (Option<(SystemTime, PathBuf)>, u32) {
@@ -107,14 +163,20 @@ pub mod fs {
                         continue;
                     };
 
-                    if current_entry_count == ENTRY_LIMIT {
+                    if current_entry_count == entry_limit {
                         break;
                     }
 
+                    if metadata.is_dir()
+                        && file.file_name().to_str().is_some_and(|name| ignore.contains(&name))
+                    {
+                        continue;
+                    }
+
                     let (current_entry_latest, visited_entries) =
                     // We avoid symlinks to directories to avoid infinite loops.
                     if metadata.is_dir() && !metadata.is_symlink() {
-                        traverse_directories(&file.path(), local_latest.clone(), current_entry_count)
+                        traverse_directories(&file.path(), ignore, entry_limit, local_latest.clone(), current_entry_count)
                     } else {
                         (metadata.modified().ok().map(|metadata| (metadata, file.path())), current_entry_count + 1)
                     };
@@ -151,13 +213,63 @@ pub mod fs {
             .ok();
 
         let (latest_found_modification, _) =
-            traverse_directories(dir, directory_last_modification, 0);
+            traverse_directories(dir, ignore, entry_limit, directory_last_modification, 0);
 
         // This should only be an error if every single metadata read failed, which should be
         // unlikely.
         latest_found_modification.context("Failed to read any file")
     }
 
+    /// Returns the total size in bytes of the files inside a directory, including its
+    /// subdirectories.
+    ///
+    /// Like [latest_modification], this is a "best effort" approximation: entries that can't be
+    /// read are skipped, and symlinks are counted as themselves rather than followed.
+    pub fn disk_usage(dir: &Path) -> u64 {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return 0;
+        };
+
+        let mut size = 0;
+        for file in entries {
+            let Ok(file) = file else {
+                continue;
+            };
+            let Ok(metadata) = file.metadata() else {
+                continue;
+            };
+
+            // We avoid symlinks to directories to avoid infinite loops.
+            size += if metadata.is_dir() && !metadata.is_symlink() {
+                disk_usage(&file.path())
+            } else {
+                metadata.len()
+            };
+        }
+
+        size
+    }
+
+    /// Replaces the contents of the file at `path` with `contents`, so that readers either see the
+    /// previous contents or the new ones, but never a partially written file.
+    ///
+    /// The contents are written to a temporary file next to `path` first, which is then renamed
+    /// over it.
+    pub fn write_atomically(path: &Path, contents: &[u8]) -> anyhow::Result<()> {
+        use std::io::Write;
+
+        let tmp = path.with_extension("tmp");
+        let mut file = fs::File::create(&tmp)
+            .with_context(|| format!("failed to create temporary file '{}'", tmp.display()))?;
+        file.write_all(contents)
+            .with_context(|| format!("failed to write temporary file '{}'", tmp.display()))?;
+        // The contents must reach the disk before the rename does.
+        file.sync_all()
+            .with_context(|| format!("failed to flush temporary file '{}'", tmp.display()))?;
+        fs::rename(&tmp, path)
+            .with_context(|| format!("failed to rename {} -> {}", tmp.display(), path.display()))
+    }
+
     /// Recursively copy every entry from `src` into `dst`, preserving the directory layout and
     /// recreating symlinks. Entries whose file name appears in `skip` are not copied. `dst` is
     /// expected to already exist.
@@ -200,3 +312,201 @@ pub mod fs {
         Ok(())
     }
 }
+
+pub mod process {
+    use std::process::{Child, ExitStatus};
+
+    /// Waits for `child` to exit, making sure that the signals meant to stop it reach it, and that
+    /// midenup does not exit before it.
+    ///
+    /// Ctrl-C (SIGINT) and Ctrl-\\ (SIGQUIT) are sent by the terminal to the whole foreground
+    /// process group, `child` included, so midenup only ignores them and lets `child` decide what
+    /// to do. SIGTERM and SIGHUP are usually sent to midenup alone, so they get forwarded to
+    /// `child`.
+    pub fn wait_forwarding_signals(child: &mut Child) -> std::io::Result<ExitStatus> {
+        #[cfg(unix)]
+        {
+            unix::wait_forwarding_signals(child)
+        }
+        #[cfg(not(unix))]
+        {
+            child.wait()
+        }
+    }
+
+    #[cfg(unix)]
+    mod unix {
+        use std::{
+            process::{Child, ExitStatus},
+            sync::atomic::{AtomicI32, Ordering},
+        };
+
+        /// The process signals get forwarded to, or 0 if there is none.
+        pub(crate) static FORWARD_TO: AtomicI32 = AtomicI32::new(0);
+
+        extern "C" fn forward(signal: libc::c_int) {
+            let pid = FORWARD_TO.load(Ordering::SeqCst);
+            if pid > 0 {
+                // SAFETY: kill is async-signal-safe.
+                unsafe { libc::kill(pid, signal) };
+            }
+        }
+
+        pub fn wait_forwarding_signals(child: &mut Child) -> std::io::Result<ExitStatus> {
+            let handlers = [
+                (libc::SIGINT, libc::SIG_IGN),
+                (libc::SIGQUIT, libc::SIG_IGN),
+                (libc::SIGTERM, forward as extern "C" fn(libc::c_int) as libc::sighandler_t),
+                (libc::SIGHUP, forward as extern "C" fn(libc::c_int) as libc::sighandler_t),
+            ];
+
+            FORWARD_TO.store(child.id() as i32, Ordering::SeqCst);
+            let previous = handlers.map(|(signal, handler)| (signal, set_handler(signal, handler)));
+
+            let status = child.wait();
+
+            for (signal, action) in previous {
+                // SAFETY: `action` was returned by sigaction for this same signal.
+                unsafe { libc::sigaction(signal, &action, std::ptr::null_mut()) };
+            }
+            FORWARD_TO.store(0, Ordering::SeqCst);
+
+            status
+        }
+
+        /// Sets the handler of `signal`, returning the previous action.
+        fn set_handler(signal: libc::c_int, handler: libc::sighandler_t) -> libc::sigaction {
+            // SAFETY: both structs are fully initialized before sigaction reads them, and the
+            // handler only calls async-signal-safe functions.
+            unsafe {
+                let mut action: libc::sigaction = std::mem::zeroed();
+                action.sa_sigaction = handler;
+                action.sa_flags = libc::SA_RESTART;
+                libc::sigemptyset(&mut action.sa_mask);
+
+                let mut previous: libc::sigaction = std::mem::zeroed();
+                libc::sigaction(signal, &action, &mut previous);
+                previous
+            }
+        }
+    }
+
+    #[cfg(all(test, unix))]
+    pub(crate) use unix::FORWARD_TO;
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs::File,
+        path::{Path, PathBuf},
+        time::{Duration, SystemTime},
+    };
+
+    use super::fs::{
+        IGNORED_DIRECTORIES, disk_usage, latest_modification, latest_modification_bounded,
+    };
+
+    /// Creates an empty file at `path`, modified at `time`.
+    fn touch(path: &Path, time: SystemTime) -> PathBuf {
+        File::create(path).unwrap().set_modified(time).unwrap();
+        path.to_path_buf()
+    }
+
+    #[test]
+    fn latest_modification_finds_most_recent_file() {
+        let dir = tempdir::TempDir::new("latest_modification").unwrap();
+        let now = SystemTime::now();
+
+        std::fs::create_dir(dir.path().join("src")).unwrap();
+        touch(&dir.path().join("Cargo.toml"), now + Duration::from_secs(10));
+        touch(&dir.path().join("src").join("main.rs"), now + Duration::from_secs(20));
+        let latest = touch(&dir.path().join("src").join("lib.rs"), now + Duration::from_secs(60));
+        touch(&dir.path().join("README.md"), now + Duration::from_secs(30));
+
+        let (time, path) = latest_modification(dir.path(), IGNORED_DIRECTORIES).unwrap();
+        assert_eq!(path, latest);
+        assert_eq!(time, now + Duration::from_secs(60));
+    }
+
+    #[test]
+    fn latest_modification_skips_ignored_directories() {
+        let dir = tempdir::TempDir::new("latest_modification_ignore").unwrap();
+        let now = SystemTime::now();
+
+        let source = touch(&dir.path().join("lib.rs"), now + Duration::from_secs(60));
+        std::fs::create_dir(dir.path().join("target")).unwrap();
+        let build_output =
+            touch(&dir.path().join("target").join("output"), now + Duration::from_secs(120));
+
+        let (time, path) = latest_modification(dir.path(), IGNORED_DIRECTORIES).unwrap();
+        assert_eq!(path, source);
+        assert_eq!(time, now + Duration::from_secs(60));
+
+        let (_, path) = latest_modification(dir.path(), &[]).unwrap();
+        assert_eq!(path, build_output);
+    }
+
+    #[test]
+    fn latest_modification_stops_at_entry_limit() {
+        let dir = tempdir::TempDir::new("latest_modification_limit").unwrap();
+        touch(&dir.path().join("lib.rs"), SystemTime::now() + Duration::from_secs(60));
+
+        // No entries get checked, so only the directory itself is left.
+        let (_, path) = latest_modification_bounded(dir.path(), &[], 0).unwrap();
+        assert_eq!(path, dir.path());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn latest_modification_does_not_follow_directory_symlinks() {
+        let dir = tempdir::TempDir::new("latest_modification_symlink").unwrap();
+        let now = SystemTime::now();
+
+        std::fs::create_dir(dir.path().join("src")).unwrap();
+        let latest = touch(&dir.path().join("src").join("lib.rs"), now + Duration::from_secs(60));
+        // Following this symlink would loop forever.
+        std::os::unix::fs::symlink(dir.path(), dir.path().join("src").join("loop")).unwrap();
+
+        let (_, path) = latest_modification(dir.path(), IGNORED_DIRECTORIES).unwrap();
+        assert_eq!(path, latest);
+    }
+
+    #[test]
+    fn disk_usage_sums_nested_files() {
+        let dir = tempdir::TempDir::new("disk_usage").unwrap();
+        std::fs::create_dir_all(dir.path().join("bin")).unwrap();
+        std::fs::write(dir.path().join("manifest.json"), [0; 100]).unwrap();
+        std::fs::write(dir.path().join("bin").join("vm"), [0; 1000]).unwrap();
+
+        assert_eq!(disk_usage(dir.path()), 1100);
+        assert_eq!(disk_usage(&dir.path().join("missing")), 0);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn wait_forwards_sigterm_to_the_child() {
+        use std::sync::atomic::Ordering;
+
+        use super::process::{FORWARD_TO, wait_forwarding_signals};
+
+        let mut child = std::process::Command::new("sh")
+            .args(["-c", "trap 'exit 7' TERM; sleep 10 & wait"])
+            .spawn()
+            .unwrap();
+
+        let sender = std::thread::spawn(|| {
+            // Only signal midenup once it forwards signals, and the child has set up its trap.
+            while FORWARD_TO.load(Ordering::SeqCst) == 0 {
+                std::thread::sleep(Duration::from_millis(10));
+            }
+            std::thread::sleep(Duration::from_millis(200));
+            // SAFETY: sending a signal to ourselves, which is handled by now.
+            unsafe { libc::kill(libc::getpid(), libc::SIGTERM) };
+        });
+
+        let status = wait_forwarding_signals(&mut child).unwrap();
+        sender.join().unwrap();
+        assert_eq!(status.code(), Some(7));
+    }
+}
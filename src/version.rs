@@ -48,9 +48,9 @@ impl Default for GitTarget {
 impl fmt::Display for GitTarget {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match &self {
-            GitTarget::Branch { name, .. } => write!(f, "branch = \"{name}"),
-            GitTarget::Revision { hash } => write!(f, "rev = \"{hash}"),
-            GitTarget::Tag { name: tag } => write!(f, "tag = \"{tag}"),
+            GitTarget::Branch { name, .. } => write!(f, "branch = \"{name}\""),
+            GitTarget::Revision { hash } => write!(f, "rev = \"{hash}\""),
+            GitTarget::Tag { name: tag } => write!(f, "tag = \"{tag}\""),
         }
     }
 }
@@ -66,7 +66,7 @@ impl GitTarget {
 }
 
 /// Represents the canonical versioning authority for a tool or toolchain
-#[derive(Serialize, Deserialize, Debug, Clone, Hash)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
 #[serde(rename_all = "snake_case")]
 pub enum Authority {
     /// The authority for this tool/toolchain is a local filesystem path
@@ -108,6 +108,27 @@ pub enum Authority {
         /// The semantic versioning string for the package to fetch
         version: semver::Version,
     },
+    /// The authority for this tool/toolchain is crates.io, with the version of the package given
+    /// as a requirement (e.g. `^0.16`) rather than as an exact version.
+    ///
+    /// This only appears in upstream manifests: the requirement is resolved against crates.io when
+    /// the component is installed, and the component is recorded in the local manifest as an
+    /// [Authority::Cargo] with the resolved version. See
+    /// [crate::channel::Channel::resolve_versions].
+    ///
+    /// NOTE: This must come after [Authority::Cargo], since exact versions are valid requirements
+    /// too.
+    #[serde(untagged)]
+    CargoRequirement {
+        /// The name of the crates.io package under which this tool is provided.
+        ///
+        /// If `None`, then the package name is the same as the component
+        #[serde(skip_serializing_if = "Option::is_none")]
+        package: Option<String>,
+        /// The requirement the installed version of the package must match
+        #[serde(rename = "version")]
+        requirement: semver::VersionReq,
+    },
 }
 
 impl core::str::FromStr for Authority {
@@ -118,10 +139,32 @@ impl core::str::FromStr for Authority {
     }
 }
 
+impl Authority {
+    /// A short description of the version a component is installed from, e.g. `0.16.2` or
+    /// `main (abc1234)`.
+    pub fn describe(&self) -> String {
+        match self {
+            Authority::Cargo { version, .. } => version.to_string(),
+            Authority::CargoRequirement { requirement, .. } => requirement.to_string(),
+            Authority::Git { target, .. } => match target {
+                GitTarget::Branch { name, latest_revision: Some(revision) } => {
+                    let revision = revision.get(..7).unwrap_or(revision);
+                    format!("{name} ({revision})")
+                },
+                GitTarget::Branch { name, latest_revision: None } => name.clone(),
+                GitTarget::Revision { hash } => hash.get(..7).unwrap_or(hash).to_string(),
+                GitTarget::Tag { name } => name.clone(),
+            },
+            Authority::Path { path, .. } => path.display().to_string(),
+        }
+    }
+}
+
 impl fmt::Display for Authority {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match &self {
             Authority::Cargo { version, .. } => write!(f, "{version}"),
+            Authority::CargoRequirement { requirement, .. } => write!(f, "{requirement}"),
             Authority::Git { repository_url, target, .. } => {
                 write!(f, "{repository_url}:{target}")
             },
@@ -129,3 +172,47 @@ impl fmt::Display for Authority {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Authority, GitTarget};
+
+    #[test]
+    fn git_target_display_is_a_valid_toml_key_value() {
+        let tag = GitTarget::Tag { name: String::from("v1.0") };
+        assert_eq!(tag.to_string(), "tag = \"v1.0\"");
+
+        let revision = GitTarget::Revision { hash: String::from("abc123") };
+        assert_eq!(revision.to_string(), "rev = \"abc123\"");
+
+        let branch = GitTarget::Branch {
+            name: String::from("main"),
+            latest_revision: Some(String::from("abc123")),
+        };
+        assert_eq!(branch.to_string(), "branch = \"main\"");
+    }
+
+    /// Exact versions keep deserializing as [Authority::Cargo], anything else that is a valid
+    /// requirement as [Authority::CargoRequirement].
+    #[test]
+    fn cargo_versions_and_requirements() {
+        let exact: Authority = r#"{"package": "miden-vm", "version": "0.16.2"}"#.parse().unwrap();
+        assert_eq!(
+            exact,
+            Authority::Cargo {
+                package: Some(String::from("miden-vm")),
+                version: semver::Version::new(0, 16, 2),
+            }
+        );
+
+        let requirement: Authority = r#"{"version": "^0.16"}"#.parse().unwrap();
+        assert_eq!(
+            requirement,
+            Authority::CargoRequirement {
+                package: None,
+                requirement: semver::VersionReq::parse("^0.16").unwrap(),
+            }
+        );
+        assert_eq!(serde_json::to_string(&requirement).unwrap(), r#"{"version":"^0.16"}"#);
+    }
+}
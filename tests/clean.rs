@@ -0,0 +1,57 @@
+use clap::Parser;
+use midenup::{commands::Midenup, utils};
+
+mod common;
+
+use common::*;
+
+/// Checks that `midenup clean` removes unused install directories and dangling symlinks, while
+/// leaving installed toolchains alone.
+#[test]
+fn clean_removes_stale_entries() {
+    let test_name = "clean_removes_stale_entries";
+    let test_env = environment_setup(test_name);
+
+    const FILE: &str =
+        full_path_manifest!("tests/data/integration_miden_toolchain_toml/channel-manifest.json");
+
+    let (mut local_manifest, config) = test_setup(&test_env, FILE);
+
+    let toolchains_dir = test_env.midenup_home.join("toolchains");
+    let installed_toolchains_dir = test_env.midenup_home.join("installed_toolchains");
+    std::fs::create_dir_all(&toolchains_dir).unwrap();
+
+    // An installed toolchain, which must be kept.
+    let installed = installed_toolchains_dir.join("0.16.0-installed");
+    std::fs::create_dir_all(installed.join("bin")).unwrap();
+    utils::fs::symlink(
+        &toolchains_dir.join("0.16.0"),
+        std::path::Path::new("../installed_toolchains/0.16.0-installed"),
+    )
+    .unwrap();
+    utils::fs::symlink(&toolchains_dir.join("stable"), std::path::Path::new("0.16.0")).unwrap();
+
+    // Leftovers from an interrupted install of 0.15.0.
+    let interrupted = installed_toolchains_dir.join("0.15.0-interrupted");
+    std::fs::create_dir_all(interrupted.join("bin")).unwrap();
+    let temp_symlink = installed_toolchains_dir.join("0.15.0.new");
+    utils::fs::symlink(&temp_symlink, std::path::Path::new("0.15.0-interrupted")).unwrap();
+
+    // A symlink to a toolchain that was removed.
+    let dangling = toolchains_dir.join("0.14.0");
+    utils::fs::symlink(&dangling, std::path::Path::new("../installed_toolchains/0.14.0-removed"))
+        .unwrap();
+
+    let command = Midenup::try_parse_from(["midenup", "clean", "--yes"]).unwrap();
+    command
+        .execute_with_manifest(&config, &mut local_manifest)
+        .expect("failed to clean");
+
+    assert!(installed.exists());
+    assert!(toolchains_dir.join("0.16.0").exists());
+    assert!(toolchains_dir.join("stable").exists());
+
+    assert!(!interrupted.exists());
+    assert!(std::fs::symlink_metadata(&temp_symlink).is_err());
+    assert!(std::fs::symlink_metadata(&dangling).is_err());
+}
@@ -29,7 +29,7 @@ pub fn test_setup(env: &TestEnvironment, manifest_uri: &str) -> (LocalManifest,
             local_manifest_path.to_str().expect("Couldn't convert miden directory"),
         );
 
-        match manifest::Manifest::load_from(local_manifest_uri) {
+        match manifest::Manifest::load_from(local_manifest_uri, false) {
             Ok(manifest) => Ok(manifest),
             Err(manifest::ManifestError::Empty | manifest::ManifestError::Missing(_)) => {
                 Ok(manifest::Manifest::default())
@@ -43,8 +43,10 @@ pub fn test_setup(env: &TestEnvironment, manifest_uri: &str) -> (LocalManifest,
         env.present_working_dir.clone(),
         env.midenup_home.clone(),
         env.cargo_home.clone(),
-        manifest_uri,
+        Some(manifest_uri.to_string()),
         true,
+        false,
+        false,
     )
     .unwrap_or_else(|err| {
         panic!(
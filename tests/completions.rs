@@ -0,0 +1,42 @@
+mod common;
+
+use common::*;
+
+/// Checks that a completion script is generated for every supported shell, and that it offers the
+/// installed toolchains as channels where the shell supports it.
+#[test]
+fn completions_for_every_shell() {
+    let test_name = "completions_for_every_shell";
+    let test_env = environment_setup(test_name);
+
+    const FILE: &str =
+        full_path_manifest!("tests/data/uninstall_component_test/channel-manifest.json");
+
+    // Pretend that the toolchains from FILE are installed.
+    std::fs::create_dir_all(&test_env.midenup_home).unwrap();
+    std::fs::copy(
+        full_path!("tests/data/uninstall_component_test/channel-manifest.json"),
+        test_env.midenup_home.join("manifest.json"),
+    )
+    .unwrap();
+
+    for shell in ["bash", "elvish", "fish", "powershell", "zsh"] {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_midenup"))
+            .args(["completions", shell])
+            .current_dir(&test_env.present_working_dir)
+            .env("MIDENUP_HOME", &test_env.midenup_home)
+            .env("CARGO_HOME", &test_env.cargo_home)
+            .env("MIDENUP_MANIFEST_URI", FILE)
+            .output()
+            .expect("failed to run midenup");
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(output.status.success(), "failed to generate {shell} completions: {stderr}");
+
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert!(stdout.contains("uninstall"), "{shell} completions lack subcommands: {stdout}");
+        // Only the bash and zsh completions offer the values of positional arguments.
+        if matches!(shell, "bash" | "zsh") {
+            assert!(stdout.contains("0.16.0"), "{shell} completions lack channels: {stdout}");
+        }
+    }
+}
@@ -0,0 +1,85 @@
+mod common;
+
+use common::*;
+
+/// Checks that the manifest URI set with `midenup config set` is used unless the URI is given on
+/// the command line or through `MIDENUP_MANIFEST_URI`.
+#[test]
+fn config_manifest_uri_precedence() {
+    let test_name = "config_manifest_uri_precedence";
+    let test_env = environment_setup(test_name);
+
+    const FILE: &str = full_path_manifest!("tests/data/list_test/channel-manifest.json");
+    const OTHER_FILE: &str =
+        full_path_manifest!("tests/data/integration_miden_toolchain_toml/channel-manifest.json");
+
+    let midenup = |args: &[&str], manifest_uri_env: Option<&str>| {
+        let mut command = std::process::Command::new(env!("CARGO_BIN_EXE_midenup"));
+        command
+            .args(args)
+            .current_dir(&test_env.present_working_dir)
+            .env("MIDENUP_HOME", &test_env.midenup_home)
+            .env("CARGO_HOME", &test_env.cargo_home)
+            .env_remove("MIDENUP_MANIFEST_URI");
+        if let Some(uri) = manifest_uri_env {
+            command.env("MIDENUP_MANIFEST_URI", uri);
+        }
+        let output = command.output().expect("failed to run midenup");
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        String::from_utf8(output.stdout).unwrap()
+    };
+    let manifest_uri = |stdout: String| stdout.lines().nth(1).unwrap_or_default().to_string();
+
+    midenup(&["config", "set", "manifest-uri", FILE], Some(FILE));
+    assert_eq!(midenup(&["config", "get", "manifest-uri"], Some(FILE)).trim(), FILE);
+
+    let show_home = ["show", "home", "--manifest-uri"];
+    assert_eq!(manifest_uri(midenup(&show_home, None)), FILE);
+    assert_eq!(manifest_uri(midenup(&show_home, Some(OTHER_FILE))), OTHER_FILE);
+    let mut show_home_with_uri = vec!["--manifest-uri", OTHER_FILE];
+    show_home_with_uri.extend(show_home);
+    assert_eq!(manifest_uri(midenup(&show_home_with_uri, None)), OTHER_FILE);
+
+    midenup(&["config", "unset", "manifest-uri"], Some(FILE));
+    let stdout = midenup(&["config", "get", "manifest-uri"], Some(FILE));
+    assert!(stdout.contains("manifest-uri is not set"), "{stdout}");
+}
+
+/// Checks that invalid settings don't prevent midenup from running, and that they can be replaced
+/// with `midenup config set`.
+#[test]
+fn invalid_settings_are_ignored() {
+    let test_name = "invalid_settings_are_ignored";
+    let test_env = environment_setup(test_name);
+
+    const FILE: &str = full_path_manifest!("tests/data/list_test/channel-manifest.json");
+
+    std::fs::create_dir_all(&test_env.midenup_home).unwrap();
+    let settings_path = test_env.midenup_home.join("config.toml");
+    std::fs::write(&settings_path, "jobs = 2\nunknown-setting = true\n").unwrap();
+
+    let midenup = |args: &[&str]| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_midenup"))
+            .args(args)
+            .current_dir(&test_env.present_working_dir)
+            .env("MIDENUP_HOME", &test_env.midenup_home)
+            .env("CARGO_HOME", &test_env.cargo_home)
+            .env("MIDENUP_MANIFEST_URI", FILE)
+            .output()
+            .expect("failed to run midenup")
+    };
+
+    let output = midenup(&["show", "home"]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(output.status.success(), "{stderr}");
+    assert!(stderr.contains("invalid settings"), "{stderr}");
+
+    let output = midenup(&["config", "get", "jobs"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("unknown-setting"), "{stderr}");
+
+    let output = midenup(&["config", "set", "jobs", "4"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(std::fs::read_to_string(&settings_path).unwrap(), "jobs = 4\n");
+}
@@ -0,0 +1,289 @@
+mod common;
+
+use common::*;
+
+/// Checks that `midenup doctor` reports a failure when midenup was never initialized, since the
+/// `miden` symlink is missing.
+#[test]
+fn doctor_fails_when_uninitialized() {
+    let test_name = "doctor_fails_when_uninitialized";
+    let test_env = environment_setup(test_name);
+
+    const FILE: &str =
+        full_path_manifest!("tests/data/integration_miden_toolchain_toml/channel-manifest.json");
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_midenup"))
+        .arg("doctor")
+        .current_dir(&test_env.present_working_dir)
+        .env("MIDENUP_HOME", &test_env.midenup_home)
+        .env("CARGO_HOME", &test_env.cargo_home)
+        .env("MIDENUP_MANIFEST_URI", FILE)
+        .output()
+        .expect("failed to run midenup");
+    assert!(!output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("run `midenup init` to create it"), "{stdout}");
+}
+
+/// Checks that `midenup doctor` reports installed toolchains in which an alias shadows a
+/// component.
+#[test]
+fn doctor_reports_alias_collisions() {
+    let test_name = "doctor_reports_alias_collisions";
+    let test_env = environment_setup(test_name);
+
+    const FILE: &str = full_path_manifest!("tests/data/alias_collision_test/channel-manifest.json");
+
+    std::fs::create_dir_all(&test_env.midenup_home).unwrap();
+    std::fs::copy(
+        full_path!("tests/data/alias_collision_test/channel-manifest.json"),
+        test_env.midenup_home.join("manifest.json"),
+    )
+    .unwrap();
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_midenup"))
+        .arg("doctor")
+        .current_dir(&test_env.present_working_dir)
+        .env("MIDENUP_HOME", &test_env.midenup_home)
+        .env("CARGO_HOME", &test_env.cargo_home)
+        .env("MIDENUP_MANIFEST_URI", FILE)
+        .output()
+        .expect("failed to run midenup");
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        stdout.contains("alias 'vm' of component 'client' shadows component 'vm'"),
+        "{stdout}"
+    );
+}
+
+/// Checks that `midenup doctor` reports every problem that would make installing a toolchain fail,
+/// for toolchains that got installed anyway.
+#[test]
+fn doctor_reports_invalid_toolchains() {
+    let test_name = "doctor_reports_invalid_toolchains";
+    let test_env = environment_setup(test_name);
+
+    const FILE: &str = full_path_manifest!("tests/data/list_test/channel-manifest.json");
+
+    std::fs::create_dir_all(&test_env.midenup_home).unwrap();
+    std::fs::write(
+        test_env.midenup_home.join("manifest.json"),
+        r#"{
+            "manifest_version": "1.0.1",
+            "date": 1745931671,
+            "channels": [
+                {
+                    "name": "0.16.0",
+                    "components": [
+                        {
+                            "name": "fake",
+                            "package": "midenup-nonexistent-test-component",
+                            "version": "0.2.0",
+                            "requires": ["base"]
+                        },
+                        {
+                            "name": "other",
+                            "package": "midenup-nonexistent-test-component",
+                            "version": "0.2.0"
+                        }
+                    ]
+                }
+            ]
+        }"#,
+    )
+    .unwrap();
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_midenup"))
+        .arg("doctor")
+        .current_dir(&test_env.present_working_dir)
+        .env("MIDENUP_HOME", &test_env.midenup_home)
+        .env("CARGO_HOME", &test_env.cargo_home)
+        .env("MIDENUP_MANIFEST_URI", FILE)
+        .output()
+        .expect("failed to run midenup");
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("component 'fake' requires 'base'"), "{stdout}");
+    assert!(
+        stdout.contains(
+            "components 'fake', 'other' are all installed from package \
+             'midenup-nonexistent-test-component'"
+        ),
+        "{stdout}"
+    );
+}
+
+/// Checks that `midenup doctor` warns when the `miden` symlink points to another midenup
+/// executable, which uses a different home.
+#[test]
+fn doctor_warns_about_other_midenup_home() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let test_name = "doctor_warns_about_other_midenup_home";
+    let test_env = environment_setup(test_name);
+
+    const FILE: &str =
+        full_path_manifest!("tests/data/integration_miden_toolchain_toml/channel-manifest.json");
+
+    // A stand-in for another midenup executable, which only answers `show home`.
+    let other_midenup = test_env.present_working_dir.join("other-midenup");
+    std::fs::write(&other_midenup, "#!/bin/sh\necho /other/midenup/home\n").unwrap();
+    std::fs::set_permissions(&other_midenup, std::fs::Permissions::from_mode(0o755)).unwrap();
+    let cargo_bin = test_env.cargo_home.join("bin");
+    std::fs::create_dir_all(&cargo_bin).unwrap();
+    std::os::unix::fs::symlink(&other_midenup, cargo_bin.join("miden")).unwrap();
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_midenup"))
+        .arg("doctor")
+        .current_dir(&test_env.present_working_dir)
+        .env("MIDENUP_HOME", &test_env.midenup_home)
+        .env("CARGO_HOME", &test_env.cargo_home)
+        .env("MIDENUP_MANIFEST_URI", FILE)
+        .output()
+        .expect("failed to run midenup");
+    assert!(!output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        stdout.contains(&format!(
+            "`miden` uses '/other/midenup/home' as midenup's home, instead of '{}'",
+            test_env.midenup_home.display()
+        )),
+        "{stdout}"
+    );
+}
+
+/// Checks that midenup runs the `cargo` executable named by `MIDENUP_CARGO`.
+#[test]
+fn doctor_uses_midenup_cargo() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let test_name = "doctor_uses_midenup_cargo";
+    let test_env = environment_setup(test_name);
+
+    const FILE: &str =
+        full_path_manifest!("tests/data/integration_miden_toolchain_toml/channel-manifest.json");
+
+    // A stand-in for cargo, which records how it was called.
+    let calls = test_env.present_working_dir.join("cargo-calls");
+    let stub_cargo = test_env.present_working_dir.join("stub-cargo");
+    std::fs::write(
+        &stub_cargo,
+        format!("#!/bin/sh\necho \"$@\" >> '{}'\necho cargo 0.0.0-stub\n", calls.display()),
+    )
+    .unwrap();
+    std::fs::set_permissions(&stub_cargo, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_midenup"))
+        .arg("doctor")
+        .current_dir(&test_env.present_working_dir)
+        .env("MIDENUP_HOME", &test_env.midenup_home)
+        .env("CARGO_HOME", &test_env.cargo_home)
+        .env("MIDENUP_MANIFEST_URI", FILE)
+        .env("MIDENUP_CARGO", &stub_cargo)
+        .output()
+        .expect("failed to run midenup");
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("cargo is available (cargo 0.0.0-stub)"), "{stdout}");
+    let calls = std::fs::read_to_string(calls).unwrap();
+    assert!(calls.lines().any(|call| call == "+nightly --version"), "{calls}");
+}
+
+/// Fakes an install of both channels of the `uninstall_all_test` manifest, with 0.16.0 as stable,
+/// and then deletes 0.16.0 by hand so that the `stable` symlink dangles.
+fn fake_dangling_stable_symlink(test_env: &TestEnvironment) {
+    // Fake an install of both channels, with 0.16.0 as stable.
+    let toolchain_dir = test_env.midenup_home.join("toolchains");
+    std::fs::create_dir_all(&toolchain_dir).unwrap();
+    for version in ["0.15.0", "0.16.0"] {
+        let install_dir = test_env
+            .midenup_home
+            .join("installed_toolchains")
+            .join(format!("{version}-fake"));
+        for dir in ["bin", "opt", "lib"] {
+            std::fs::create_dir_all(install_dir.join(dir)).unwrap();
+        }
+        midenup::utils::fs::symlink(
+            &toolchain_dir.join(version),
+            std::path::Path::new(&format!("../installed_toolchains/{version}-fake")),
+        )
+        .unwrap();
+    }
+    midenup::utils::fs::symlink(&toolchain_dir.join("stable"), std::path::Path::new("0.16.0"))
+        .unwrap();
+    std::fs::copy(
+        full_path!("tests/data/uninstall_all_test/channel-manifest.json"),
+        test_env.midenup_home.join("manifest.json"),
+    )
+    .unwrap();
+
+    // Delete the stable toolchain by hand.
+    std::fs::remove_dir_all(test_env.midenup_home.join("installed_toolchains").join("0.16.0-fake"))
+        .unwrap();
+}
+
+/// Checks that `midenup doctor` repairs a `stable` symlink pointing to a toolchain that was deleted
+/// by hand, pointing it to the latest stable toolchain which is still installed.
+#[test]
+fn dangling_stable_symlink_is_repaired() {
+    let test_name = "dangling_stable_symlink_is_repaired";
+    let test_env = environment_setup(test_name);
+
+    const FILE: &str = full_path_manifest!("tests/data/uninstall_all_test/channel-manifest.json");
+
+    fake_dangling_stable_symlink(&test_env);
+    let toolchain_dir = test_env.midenup_home.join("toolchains");
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_midenup"))
+        .arg("doctor")
+        .current_dir(&test_env.present_working_dir)
+        .env("MIDENUP_HOME", &test_env.midenup_home)
+        .env("CARGO_HOME", &test_env.cargo_home)
+        .env("MIDENUP_MANIFEST_URI", FILE)
+        .output()
+        .expect("failed to run midenup");
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("it now points to 0.15.0"), "{stdout}");
+    assert_eq!(
+        std::fs::read_link(toolchain_dir.join("stable")).unwrap(),
+        std::path::Path::new("0.15.0")
+    );
+}
+
+/// Checks that the next command which modifies `MIDENUP_HOME` repairs a dangling `stable`
+/// symlink, while read-only commands leave it alone.
+#[test]
+fn dangling_stable_symlink_is_repaired_by_next_command() {
+    let test_name = "dangling_stable_symlink_is_repaired_by_next_command";
+    let test_env = environment_setup(test_name);
+
+    const FILE: &str = full_path_manifest!("tests/data/uninstall_all_test/channel-manifest.json");
+
+    fake_dangling_stable_symlink(&test_env);
+    let stable_symlink = test_env.midenup_home.join("toolchains").join("stable");
+
+    let midenup = |args: &[&str]| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_midenup"))
+            .args(args)
+            .current_dir(&test_env.present_working_dir)
+            .env("MIDENUP_HOME", &test_env.midenup_home)
+            .env("CARGO_HOME", &test_env.cargo_home)
+            .env("MIDENUP_MANIFEST_URI", FILE)
+            .output()
+            .expect("failed to run midenup")
+    };
+
+    midenup(&["list"]);
+    assert_eq!(std::fs::read_link(&stable_symlink).unwrap(), std::path::Path::new("0.16.0"));
+
+    // Without the repair, `clean` would remove the dangling `stable` symlink.
+    let output = midenup(&["clean", "--yes"]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(output.status.success(), "{stderr}");
+    assert!(stderr.contains("it now points to 0.15.0"), "{stderr}");
+    assert_eq!(std::fs::read_link(&stable_symlink).unwrap(), std::path::Path::new("0.15.0"));
+}
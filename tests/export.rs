@@ -0,0 +1,61 @@
+use midenup::{channel, commands::Lockfile, version};
+
+mod common;
+
+use common::*;
+
+/// Checks that `midenup export` locks the installed active toolchain, and that `midenup import`
+/// installs exactly the locked versions, regardless of pins.
+#[test]
+fn export_then_import() {
+    let test_name = "export_then_import";
+    let test_env = environment_setup(test_name);
+
+    const FILE: &str = full_path_manifest!("tests/data/list_test/channel-manifest.json");
+
+    // Pretend that the stable toolchain is installed, with an older version of its only component.
+    let (_, config) = test_setup(&test_env, FILE);
+    let mut installed_channel =
+        config.manifest.get_channel(&channel::UserChannel::Stable).unwrap().clone();
+    installed_channel.components[0].version = version::Authority::Cargo {
+        package: Some(String::from("midenup-nonexistent-test-component")),
+        version: semver::Version::new(0, 1, 5),
+    };
+    let mut local_manifest = LocalManifest::default();
+    local_manifest.add_channel(installed_channel.clone());
+    local_manifest.pin(String::from("fake"), semver::Version::new(0, 1, 0));
+    std::fs::create_dir_all(&test_env.midenup_home).unwrap();
+    std::fs::write(
+        test_env.midenup_home.join("manifest.json"),
+        serde_json::to_string_pretty(&local_manifest).unwrap(),
+    )
+    .unwrap();
+
+    let midenup = |args: &[&str]| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_midenup"))
+            .args(args)
+            .current_dir(&test_env.present_working_dir)
+            .env("MIDENUP_HOME", &test_env.midenup_home)
+            .env("CARGO_HOME", &test_env.cargo_home)
+            .env("MIDENUP_MANIFEST_URI", FILE)
+            .output()
+            .expect("failed to run midenup")
+    };
+
+    let output = midenup(&["export"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let lockfile: Lockfile = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(lockfile.channel.name, installed_channel.name);
+    assert_eq!(lockfile.channel.components, installed_channel.components);
+
+    let lockfile_path = test_env.present_working_dir.join("miden.lock");
+    std::fs::write(&lockfile_path, &output.stdout).unwrap();
+
+    let output = midenup(&["import", lockfile_path.to_str().unwrap(), "--dry-run"]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(output.status.success(), "{stdout}{}", String::from_utf8_lossy(&output.stderr));
+    assert!(
+        stdout.contains("from crates.io (midenup-nonexistent-test-component 0.1.5)"),
+        "{stdout}"
+    );
+}
@@ -0,0 +1,67 @@
+use clap::Parser;
+use midenup::{commands::Midenup, utils};
+
+mod common;
+
+use common::*;
+
+/// Checks that `midenup gc` removes the files of an installed toolchain which no component
+/// installed, while leaving the components' own files alone.
+#[test]
+fn gc_removes_orphaned_files() {
+    let test_name = "gc_removes_orphaned_files";
+    let test_env = environment_setup(test_name);
+
+    // Fake an install of the channel, as if its components had been installed from artifacts.
+    let install_dir = test_env.midenup_home.join("installed_toolchains").join("0.16.0-fake");
+    let toolchain_dir = test_env.midenup_home.join("toolchains");
+    for dir in ["bin", "opt", "lib"] {
+        std::fs::create_dir_all(install_dir.join(dir)).unwrap();
+    }
+    std::fs::create_dir_all(&toolchain_dir).unwrap();
+    utils::fs::symlink(
+        &toolchain_dir.join("0.16.0"),
+        std::path::Path::new("../installed_toolchains/0.16.0-fake"),
+    )
+    .unwrap();
+    std::fs::copy(
+        full_path!("tests/data/uninstall_component_test/channel-manifest.json"),
+        test_env.midenup_home.join("manifest.json"),
+    )
+    .unwrap();
+
+    let binary = install_dir.join("bin").join("fake");
+    let library = install_dir.join("lib").join("fakelib.masp");
+    let opt_symlink = install_dir.join("opt").join("miden fake");
+    std::fs::write(&binary, "#!/bin/sh\n").unwrap();
+    std::fs::write(&library, "").unwrap();
+    utils::fs::symlink(&opt_symlink, std::path::Path::new("../bin/fake")).unwrap();
+
+    // Leftovers of a failed install.
+    let stray_binary = install_dir.join("bin").join("stray");
+    let stray_library = install_dir.join("lib").join("old.masp");
+    let stray_symlink = install_dir.join("opt").join("miden stray");
+    let dangling_symlink = install_dir.join("opt").join("miden removed");
+    std::fs::write(&stray_binary, "#!/bin/sh\n").unwrap();
+    std::fs::write(&stray_library, "").unwrap();
+    utils::fs::symlink(&stray_symlink, std::path::Path::new("../bin/stray")).unwrap();
+    utils::fs::symlink(&dangling_symlink, std::path::Path::new("../bin/removed")).unwrap();
+
+    const FILE: &str =
+        full_path_manifest!("tests/data/uninstall_component_test/channel-manifest.json");
+    let (mut local_manifest, config) = test_setup(&test_env, FILE);
+
+    let command = Midenup::try_parse_from(["midenup", "gc", "--yes"]).unwrap();
+    command
+        .execute_with_manifest(&config, &mut local_manifest)
+        .expect("failed to collect orphaned files");
+
+    assert!(binary.exists());
+    assert!(library.exists());
+    assert!(opt_symlink.exists());
+
+    assert!(!stray_binary.exists());
+    assert!(!stray_library.exists());
+    assert!(std::fs::symlink_metadata(&stray_symlink).is_err());
+    assert!(std::fs::symlink_metadata(&dangling_symlink).is_err());
+}
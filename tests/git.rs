@@ -0,0 +1,96 @@
+use std::{path::Path, sync::Once};
+
+use midenup::utils;
+
+/// Configures the git commands run by these tests.
+///
+/// Repositories are cloned from local paths here, which git forbids for submodules by default.
+/// Version 0 of git's protocol is used since, like some servers, it refuses to fetch commits by
+/// hash unless they are the tip of a branch.
+fn configure_git() {
+    static CONFIGURE: Once = Once::new();
+    // SAFETY: Every test calls this before spawning any process, and waits for it to finish.
+    CONFIGURE.call_once(|| unsafe {
+        std::env::set_var("GIT_CONFIG_COUNT", "2");
+        std::env::set_var("GIT_CONFIG_KEY_0", "protocol.file.allow");
+        std::env::set_var("GIT_CONFIG_VALUE_0", "always");
+        std::env::set_var("GIT_CONFIG_KEY_1", "protocol.version");
+        std::env::set_var("GIT_CONFIG_VALUE_1", "0");
+    });
+}
+
+/// Runs `git` with `args` inside `dir`, panicking if it fails.
+fn git(dir: &Path, args: &[&str]) -> String {
+    let output = std::process::Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["-c", "user.name=midenup", "-c", "user.email=midenup@example.com"])
+        .args(args)
+        .output()
+        .expect("failed to run git");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    String::from_utf8(output.stdout).unwrap().trim().to_string()
+}
+
+/// Commits `file` to the repository in `dir`, and returns the hash of the commit.
+fn commit_file(dir: &Path, file: &str) -> String {
+    std::fs::write(dir.join(file), file).unwrap();
+    git(dir, &["add", file]);
+    git(dir, &["commit", "--quiet", "-m", file]);
+    git(dir, &["rev-parse", "HEAD"])
+}
+
+/// Creates a repository in `dir` holding a single file, and returns the hash of its commit.
+fn create_repository(dir: &Path, file: &str) -> String {
+    std::fs::create_dir_all(dir).unwrap();
+    git(dir, &["init", "--quiet"]);
+    commit_file(dir, file)
+}
+
+/// Checks that `clone_specific_revision` checks out the submodules of a repository when asked to.
+#[test]
+fn clone_specific_revision_with_submodules() {
+    configure_git();
+    let dir = tempdir::TempDir::new("clone_specific_revision_with_submodules").unwrap();
+    let submodule = dir.path().join("submodule");
+    let repository = dir.path().join("repository");
+    create_repository(&submodule, "lib.rs");
+    create_repository(&repository, "main.rs");
+    git(
+        &repository,
+        &["submodule", "--quiet", "add", submodule.to_str().unwrap(), "vendor"],
+    );
+    git(&repository, &["commit", "--quiet", "-m", "add submodule"]);
+    let revision = git(&repository, &["rev-parse", "HEAD"]);
+    let repository_url = format!("file://{}", repository.display());
+
+    let clone = dir.path().join("clone");
+    utils::git::clone_specific_revision(&repository_url, &revision, &clone, true).unwrap();
+    assert_eq!(git(&clone, &["rev-parse", "HEAD"]), revision);
+    assert!(clone.join("main.rs").exists());
+    assert!(clone.join("vendor").join("lib.rs").exists());
+
+    // Without submodules, only the repository itself is checked out.
+    let clone = dir.path().join("clone-without-submodules");
+    utils::git::clone_specific_revision(&repository_url, &revision, &clone, false).unwrap();
+    assert!(clone.join("main.rs").exists());
+    assert!(!clone.join("vendor").join("lib.rs").exists());
+}
+
+/// Checks that `clone_specific_revision` can clone a revision which isn't the tip of a branch, even
+/// if the server refuses to fetch it on its own.
+#[test]
+fn clone_specific_revision_older_revision() {
+    configure_git();
+    let dir = tempdir::TempDir::new("clone_specific_revision_older_revision").unwrap();
+    let repository = dir.path().join("repository");
+    let revision = create_repository(&repository, "main.rs");
+    commit_file(&repository, "lib.rs");
+    let repository_url = format!("file://{}", repository.display());
+
+    let clone = dir.path().join("clone");
+    utils::git::clone_specific_revision(&repository_url, &revision, &clone, false).unwrap();
+    assert_eq!(git(&clone, &["rev-parse", "HEAD"]), revision);
+    assert!(clone.join("main.rs").exists());
+    assert!(!clone.join("lib.rs").exists());
+}
@@ -77,8 +77,13 @@ fn integration_install_from_non_cargo() {
         // Commit corresponding to release number 0.16.4 of the miden-vm
         // See https://github.com/0xMiden/miden-vm/releases/tag/v0.16.4
         let vm_release_16 = "fc368686bd1e6e171a51a1a5b365ef5400e4b8d5";
-        utils::git::clone_specific_revision(miden_vm_repo, vm_release_16, &miden_vm_clone_path)
-            .unwrap();
+        utils::git::clone_specific_revision(
+            miden_vm_repo,
+            vm_release_16,
+            &miden_vm_clone_path,
+            false,
+        )
+        .unwrap();
     };
 
     // Initial manifest with a client tracked by version::Authority::Git::Revision
@@ -274,3 +279,320 @@ fn integration_test_components_are_runnable() {
         });
     }
 }
+
+/// Checks that installing a channel in which an alias shadows a component fails before anything
+/// is installed, naming the collision.
+#[test]
+fn install_rejects_alias_collisions() {
+    let test_name = "install_rejects_alias_collisions";
+    let test_env = environment_setup(test_name);
+
+    const FILE: &str = full_path_manifest!("tests/data/alias_collision_test/channel-manifest.json");
+
+    let (mut local_manifest, config) = test_setup(&test_env, FILE);
+
+    let command = Midenup::try_parse_from(["midenup", "install", "0.16.0"]).unwrap();
+    let err = command
+        .execute_with_manifest(&config, &mut local_manifest)
+        .expect_err("installed a channel with an alias shadowing a component");
+    let message = format!("{err:#}");
+    assert!(
+        message.contains("alias 'vm' of component 'client' shadows component 'vm'"),
+        "{message}"
+    );
+
+    assert!(!test_env.midenup_home.join("toolchains").join("0.16.0").exists());
+}
+
+/// Checks that installing fails before anything is installed when the nightly toolchain needed to
+/// run the install script is missing, explaining how to install it.
+#[test]
+fn install_requires_nightly_toolchain() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let test_name = "install_requires_nightly_toolchain";
+    let test_env = environment_setup(test_name);
+
+    const FILE: &str = full_path_manifest!("tests/data/list_test/channel-manifest.json");
+
+    // A stand-in for cargo without any toolchain installed.
+    let stub_cargo = test_env.present_working_dir.join("stub-cargo");
+    std::fs::write(
+        &stub_cargo,
+        "#!/bin/sh\necho \"error: toolchain '$1' is not installed\" >&2\nexit 1\n",
+    )
+    .unwrap();
+    std::fs::set_permissions(&stub_cargo, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_midenup"))
+        .args(["install", "stable"])
+        .current_dir(&test_env.present_working_dir)
+        .env("MIDENUP_HOME", &test_env.midenup_home)
+        .env("CARGO_HOME", &test_env.cargo_home)
+        .env("MIDENUP_MANIFEST_URI", FILE)
+        .env("MIDENUP_CARGO", &stub_cargo)
+        .output()
+        .expect("failed to run midenup");
+    assert!(!output.status.success());
+
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("rust toolchain 'nightly' is not available"), "{stderr}");
+    assert!(stderr.contains("rustup toolchain install nightly"), "{stderr}");
+    assert!(!test_env.midenup_home.join("toolchains").join("0.16.0").exists());
+}
+
+/// Checks that `midenup install --dry-run` prints what it would install without touching the
+/// filesystem.
+#[test]
+fn install_dry_run_changes_nothing() {
+    let test_name = "install_dry_run_changes_nothing";
+    let test_env = environment_setup(test_name);
+
+    const FILE: &str = full_path_manifest!("tests/data/list_test/channel-manifest.json");
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_midenup"))
+        .args(["install", "stable", "--dry-run"])
+        .current_dir(&test_env.present_working_dir)
+        .env("MIDENUP_HOME", &test_env.midenup_home)
+        .env("CARGO_HOME", &test_env.cargo_home)
+        .env("MIDENUP_MANIFEST_URI", FILE)
+        .output()
+        .expect("failed to run midenup");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(output.status.success(), "{stdout}{}", String::from_utf8_lossy(&output.stderr));
+
+    assert!(
+        stdout.contains("from crates.io (midenup-nonexistent-test-component 0.2.0)"),
+        "{stdout}"
+    );
+    assert!(
+        stdout.contains("add toolchain 0.16.0 to the local manifest, as stable"),
+        "{stdout}"
+    );
+
+    assert!(!test_env.midenup_home.exists());
+    assert!(!test_env.cargo_home.exists());
+}
+
+/// Checks that `midenup install --manifest` loads the manifest from a plain path, overriding
+/// `MIDENUP_MANIFEST_URI`.
+#[test]
+fn install_with_manifest_file() {
+    let test_name = "install_with_manifest_file";
+    let test_env = environment_setup(test_name);
+
+    const FILE: &str = full_path_manifest!("tests/data/list_test/channel-manifest.json");
+    let path = FILE.strip_prefix("file://").unwrap();
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_midenup"))
+        .args(["install", "stable", "--dry-run", "--manifest", path])
+        .current_dir(&test_env.present_working_dir)
+        .env("MIDENUP_HOME", &test_env.midenup_home)
+        .env("CARGO_HOME", &test_env.cargo_home)
+        .env("MIDENUP_MANIFEST_URI", "file:///nonexistent/channel-manifest.json")
+        .output()
+        .expect("failed to run midenup");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(output.status.success(), "{stdout}{}", String::from_utf8_lossy(&output.stderr));
+
+    assert!(
+        stdout.contains("add toolchain 0.16.0 to the local manifest, as stable"),
+        "{stdout}"
+    );
+}
+
+/// Checks that `midenup install --toolchain-file` installs the channel and components selected by
+/// a `miden-toolchain.toml` file.
+#[test]
+fn install_from_toolchain_file() {
+    let test_name = "install_from_toolchain_file";
+    let test_env = environment_setup(test_name);
+
+    const FILE: &str =
+        full_path_manifest!("tests/data/integration_miden_toolchain_toml/channel-manifest.json");
+
+    let toolchain_file = test_env.present_working_dir.join("miden-toolchain.toml");
+    std::fs::create_dir_all(&test_env.present_working_dir).unwrap();
+    std::fs::write(
+        &toolchain_file,
+        "[toolchain]\nchannel = \"0.15.0\"\ncomponents = [\"midenc\"]\n",
+    )
+    .unwrap();
+
+    let midenup = |toolchain_file: &std::path::Path| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_midenup"))
+            .args(["install", "--dry-run", "--toolchain-file"])
+            .arg(toolchain_file)
+            .current_dir(&test_env.present_working_dir)
+            .env("MIDENUP_HOME", &test_env.midenup_home)
+            .env("CARGO_HOME", &test_env.cargo_home)
+            .env("MIDENUP_MANIFEST_URI", FILE)
+            .output()
+            .expect("failed to run midenup")
+    };
+
+    let output = midenup(&toolchain_file);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(output.status.success(), "{stdout}{}", String::from_utf8_lossy(&output.stderr));
+    assert!(stdout.contains("toolchain 0.15.0"), "{stdout}");
+    for component in ["vm", "midenc", "core"] {
+        assert!(stdout.contains(&format!("download {component} ")), "{stdout}");
+    }
+    assert!(!stdout.contains("download debug "), "{stdout}");
+
+    let output = midenup(&test_env.present_working_dir.join("missing.toml"));
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("unable to read toolchain file"), "{stderr}");
+}
+
+/// Checks that `midenup reinstall` keeps exactly the components of a partially installed
+/// toolchain, and replaces its previous install.
+#[test]
+fn integration_reinstall_partial_toolchain() {
+    let test_name = "integration_reinstall_partial_toolchain";
+    let test_env = environment_setup(test_name);
+
+    const FILE: &str = full_path_manifest!("manifest/channel-manifest.json");
+
+    let (mut local_manifest, config) = test_setup(&test_env, FILE);
+
+    let command =
+        Midenup::try_parse_from(["midenup", "install", "0.15.0", "--components", "verify"])
+            .unwrap();
+    command
+        .execute_with_manifest(&config, &mut local_manifest)
+        .expect("failed to install 0.15.0");
+
+    let command = Midenup::try_parse_from(["midenup", "reinstall", "0.15.0"]).unwrap();
+    command
+        .execute_with_manifest(&config, &mut local_manifest)
+        .expect("failed to reinstall 0.15.0");
+
+    let channel = local_manifest
+        .get_channel(&channel::UserChannel::Version(semver::Version::new(0, 15, 0)))
+        .expect("0.15.0 is no longer installed");
+    assert!(channel.is_partially_installed());
+    let components = channel.components.iter().map(|c| c.name.as_ref()).collect::<Vec<&str>>();
+    assert_eq!(components, ["verify"]);
+
+    let installs = std::fs::read_dir(test_env.midenup_home.join("installed_toolchains"))
+        .unwrap()
+        .count();
+    assert_eq!(installs, 1);
+}
+
+/// Checks that `midenup reinstall --dry-run` would reinstall the components recorded in the local
+/// manifest, rather than the ones of the upstream channel.
+#[test]
+fn reinstall_dry_run_keeps_installed_components() {
+    let test_name = "reinstall_dry_run_keeps_installed_components";
+    let test_env = environment_setup(test_name);
+
+    const FILE: &str = full_path_manifest!("tests/data/list_test/channel-manifest.json");
+
+    let local_manifest = r#"{
+        "manifest_version": "1.0.1",
+        "date": 1745931671,
+        "channels": [
+            {
+                "name": "0.16.0",
+                "tags": ["partial"],
+                "components": [
+                    {
+                        "name": "vm",
+                        "package": "miden-vm",
+                        "version": "0.16.0",
+                        "optional": true
+                    }
+                ]
+            }
+        ]
+    }"#;
+    std::fs::create_dir_all(&test_env.midenup_home).unwrap();
+    let local_manifest_path = test_env.midenup_home.join("manifest.json");
+    std::fs::write(&local_manifest_path, local_manifest).unwrap();
+
+    let midenup = |args: &[&str]| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_midenup"))
+            .args(args)
+            .current_dir(&test_env.present_working_dir)
+            .env("MIDENUP_HOME", &test_env.midenup_home)
+            .env("CARGO_HOME", &test_env.cargo_home)
+            .env("MIDENUP_MANIFEST_URI", FILE)
+            .output()
+            .expect("failed to run midenup")
+    };
+
+    let output = midenup(&["reinstall", "0.16.0", "--dry-run"]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(output.status.success(), "{stdout}{}", String::from_utf8_lossy(&output.stderr));
+    assert!(stdout.contains("from crates.io (miden-vm 0.16.0)"), "{stdout}");
+    assert!(!stdout.contains("midenup-nonexistent-test-component"), "{stdout}");
+    assert_eq!(std::fs::read_to_string(&local_manifest_path).unwrap(), local_manifest);
+
+    let output = midenup(&["reinstall", "0.15.0", "--dry-run"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("toolchain '0.15.0' is not installed"), "{stderr}");
+}
+
+/// Checks that a failed `midenup reinstall` leaves the previous install of the toolchain in place,
+/// even though the reinstall builds the same components as the previous install.
+#[test]
+fn failed_reinstall_keeps_previous_install() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let test_name = "failed_reinstall_keeps_previous_install";
+    let test_env = environment_setup(test_name);
+
+    const FILE: &str = full_path_manifest!("tests/data/list_test/channel-manifest.json");
+
+    std::fs::create_dir_all(&test_env.midenup_home).unwrap();
+    std::fs::copy(
+        full_path!("tests/data/list_test/channel-manifest.json"),
+        test_env.midenup_home.join("manifest.json"),
+    )
+    .unwrap();
+
+    // Fake the install of 0.16.0, in the directory a reinstall would otherwise start over from.
+    let (local_manifest, _) = test_setup(&test_env, FILE);
+    let hash = local_manifest
+        .get_channel(&channel::UserChannel::Version(semver::Version::new(0, 16, 0)))
+        .unwrap()
+        .content_hash();
+    let install_dir_name = format!("0.16.0-{hash}");
+    let install_dir = test_env.midenup_home.join("installed_toolchains").join(&install_dir_name);
+    std::fs::create_dir_all(install_dir.join("bin")).unwrap();
+    std::fs::write(install_dir.join("bin").join("midenup-nonexistent-test-component"), "").unwrap();
+    let toolchains_dir = test_env.midenup_home.join("toolchains");
+    std::fs::create_dir_all(&toolchains_dir).unwrap();
+    let target = std::path::Path::new("..").join("installed_toolchains").join(&install_dir_name);
+    std::os::unix::fs::symlink(&target, toolchains_dir.join("0.16.0")).unwrap();
+
+    // A stand-in for cargo which has a nightly toolchain, but fails to run the install script.
+    let stub_cargo = test_env.present_working_dir.join("stub-cargo");
+    std::fs::write(
+        &stub_cargo,
+        "#!/bin/sh\nif [ \"$2\" = --version ]; then echo 'cargo 1.90.0-nightly'; exit 0; fi\nexit \
+         1\n",
+    )
+    .unwrap();
+    std::fs::set_permissions(&stub_cargo, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_midenup"))
+        .args(["reinstall", "0.16.0"])
+        .current_dir(&test_env.present_working_dir)
+        .env("MIDENUP_HOME", &test_env.midenup_home)
+        .env("CARGO_HOME", &test_env.cargo_home)
+        .env("MIDENUP_MANIFEST_URI", FILE)
+        .env("MIDENUP_CARGO", &stub_cargo)
+        .output()
+        .expect("failed to run midenup");
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(!output.status.success());
+    assert!(stderr.contains("failed to install toolchain"), "{stderr}");
+
+    assert!(install_dir.join("bin").join("midenup-nonexistent-test-component").exists());
+    assert_eq!(std::fs::read_link(toolchains_dir.join("0.16.0")).unwrap(), target);
+}
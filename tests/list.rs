@@ -0,0 +1,47 @@
+mod common;
+
+use common::*;
+
+/// Checks that `midenup list` marks the stable and nightly channels, and that `--stable` and
+/// `--nightly` filter the listed channels.
+#[test]
+fn list_filters_channels() {
+    let test_name = "list_filters_channels";
+    let test_env = environment_setup(test_name);
+
+    const FILE: &str = full_path_manifest!("tests/data/list_test/channel-manifest.json");
+
+    let list = |args: &[&str]| {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_midenup"))
+            .arg("list")
+            .args(args)
+            .current_dir(&test_env.present_working_dir)
+            .env("MIDENUP_HOME", &test_env.midenup_home)
+            .env("CARGO_HOME", &test_env.cargo_home)
+            .env("MIDENUP_MANIFEST_URI", FILE)
+            .output()
+            .expect("failed to run midenup");
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        String::from_utf8(output.stdout).unwrap()
+    };
+    let line = |output: &str, channel: &str| {
+        output
+            .lines()
+            .find(|line| line.starts_with(channel))
+            .unwrap_or_else(|| panic!("{channel} is not listed: {output}"))
+            .to_string()
+    };
+
+    let all = list(&[]);
+    assert!(!line(&all, "0.15.0").contains('('), "{all}");
+    assert!(line(&all, "0.16.0").contains("(stable)"), "{all}");
+    assert!(line(&all, "0.17.0-nightly").contains("(nightly)"), "{all}");
+
+    let stable = list(&["--stable"]);
+    assert!(stable.contains("0.15.0") && stable.contains("0.16.0"), "{stable}");
+    assert!(!stable.contains("0.17.0-nightly"), "{stable}");
+
+    let nightly = list(&["--nightly"]);
+    assert!(nightly.contains("0.17.0-nightly"), "{nightly}");
+    assert!(!nightly.contains("0.15.0") && !nightly.contains("0.16.0"), "{nightly}");
+}
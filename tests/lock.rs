@@ -0,0 +1,39 @@
+use std::time::Duration;
+
+mod common;
+
+use common::*;
+
+/// Checks that commands which modify midenup's state wait for other midenup processes to finish.
+#[test]
+fn commands_wait_for_the_lock() {
+    let test_name = "commands_wait_for_the_lock";
+    let test_env = environment_setup(test_name);
+
+    const FILE: &str =
+        full_path_manifest!("tests/data/uninstall_component_test/channel-manifest.json");
+    let (_, config) = test_setup(&test_env, FILE);
+
+    let lock = config.lock().expect("failed to acquire lock");
+
+    let mut child = std::process::Command::new(env!("CARGO_BIN_EXE_midenup"))
+        .args(["clean", "--yes"])
+        .current_dir(&test_env.present_working_dir)
+        .env("MIDENUP_HOME", &test_env.midenup_home)
+        .env("CARGO_HOME", &test_env.cargo_home)
+        .env("MIDENUP_MANIFEST_URI", FILE)
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .expect("failed to run midenup");
+
+    std::thread::sleep(Duration::from_millis(500));
+    assert!(child.try_wait().unwrap().is_none(), "midenup did not wait for the lock");
+
+    drop(lock);
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("another midenup operation is in progress"), "{stderr}");
+}
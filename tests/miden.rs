@@ -1,5 +1,5 @@
 use clap::Parser;
-use midenup::commands::Midenup;
+use midenup::{commands::Midenup, options::ColorChoice};
 
 mod common;
 
@@ -101,6 +101,120 @@ fn integration_miden_test() {
         .eq(installed_toolchains);
 }
 
+/// Checks that `miden +<channel>` runs a component from that channel for a single invocation,
+/// instead of the active toolchain, and that unknown channels are rejected before installing
+/// anything.
+#[test]
+fn miden_channel_argument() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let test_name = "miden_channel_argument";
+    let test_env = environment_setup(test_name);
+
+    const FILE: &str =
+        full_path_manifest!("tests/data/integration_miden_test/channel-manifest.json");
+
+    // Fake an install of 0.15.0 and 0.16.0, whose clients record which toolchain they ran from.
+    let calls = test_env.present_working_dir.join("client-calls");
+    for version in ["0.15.0", "0.16.0"] {
+        let bin_dir = test_env.midenup_home.join("toolchains").join(version).join("bin");
+        std::fs::create_dir_all(&bin_dir).unwrap();
+        let client = bin_dir.join("miden-client");
+        std::fs::write(
+            &client,
+            format!("#!/bin/sh\necho \"$MIDENUP_TOOLCHAIN $@\" >> {}\n", calls.display()),
+        )
+        .unwrap();
+        std::fs::set_permissions(&client, std::fs::Permissions::from_mode(0o755)).unwrap();
+    }
+    std::fs::copy(
+        full_path!("tests/data/integration_miden_test/channel-manifest.json"),
+        test_env.midenup_home.join("manifest.json"),
+    )
+    .unwrap();
+
+    let (mut local_manifest, config) = test_setup(&test_env, FILE);
+
+    // The active toolchain is stable, i.e. 0.16.0.
+    for argv in [["miden", "+0.15.0", "client", "sync"], ["miden", "client", "sync", "--all"]] {
+        let command = Midenup::try_parse_from(argv).unwrap();
+        command
+            .execute_with_manifest(&config, &mut local_manifest)
+            .expect("failed to run client");
+    }
+
+    // 0.99.0 doesn't exist upstream.
+    let command = Midenup::try_parse_from(["miden", "+0.99.0", "client", "sync"]).unwrap();
+    let err = command
+        .execute_with_manifest(&config, &mut local_manifest)
+        .expect_err("an unknown channel was accepted");
+    assert!(format!("{err:#}").contains("'+0.99.0'"), "{err:#}");
+
+    assert_eq!(std::fs::read_to_string(&calls).unwrap(), "0.15.0 sync\n0.16.0 sync --all\n");
+    assert!(!test_env.midenup_home.join("toolchains").join("0.99.0").exists());
+}
+
+/// Checks that `--manifest-uri` selects the upstream manifest for both `miden` and `midenup`.
+#[test]
+fn manifest_uri_argument() {
+    const FILE: &str =
+        full_path_manifest!("tests/data/integration_miden_test/channel-manifest.json");
+
+    for argv in [
+        ["miden", "--manifest-uri", FILE, "--version"],
+        ["midenup", "--manifest-uri", FILE, "list"],
+    ] {
+        let command = Midenup::try_parse_from(argv).unwrap();
+        let config = command.config().expect("failed to load config");
+        assert_eq!(config.manifest_uri, FILE);
+        assert!(config.manifest.get_channel_by_name(&semver::Version::new(0, 14, 0)).is_some());
+    }
+}
+
+/// Checks that `--color` is honored by both `miden` and `midenup`, and that `NO_COLOR` disables
+/// colors unless they are forced.
+#[test]
+fn color_argument() {
+    let test_name = "color_argument";
+    let test_env = environment_setup(test_name);
+
+    const FILE: &str =
+        full_path_manifest!("tests/data/integration_miden_test/channel-manifest.json");
+
+    for (argv, color) in [
+        (vec!["miden", "--color", "never", "--version"], ColorChoice::Never),
+        (vec!["midenup", "--color", "always", "list"], ColorChoice::Always),
+        (vec!["midenup", "list"], ColorChoice::Auto),
+    ] {
+        assert_eq!(Midenup::try_parse_from(argv).unwrap().color(), color);
+    }
+
+    let show_pins = |args: &[&str], no_color: bool| {
+        let mut command = std::process::Command::new(env!("CARGO_BIN_EXE_midenup"));
+        command
+            .args(args)
+            .args(["show", "pins"])
+            .current_dir(&test_env.present_working_dir)
+            .env("MIDENUP_HOME", &test_env.midenup_home)
+            .env("CARGO_HOME", &test_env.cargo_home)
+            .env("MIDENUP_MANIFEST_URI", FILE)
+            .env_remove("CLICOLOR_FORCE")
+            .env_remove("NO_COLOR");
+        if no_color {
+            command.env("NO_COLOR", "1");
+        }
+        let output = command.output().expect("failed to run midenup");
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    // Escape sequences start with ESC.
+    assert!(show_pins(&["--color", "always"], false).contains('\x1b'));
+    assert!(show_pins(&["--color", "always"], true).contains('\x1b'));
+    assert!(!show_pins(&["--color", "auto"], true).contains('\x1b'));
+    assert!(!show_pins(&[], false).contains('\x1b'));
+}
+
 /// Checks that the `miden` utility recognizes the existence of a `miden-toolchain.toml` file.
 ///
 /// This file contains the required toolchain for the current project, along with a list of
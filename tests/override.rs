@@ -0,0 +1,52 @@
+use clap::Parser;
+use midenup::commands::Midenup;
+
+mod common;
+
+use common::*;
+
+/// Checks that the toolchain set via `midenup override` is resolved both through the `default`
+/// symlink and, when the symlink is missing, through the `default-toolchain.toml` file.
+#[test]
+fn override_resolves_default_toolchain() {
+    let test_name = "override_resolves_default_toolchain";
+    let test_env = environment_setup(test_name);
+
+    const FILE: &str =
+        full_path_manifest!("tests/data/integration_miden_toolchain_toml/channel-manifest.json");
+
+    let (mut local_manifest, config) = test_setup(&test_env, FILE);
+
+    let command = Midenup::try_parse_from(["midenup", "override", "0.15.0"]).unwrap();
+    command
+        .execute_with_manifest(&config, &mut local_manifest)
+        .expect("failed to override default toolchain");
+
+    let default_symlink = test_env.midenup_home.join("toolchains").join("default");
+    let default_toolchain_file = test_env.midenup_home.join("default-toolchain.toml");
+    assert!(default_symlink.symlink_metadata().is_ok());
+    assert!(default_toolchain_file.exists());
+
+    let active_toolchain = || {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_midenup"))
+            .args(["show", "active-toolchain"])
+            .current_dir(&test_env.present_working_dir)
+            .env("MIDENUP_HOME", &test_env.midenup_home)
+            .env("CARGO_HOME", &test_env.cargo_home)
+            .env("MIDENUP_MANIFEST_URI", FILE)
+            .output()
+            .expect("failed to run midenup");
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    // Only the symlink.
+    let contents = std::fs::read_to_string(&default_toolchain_file).unwrap();
+    std::fs::remove_file(&default_toolchain_file).unwrap();
+    assert_eq!(active_toolchain(), "0.15.0\n");
+
+    // Only the file.
+    std::fs::write(&default_toolchain_file, contents).unwrap();
+    std::fs::remove_file(&default_symlink).unwrap();
+    assert_eq!(active_toolchain(), "0.15.0\n");
+}
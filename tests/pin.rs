@@ -0,0 +1,74 @@
+use clap::Parser;
+use midenup::commands::Midenup;
+
+mod common;
+
+use common::*;
+
+/// Checks that `midenup pin` records the pinned version in the local manifest, that it is listed
+/// by `midenup show pins`, and that `midenup unpin` removes it again.
+#[test]
+fn pin_and_unpin_component() {
+    let test_name = "pin_and_unpin_component";
+    let test_env = environment_setup(test_name);
+
+    const FILE: &str =
+        full_path_manifest!("tests/data/uninstall_component_test/channel-manifest.json");
+
+    let (mut local_manifest, config) = test_setup(&test_env, FILE);
+
+    let command = Midenup::try_parse_from(["midenup", "pin", "fake", "0.2.0"]).unwrap();
+    command
+        .execute_with_manifest(&config, &mut local_manifest)
+        .expect("failed to pin component");
+    assert_eq!(local_manifest.pins().get("fake"), Some(&semver::Version::new(0, 2, 0)));
+
+    let command = Midenup::try_parse_from(["midenup", "pin", "missing", "0.2.0"]).unwrap();
+    assert!(command.execute_with_manifest(&config, &mut local_manifest).is_err());
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_midenup"))
+        .args(["show", "pins"])
+        .current_dir(&test_env.present_working_dir)
+        .env("MIDENUP_HOME", &test_env.midenup_home)
+        .env("CARGO_HOME", &test_env.cargo_home)
+        .env("MIDENUP_MANIFEST_URI", FILE)
+        .output()
+        .expect("failed to run midenup");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(stdout, "fake 0.2.0\n");
+
+    let command = Midenup::try_parse_from(["midenup", "unpin", "fake"]).unwrap();
+    command
+        .execute_with_manifest(&config, &mut local_manifest)
+        .expect("failed to unpin component");
+    assert!(local_manifest.pins().is_empty());
+
+    let command = Midenup::try_parse_from(["midenup", "unpin", "fake"]).unwrap();
+    assert!(command.execute_with_manifest(&config, &mut local_manifest).is_err());
+}
+
+/// Checks that the local manifest is written through a temporary file, which a previous
+/// interrupted write might have left behind half-written.
+#[test]
+fn local_manifest_is_replaced_atomically() {
+    let test_name = "local_manifest_is_replaced_atomically";
+    let test_env = environment_setup(test_name);
+
+    const FILE: &str =
+        full_path_manifest!("tests/data/uninstall_component_test/channel-manifest.json");
+
+    let (mut local_manifest, config) = test_setup(&test_env, FILE);
+    let tmp = test_env.midenup_home.join("manifest.tmp");
+    std::fs::create_dir_all(&test_env.midenup_home).unwrap();
+    std::fs::write(&tmp, "{\"manifest_version\": \"1.0").unwrap();
+
+    let command = Midenup::try_parse_from(["midenup", "pin", "fake", "0.2.0"]).unwrap();
+    command
+        .execute_with_manifest(&config, &mut local_manifest)
+        .expect("failed to pin component");
+
+    assert!(!tmp.exists());
+    let saved = config.local_manifest().expect("the local manifest is valid");
+    assert_eq!(saved.pins().get("fake"), Some(&semver::Version::new(0, 2, 0)));
+}
@@ -0,0 +1,108 @@
+mod common;
+
+use common::*;
+
+/// Checks that a corrupt local manifest is reported along with how to repair it, and that
+/// `midenup repair-manifest` rebuilds it from the installed toolchains.
+#[test]
+fn repair_corrupt_local_manifest() {
+    let test_name = "repair_corrupt_local_manifest";
+    let test_env = environment_setup(test_name);
+
+    let upstream_manifest = test_env.present_working_dir.join("channel-manifest.json");
+    let channel = |version: &str| {
+        format!(
+            r#"{{
+                "name": "{version}",
+                "components": [
+                    {{
+                        "name": "vm",
+                        "package": "miden-vm",
+                        "version": "{version}",
+                        "installed_executable": "miden-vm"
+                    }},
+                    {{
+                        "name": "std",
+                        "package": "miden-stdlib",
+                        "version": "{version}",
+                        "installed_library": "std.masp",
+                        "library_struct": "miden_stdlib::StdLibrary"
+                    }}
+                ]
+            }}"#
+        )
+    };
+    std::fs::write(
+        &upstream_manifest,
+        format!(
+            r#"{{"manifest_version": "1.0.1", "date": 1745931671, "channels": [{}, {}]}}"#,
+            channel("0.15.0"),
+            channel("0.16.0")
+        ),
+    )
+    .unwrap();
+    let manifest_uri = format!("file://{}", upstream_manifest.display());
+
+    // 0.16.0 is fully installed, 0.15.0 only has the VM, and 0.14.0 is not in the upstream
+    // manifest.
+    let toolchains_dir = test_env.midenup_home.join("toolchains");
+    for (version, files) in [
+        ("0.14.0", &["bin/miden-vm"][..]),
+        ("0.15.0", &["bin/miden-vm"][..]),
+        ("0.16.0", &["bin/miden-vm", "lib/std.masp"][..]),
+    ] {
+        for file in files {
+            let path = toolchains_dir.join(version).join(file);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, "").unwrap();
+        }
+    }
+    let local_manifest_path = test_env.midenup_home.join("manifest.json");
+    std::fs::write(&local_manifest_path, "{\"manifest_version\": \"1.0").unwrap();
+
+    let midenup = |args: &[&str]| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_midenup"))
+            .args(args)
+            .current_dir(&test_env.present_working_dir)
+            .env("MIDENUP_HOME", &test_env.midenup_home)
+            .env("CARGO_HOME", &test_env.cargo_home)
+            .env("MIDENUP_MANIFEST_URI", &manifest_uri)
+            .output()
+            .expect("failed to run midenup")
+    };
+
+    let output = midenup(&["list"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("Run `midenup repair-manifest`"), "{stderr}");
+
+    let output = midenup(&["repair-manifest"]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(output.status.success(), "{stdout}{}", String::from_utf8_lossy(&output.stderr));
+    assert!(stdout.contains("toolchain 0.14.0 is not in the upstream manifest"), "{stdout}");
+    assert!(test_env.midenup_home.join("manifest.json.corrupt").exists());
+
+    let contents = std::fs::read_to_string(&local_manifest_path).unwrap();
+    let local_manifest: midenup::manifest::Manifest = serde_json::from_str(&contents).unwrap();
+    let channels = local_manifest
+        .get_channels()
+        .map(|channel| {
+            let components =
+                channel.components.iter().map(|c| c.name.to_string()).collect::<Vec<_>>();
+            (channel.name.to_string(), components, channel.is_partially_installed())
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        channels,
+        [
+            (String::from("0.15.0"), vec![String::from("vm")], true),
+            (String::from("0.16.0"), vec![String::from("std"), String::from("vm")], false),
+        ]
+    );
+
+    // A valid manifest is left alone.
+    let output = midenup(&["repair-manifest"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stdout).unwrap().contains("nothing to repair"));
+    assert_eq!(std::fs::read_to_string(&local_manifest_path).unwrap(), contents);
+}
@@ -0,0 +1,40 @@
+use clap::Parser;
+use midenup::commands::Midenup;
+
+mod common;
+
+use common::*;
+
+/// Checks that `midenup set --unset` removes the `miden-toolchain.toml` created by `midenup set`,
+/// and fails if there is none.
+#[test]
+fn set_unset_removes_toolchain_file() {
+    let test_name = "set_unset_removes_toolchain_file";
+    let test_env = environment_setup(test_name);
+
+    const FILE: &str = full_path_manifest!("tests/data/list_test/channel-manifest.json");
+
+    let (mut local_manifest, config) = test_setup(&test_env, FILE);
+    let toolchain_file = test_env.present_working_dir.join("miden-toolchain.toml");
+
+    let command = Midenup::try_parse_from(["midenup", "set", "0.16.0"]).unwrap();
+    command
+        .execute_with_manifest(&config, &mut local_manifest)
+        .expect("failed to set local toolchain");
+    assert!(toolchain_file.exists());
+
+    let command = Midenup::try_parse_from(["midenup", "set", "--unset"]).unwrap();
+    command
+        .execute_with_manifest(&config, &mut local_manifest)
+        .expect("failed to unset local toolchain");
+    assert!(!toolchain_file.exists());
+
+    let command = Midenup::try_parse_from(["midenup", "set", "--unset"]).unwrap();
+    let err = command
+        .execute_with_manifest(&config, &mut local_manifest)
+        .expect_err("unset without a miden-toolchain.toml succeeded");
+    assert!(format!("{err:#}").contains("there is no miden-toolchain.toml"), "{err:#}");
+
+    assert!(Midenup::try_parse_from(["midenup", "set"]).is_err());
+    assert!(Midenup::try_parse_from(["midenup", "set", "0.16.0", "--unset"]).is_err());
+}
@@ -0,0 +1,497 @@
+use clap::Parser;
+use midenup::{channel, commands::Midenup, version};
+
+mod common;
+
+use common::*;
+
+/// Checks that `midenup show active-toolchain --verbose` explains *why* a toolchain is active, by
+/// naming the `miden-toolchain.toml` file that selected it.
+#[test]
+fn show_active_toolchain_names_toolchain_file() {
+    let test_name = "show_active_toolchain_names_toolchain_file";
+    let test_env = environment_setup(test_name);
+
+    const FILE: &str =
+        full_path_manifest!("tests/data/integration_miden_toolchain_toml/channel-manifest.json");
+
+    let (mut local_manifest, config) = test_setup(&test_env, FILE);
+
+    let command = Midenup::try_parse_from(["midenup", "set", "0.16.0"]).unwrap();
+    command
+        .execute_with_manifest(&config, &mut local_manifest)
+        .expect("failed to set local toolchain");
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_midenup"))
+        .args(["show", "active-toolchain", "--verbose"])
+        .current_dir(&test_env.present_working_dir)
+        .env("MIDENUP_HOME", &test_env.midenup_home)
+        .env("CARGO_HOME", &test_env.cargo_home)
+        .env("MIDENUP_MANIFEST_URI", FILE)
+        .output()
+        .expect("failed to run midenup");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let toolchain_file = test_env.present_working_dir.join("miden-toolchain.toml");
+    assert!(stdout.contains(&toolchain_file.display().to_string()), "{stdout}");
+    assert!(stdout.contains("The current active toolchain is 0.16.0"), "{stdout}");
+}
+
+/// Checks that `midenup show list --components` lists the components of every installed toolchain
+/// alongside the version they were installed from.
+#[test]
+fn show_list_components() {
+    let test_name = "show_list_components";
+    let test_env = environment_setup(test_name);
+
+    const FILE: &str =
+        full_path_manifest!("tests/data/integration_miden_toolchain_toml/channel-manifest.json");
+
+    // Pretend every channel in FILE is installed.
+    std::fs::create_dir_all(&test_env.midenup_home).unwrap();
+    std::fs::copy(
+        full_path!("tests/data/integration_miden_toolchain_toml/channel-manifest.json"),
+        test_env.midenup_home.join("manifest.json"),
+    )
+    .unwrap();
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_midenup"))
+        .args(["show", "list", "--components"])
+        .current_dir(&test_env.present_working_dir)
+        .env("MIDENUP_HOME", &test_env.midenup_home)
+        .env("CARGO_HOME", &test_env.cargo_home)
+        .env("MIDENUP_MANIFEST_URI", FILE)
+        .output()
+        .expect("failed to run midenup");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("0.14.0"), "{stdout}");
+    assert!(stdout.lines().any(|line| line.trim() == "midenc  0.8.1"), "{stdout}");
+}
+
+/// Checks that `midenup show components --json` describes the active toolchain, even if it is not
+/// installed.
+#[test]
+fn show_components_json() {
+    let test_name = "show_components_json";
+    let test_env = environment_setup(test_name);
+
+    const FILE: &str =
+        full_path_manifest!("tests/data/integration_miden_toolchain_toml/channel-manifest.json");
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_midenup"))
+        .args(["show", "components", "--json"])
+        .current_dir(&test_env.present_working_dir)
+        .env("MIDENUP_HOME", &test_env.midenup_home)
+        .env("CARGO_HOME", &test_env.cargo_home)
+        .env("MIDENUP_MANIFEST_URI", FILE)
+        .output()
+        .expect("failed to run midenup");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let contents: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(contents["channel"], "0.16.0");
+    assert_eq!(contents["installed"], false);
+    let executables = contents["executables"].as_array().unwrap();
+    assert!(executables.iter().any(|executable| executable == "vm"), "{contents}");
+    assert!(
+        contents["components"]
+            .as_array()
+            .is_some_and(|components| !components.is_empty())
+    );
+}
+
+/// Checks that `midenup show home --create` initializes MIDENUP_HOME, and that only its path and
+/// the manifest URI are printed to stdout, so that the output can be used in shell scripts.
+#[test]
+fn show_home_create() {
+    let test_name = "show_home_create";
+    let test_env = environment_setup(test_name);
+
+    const FILE: &str =
+        full_path_manifest!("tests/data/integration_miden_toolchain_toml/channel-manifest.json");
+
+    assert!(!test_env.midenup_home.exists());
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_midenup"))
+        .args(["show", "home", "--create", "--manifest-uri"])
+        .current_dir(&test_env.present_working_dir)
+        .env("MIDENUP_HOME", &test_env.midenup_home)
+        .env("CARGO_HOME", &test_env.cargo_home)
+        .env("MIDENUP_MANIFEST_URI", FILE)
+        .output()
+        .expect("failed to run midenup");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(stdout, format!("{}\n{FILE}\n", test_env.midenup_home.display()));
+    assert!(test_env.midenup_home.join("manifest.json").exists());
+    assert!(test_env.midenup_home.join("toolchains").is_dir());
+}
+
+/// Checks that `midenup show outdated` reports components for which a newer version is available
+/// upstream, exiting with a distinct code, and that it reports nothing once they are up to date.
+#[test]
+fn show_outdated() {
+    let test_name = "show_outdated";
+    let test_env = environment_setup(test_name);
+
+    const FILE: &str = full_path_manifest!("tests/data/list_test/channel-manifest.json");
+
+    // Pretend that an older version of the toolchain's only component is installed.
+    let (_, config) = test_setup(&test_env, FILE);
+    let upstream_channel = config
+        .manifest
+        .get_channel(&channel::UserChannel::Version(semver::Version::new(0, 16, 0)))
+        .unwrap()
+        .clone();
+    let mut installed_channel = upstream_channel.clone();
+    installed_channel.components[0].version = version::Authority::Cargo {
+        package: Some(String::from("midenup-nonexistent-test-component")),
+        version: semver::Version::new(0, 1, 0),
+    };
+    let mut local_manifest = LocalManifest::default();
+    local_manifest.add_channel(installed_channel);
+    std::fs::create_dir_all(&test_env.midenup_home).unwrap();
+    let local_manifest_path = test_env.midenup_home.join("manifest.json");
+    std::fs::write(&local_manifest_path, serde_json::to_string_pretty(&local_manifest).unwrap())
+        .unwrap();
+
+    let show_outdated = || {
+        std::process::Command::new(env!("CARGO_BIN_EXE_midenup"))
+            .args(["show", "outdated"])
+            .current_dir(&test_env.present_working_dir)
+            .env("MIDENUP_HOME", &test_env.midenup_home)
+            .env("CARGO_HOME", &test_env.cargo_home)
+            .env("MIDENUP_MANIFEST_URI", FILE)
+            .output()
+            .expect("failed to run midenup")
+    };
+
+    let output = show_outdated();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(
+        output.status.code(),
+        Some(100),
+        "{stdout}{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(
+        stdout
+            .lines()
+            .any(|line| line.split_whitespace().eq(["0.16.0", "fake", "0.1.0", "0.2.0"])),
+        "{stdout}"
+    );
+    // Nothing was changed.
+    let local_manifest_contents = std::fs::read_to_string(&local_manifest_path).unwrap();
+    assert_eq!(local_manifest_contents, serde_json::to_string_pretty(&local_manifest).unwrap());
+
+    let mut local_manifest = LocalManifest::default();
+    local_manifest.add_channel(upstream_channel);
+    std::fs::write(&local_manifest_path, serde_json::to_string_pretty(&local_manifest).unwrap())
+        .unwrap();
+
+    let output = show_outdated();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(output.status.success(), "{stdout}{}", String::from_utf8_lossy(&output.stderr));
+    assert!(stdout.contains("all components are up to date"), "{stdout}");
+}
+
+/// Checks that `midenup show path` prints the `bin` directory of the active toolchain, and that
+/// `--export` turns it into a shell command.
+#[test]
+fn show_path() {
+    let test_name = "show_path";
+    let test_env = environment_setup(test_name);
+
+    const FILE: &str = full_path_manifest!("tests/data/list_test/channel-manifest.json");
+
+    let show_path = |args: &[&str]| {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_midenup"))
+            .args(["show", "path"])
+            .args(args)
+            .current_dir(&test_env.present_working_dir)
+            .env("MIDENUP_HOME", &test_env.midenup_home)
+            .env("CARGO_HOME", &test_env.cargo_home)
+            .env("MIDENUP_MANIFEST_URI", FILE)
+            .output()
+            .expect("failed to run midenup");
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    // The stable toolchain is active by default.
+    let bin_dir = test_env.midenup_home.join("toolchains").join("0.16.0").join("bin");
+    assert_eq!(show_path(&[]), format!("{}\n", bin_dir.display()));
+    assert_eq!(
+        show_path(&["--export"]),
+        format!("export PATH=\"{}:$PATH\"\n", bin_dir.display())
+    );
+}
+
+/// Checks that `midenup show manifest` prints when the upstream manifest was generated, and when
+/// the local manifest was last written.
+#[test]
+fn show_manifest() {
+    let test_name = "show_manifest";
+    let test_env = environment_setup(test_name);
+
+    const FILE: &str = full_path_manifest!("tests/data/list_test/channel-manifest.json");
+
+    let show_manifest = || {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_midenup"))
+            .args(["show", "manifest"])
+            .current_dir(&test_env.present_working_dir)
+            .env("MIDENUP_HOME", &test_env.midenup_home)
+            .env("CARGO_HOME", &test_env.cargo_home)
+            .env("MIDENUP_MANIFEST_URI", FILE)
+            .env("TZ", "UTC")
+            .output()
+            .expect("failed to run midenup");
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    let stdout = show_manifest();
+    assert!(stdout.contains(FILE), "{stdout}");
+    assert!(stdout.contains("generated on 2025-04-29 13:01:11 +00:00"), "{stdout}");
+    assert!(stdout.contains("no toolchain was installed yet"), "{stdout}");
+
+    std::fs::create_dir_all(&test_env.midenup_home).unwrap();
+    std::fs::copy(
+        full_path!("tests/data/uninstall_component_test/channel-manifest.json"),
+        test_env.midenup_home.join("manifest.json"),
+    )
+    .unwrap();
+    let stdout = show_manifest();
+    assert!(stdout.contains("last written on 2025-04-29 13:01:11 +00:00"), "{stdout}");
+}
+
+/// Checks that `midenup show config` prints the resolved configuration, along with the active
+/// toolchain and why it is active.
+#[test]
+fn show_config() {
+    let test_name = "show_config";
+    let test_env = environment_setup(test_name);
+
+    const FILE: &str = full_path_manifest!("tests/data/list_test/channel-manifest.json");
+
+    let midenup = |args: &[&str]| {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_midenup"))
+            .args(args)
+            .current_dir(&test_env.present_working_dir)
+            .env("MIDENUP_HOME", &test_env.midenup_home)
+            .env("CARGO_HOME", &test_env.cargo_home)
+            .env("MIDENUP_MANIFEST_URI", FILE)
+            .output()
+            .expect("failed to run midenup");
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        output.stdout
+    };
+
+    let stdout = midenup(&["show", "config", "--json"]);
+    let contents: serde_json::Value = serde_json::from_slice(&stdout).unwrap();
+    assert_eq!(contents["midenup_home"], test_env.midenup_home.to_str().unwrap());
+    assert_eq!(contents["cargo_home"], test_env.cargo_home.to_str().unwrap());
+    assert_eq!(contents["manifest_uri"], FILE);
+    assert_eq!(contents["offline"], false);
+    assert_eq!(contents["active_toolchain"], "stable");
+    assert_eq!(contents["justification"]["kind"], "default");
+
+    // A miden-toolchain.toml file in the working directory takes precedence.
+    midenup(&["set", "0.15.0"]);
+    let stdout = String::from_utf8(midenup(&["show", "config"])).unwrap();
+    assert!(stdout.contains("0.15.0 (set by "), "{stdout}");
+    assert!(stdout.contains("miden-toolchain.toml"), "{stdout}");
+}
+
+/// Checks that `midenup show toolchain-file` prints the `miden-toolchain.toml` file found in the
+/// working directory or above it, along with the channel it sets.
+#[test]
+fn show_toolchain_file() {
+    let test_name = "show_toolchain_file";
+    let test_env = environment_setup(test_name);
+
+    const FILE: &str = full_path_manifest!("tests/data/list_test/channel-manifest.json");
+
+    let nested_dir = test_env.present_working_dir.join("nested");
+    std::fs::create_dir_all(&nested_dir).unwrap();
+    let midenup = |args: &[&str], dir: &std::path::Path| {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_midenup"))
+            .args(args)
+            .current_dir(dir)
+            .env("MIDENUP_HOME", &test_env.midenup_home)
+            .env("CARGO_HOME", &test_env.cargo_home)
+            .env("MIDENUP_MANIFEST_URI", FILE)
+            .output()
+            .expect("failed to run midenup");
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    let stdout = midenup(&["show", "toolchain-file"], &nested_dir);
+    assert_eq!(stdout, "none; using the default toolchain\nstable\n");
+
+    midenup(&["set", "0.15.0"], &test_env.present_working_dir);
+    let stdout = midenup(&["show", "toolchain-file"], &nested_dir);
+    let toolchain_file = test_env.present_working_dir.join("miden-toolchain.toml");
+    assert_eq!(stdout, format!("{}\n0.15.0\n", toolchain_file.display()));
+}
+
+/// Checks that `midenup show disk-usage` sizes every installed toolchain, largest first.
+#[test]
+fn show_disk_usage() {
+    let test_name = "show_disk_usage";
+    let test_env = environment_setup(test_name);
+
+    const FILE: &str =
+        full_path_manifest!("tests/data/integration_miden_toolchain_toml/channel-manifest.json");
+
+    // Pretend every channel in FILE is installed, with differently sized executables.
+    std::fs::create_dir_all(&test_env.midenup_home).unwrap();
+    std::fs::copy(
+        full_path!("tests/data/integration_miden_toolchain_toml/channel-manifest.json"),
+        test_env.midenup_home.join("manifest.json"),
+    )
+    .unwrap();
+    for (channel, size) in [("0.14.0", 1000), ("0.15.0", 3000), ("0.16.0", 2000)] {
+        let bin_dir = test_env.midenup_home.join("toolchains").join(channel).join("bin");
+        std::fs::create_dir_all(&bin_dir).unwrap();
+        std::fs::write(bin_dir.join("miden-vm"), vec![0; size]).unwrap();
+    }
+
+    let midenup = |args: &[&str]| {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_midenup"))
+            .args(args)
+            .current_dir(&test_env.present_working_dir)
+            .env("MIDENUP_HOME", &test_env.midenup_home)
+            .env("CARGO_HOME", &test_env.cargo_home)
+            .env("MIDENUP_MANIFEST_URI", FILE)
+            .output()
+            .expect("failed to run midenup");
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    let stdout = midenup(&["show", "disk-usage", "--json"]);
+    let usage: serde_json::Value = serde_json::from_str(&stdout).expect("invalid JSON");
+    let toolchains = usage["toolchains"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|toolchain| {
+            (toolchain["channel"].as_str().unwrap(), toolchain["size"].as_u64().unwrap())
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(toolchains, [("0.15.0", 3000), ("0.16.0", 2000), ("0.14.0", 1000)]);
+    assert_eq!(usage["total"], 6000);
+
+    let stdout = midenup(&["show", "disk-usage"]);
+    let lines = stdout.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 4, "{stdout}");
+    assert!(lines[0].starts_with("0.15.0") && lines[0].ends_with("2.9 KiB"), "{stdout}");
+    assert!(lines[3].contains("Total") && lines[3].ends_with("5.9 KiB"), "{stdout}");
+}
+
+/// Checks that `midenup show active-toolchain --installed` tells apart installed, partially
+/// installed and missing toolchains by its exit code, without installing anything.
+#[test]
+fn show_active_toolchain_installed() {
+    let test_name = "show_active_toolchain_installed";
+    let test_env = environment_setup(test_name);
+
+    const FILE: &str =
+        full_path_manifest!("tests/data/integration_miden_toolchain_toml/channel-manifest.json");
+
+    std::fs::create_dir_all(&test_env.present_working_dir).unwrap();
+    std::fs::write(
+        test_env.present_working_dir.join("miden-toolchain.toml"),
+        "[toolchain]\nchannel = \"0.15.0\"\ncomponents = [\"midenc\"]\n",
+    )
+    .unwrap();
+
+    let (_, config) = test_setup(&test_env, FILE);
+    let upstream_channel = config
+        .manifest
+        .get_channel(&channel::UserChannel::Version(semver::Version::new(0, 15, 0)))
+        .unwrap()
+        .clone();
+    let local_manifest_path = test_env.midenup_home.join("manifest.json");
+    let install = |channel: channel::Channel| {
+        let mut local_manifest = LocalManifest::default();
+        local_manifest.add_channel(channel);
+        std::fs::create_dir_all(&test_env.midenup_home).unwrap();
+        std::fs::write(
+            &local_manifest_path,
+            serde_json::to_string_pretty(&local_manifest).unwrap(),
+        )
+        .unwrap();
+    };
+    let show_installed = || {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_midenup"))
+            .args(["show", "active-toolchain", "--installed"])
+            .current_dir(&test_env.present_working_dir)
+            .env("MIDENUP_HOME", &test_env.midenup_home)
+            .env("CARGO_HOME", &test_env.cargo_home)
+            .env("MIDENUP_MANIFEST_URI", FILE)
+            .output()
+            .expect("failed to run midenup");
+        (output.status.code(), String::from_utf8(output.stdout).unwrap())
+    };
+
+    let (code, stdout) = show_installed();
+    assert_eq!(code, Some(101), "{stdout}");
+    assert!(stdout.contains("0.15.0 is not installed"), "{stdout}");
+
+    install(upstream_channel.select_components(["vm"], None));
+    let (code, stdout) = show_installed();
+    assert_eq!(code, Some(102), "{stdout}");
+    assert!(stdout.contains("not its components midenc, core"), "{stdout}");
+
+    install(upstream_channel.select_components(["vm", "midenc"], None));
+    let (code, stdout) = show_installed();
+    assert_eq!(code, Some(0), "{stdout}");
+    assert!(stdout.contains("0.15.0 is installed"), "{stdout}");
+}
+
+/// Checks that `midenup show nightlies` lists the nightlies available upstream along with the
+/// installed ones, marking the latest nightly and the installed ones.
+#[test]
+fn show_nightlies() {
+    let test_name = "show_nightlies";
+    let test_env = environment_setup(test_name);
+
+    const FILE: &str = full_path_manifest!("tests/data/show_nightlies_test/channel-manifest.json");
+
+    // 0.17.0-nightly.0 is only installed, and no longer available upstream.
+    std::fs::create_dir_all(&test_env.midenup_home).unwrap();
+    std::fs::copy(
+        full_path!("tests/data/show_nightlies_test/local-manifest.json"),
+        test_env.midenup_home.join("manifest.json"),
+    )
+    .unwrap();
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_midenup"))
+        .args(["--color", "never", "show", "nightlies"])
+        .current_dir(&test_env.present_working_dir)
+        .env("MIDENUP_HOME", &test_env.midenup_home)
+        .env("CARGO_HOME", &test_env.cargo_home)
+        .env("MIDENUP_MANIFEST_URI", FILE)
+        .output()
+        .expect("failed to run midenup");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines = stdout.lines().skip(1).collect::<Vec<_>>();
+    assert_eq!(
+        lines,
+        [
+            "0.17.0-nightly.0 (nightly-2025-05-01) (installed)",
+            "0.17.0-nightly.1 (nightly-2025-06-01) (installed)",
+            "0.17.0-nightly.2 (nightly) (latest)",
+        ],
+        "{stdout}"
+    );
+}
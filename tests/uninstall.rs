@@ -104,3 +104,190 @@ fn integration_install_uninstall_test() {
         .map(|channel| channel.name.clone())
         .eq(installed_toolchains);
 }
+
+/// Uninstalling a single component removes its files and drops it from the local manifest, while
+/// the rest of the toolchain stays installed.
+#[test]
+fn uninstall_single_component() {
+    let test_name = "uninstall_single_component";
+    let test_env = environment_setup(test_name);
+
+    // Fake an install of the channel, as if its components had been installed from artifacts.
+    let install_dir = test_env.midenup_home.join("installed_toolchains").join("0.16.0-fake");
+    let toolchain_dir = test_env.midenup_home.join("toolchains");
+    let binary = install_dir.join("bin").join("fake");
+    let opt_symlink = install_dir.join("opt").join("miden fake");
+    let library = install_dir.join("lib").join("fakelib.masp");
+    for dir in ["bin", "opt", "lib"] {
+        std::fs::create_dir_all(install_dir.join(dir)).unwrap();
+    }
+    std::fs::create_dir_all(&toolchain_dir).unwrap();
+    std::fs::write(&binary, "#!/bin/sh\n").unwrap();
+    std::fs::write(&library, "").unwrap();
+    midenup::utils::fs::symlink(&opt_symlink, &binary).unwrap();
+    midenup::utils::fs::symlink(
+        &toolchain_dir.join("0.16.0"),
+        std::path::Path::new("../installed_toolchains/0.16.0-fake"),
+    )
+    .unwrap();
+    std::fs::copy(
+        full_path!("tests/data/uninstall_component_test/channel-manifest.json"),
+        test_env.midenup_home.join("manifest.json"),
+    )
+    .unwrap();
+
+    const FILE: &str =
+        full_path_manifest!("tests/data/uninstall_component_test/channel-manifest.json");
+    let (mut local_manifest, config) = test_setup(&test_env, FILE);
+
+    // `fake` requires `fakelib`, so the latter can't be uninstalled on its own.
+    let command =
+        Midenup::try_parse_from(["midenup", "uninstall", "0.16.0", "--component", "fakelib"])
+            .unwrap();
+    command
+        .execute_with_manifest(&config, &mut local_manifest)
+        .expect_err("uninstalled a component required by another one");
+    assert!(library.exists());
+
+    let command =
+        Midenup::try_parse_from(["midenup", "uninstall", "0.16.0", "--component", "fake"]).unwrap();
+    command
+        .execute_with_manifest(&config, &mut local_manifest)
+        .expect("failed to uninstall component");
+
+    assert!(!binary.exists());
+    assert!(std::fs::symlink_metadata(&opt_symlink).is_err());
+    assert!(library.exists());
+    assert!(toolchain_dir.join("0.16.0").exists());
+
+    // The local manifest on disk reflects the removal.
+    let local_manifest = midenup::manifest::Manifest::load_from(
+        format!("file://{}", test_env.midenup_home.join("manifest.json").display()),
+        false,
+    )
+    .unwrap();
+    let channel = local_manifest
+        .get_channel_by_name(&midenup::version::semver::Version::new(0, 16, 0))
+        .expect("the toolchain is still installed");
+    assert!(channel.is_partially_installed());
+    assert!(channel.get_component("fake").is_none());
+    assert!(channel.get_component("fakelib").is_some());
+}
+
+/// Toolchains that are no longer available upstream, e.g. developer toolchains, can still be
+/// uninstalled.
+#[test]
+fn uninstall_local_only_channel() {
+    let test_name = "uninstall_local_only_channel";
+    let test_env = environment_setup(test_name);
+
+    // Fake an install of a channel which is only present in the local manifest.
+    let install_dir = test_env.midenup_home.join("installed_toolchains").join("0.16.0-fake");
+    let toolchain_dir = test_env.midenup_home.join("toolchains");
+    for dir in ["bin", "opt", "lib"] {
+        std::fs::create_dir_all(install_dir.join(dir)).unwrap();
+    }
+    std::fs::create_dir_all(&toolchain_dir).unwrap();
+    midenup::utils::fs::symlink(
+        &toolchain_dir.join("0.16.0"),
+        std::path::Path::new("../installed_toolchains/0.16.0-fake"),
+    )
+    .unwrap();
+    std::fs::copy(
+        full_path!("tests/data/uninstall_component_test/channel-manifest.json"),
+        test_env.midenup_home.join("manifest.json"),
+    )
+    .unwrap();
+
+    // Upstream only has 0.15.0.
+    const FILE: &str = full_path_manifest!(
+        "tests/data/integration_install_from_non_cargo/channel-manifest-1.json"
+    );
+    let (mut local_manifest, config) = test_setup(&test_env, FILE);
+    let version = midenup::version::semver::Version::new(0, 16, 0);
+    assert!(config.manifest.get_channel_by_name(&version).is_none());
+
+    let command = Midenup::try_parse_from(["midenup", "uninstall", "0.16.0"]).unwrap();
+    command
+        .execute_with_manifest(&config, &mut local_manifest)
+        .expect("failed to uninstall a toolchain missing upstream");
+
+    assert!(std::fs::symlink_metadata(toolchain_dir.join("0.16.0")).is_err());
+    assert!(!install_dir.exists());
+    let local_manifest = midenup::manifest::Manifest::load_from(
+        format!("file://{}", test_env.midenup_home.join("manifest.json").display()),
+        false,
+    )
+    .unwrap();
+    assert!(local_manifest.get_channel_by_name(&version).is_none());
+}
+
+/// `uninstall --all` uninstalls every toolchain, and removes the symlinks and files which select
+/// one of them.
+#[test]
+fn uninstall_all_toolchains() {
+    let test_name = "uninstall_all_toolchains";
+    let test_env = environment_setup(test_name);
+
+    // Fake an install of both channels, with 0.16.0 as stable and 0.15.0 as the default.
+    let toolchain_dir = test_env.midenup_home.join("toolchains");
+    std::fs::create_dir_all(&toolchain_dir).unwrap();
+    for version in ["0.15.0", "0.16.0"] {
+        let install_dir = test_env
+            .midenup_home
+            .join("installed_toolchains")
+            .join(format!("{version}-fake"));
+        for dir in ["bin", "opt", "lib"] {
+            std::fs::create_dir_all(install_dir.join(dir)).unwrap();
+        }
+        std::fs::write(install_dir.join("lib").join("fakelib.masp"), "").unwrap();
+        midenup::utils::fs::symlink(
+            &toolchain_dir.join(version),
+            std::path::Path::new(&format!("../installed_toolchains/{version}-fake")),
+        )
+        .unwrap();
+    }
+    midenup::utils::fs::symlink(&toolchain_dir.join("stable"), std::path::Path::new("0.16.0"))
+        .unwrap();
+    midenup::utils::fs::symlink(&toolchain_dir.join("default"), &toolchain_dir.join("0.15.0"))
+        .unwrap();
+    midenup::utils::fs::symlink(
+        &test_env.midenup_home.join("opt"),
+        &toolchain_dir.join("0.15.0").join("opt"),
+    )
+    .unwrap();
+    let default_toolchain_file = test_env.midenup_home.join("default-toolchain.toml");
+    std::fs::write(&default_toolchain_file, "[toolchain]\nchannel = \"0.15.0\"\n").unwrap();
+    std::fs::copy(
+        full_path!("tests/data/uninstall_all_test/channel-manifest.json"),
+        test_env.midenup_home.join("manifest.json"),
+    )
+    .unwrap();
+
+    const FILE: &str = full_path_manifest!("tests/data/uninstall_all_test/channel-manifest.json");
+    let (mut local_manifest, config) = test_setup(&test_env, FILE);
+
+    // A channel can't be given along with `--all`.
+    assert!(Midenup::try_parse_from(["midenup", "uninstall", "0.16.0", "--all"]).is_err());
+
+    let command = Midenup::try_parse_from(["midenup", "uninstall", "--all", "--yes"]).unwrap();
+    command
+        .execute_with_manifest(&config, &mut local_manifest)
+        .expect("failed to uninstall every toolchain");
+
+    assert_eq!(std::fs::read_dir(&toolchain_dir).unwrap().count(), 0);
+    assert_eq!(
+        std::fs::read_dir(test_env.midenup_home.join("installed_toolchains"))
+            .unwrap()
+            .count(),
+        0
+    );
+    assert!(std::fs::symlink_metadata(test_env.midenup_home.join("opt")).is_err());
+    assert!(!default_toolchain_file.exists());
+    let local_manifest = midenup::manifest::Manifest::load_from(
+        format!("file://{}", test_env.midenup_home.join("manifest.json").display()),
+        false,
+    )
+    .unwrap();
+    assert_eq!(local_manifest.get_channels().count(), 0);
+}
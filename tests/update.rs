@@ -144,3 +144,364 @@ fn integration_update_test() {
         .expect("Couldn't obtain directory where the stable directory is pointing to");
     assert_eq!(stable_toolchain.file_name(), toolchain_v16.file_name());
 }
+
+/// Checks that a failed update leaves the previously installed toolchain untouched, so that its
+/// components keep working.
+///
+/// The "installed" toolchain is faked, and the update is made to fail by requiring a crate that
+/// does not exist.
+#[test]
+fn integration_update_failure_keeps_previous_install() {
+    let test_name = "integration_update_failure_keeps_previous_install";
+    let test_env = environment_setup(test_name);
+
+    let toolchain_dir = test_env.midenup_home.join("toolchains");
+    let previous_install =
+        test_env.midenup_home.join("installed_toolchains").join("0.16.0-previous");
+    let previous_binary = previous_install.join("bin").join("fake");
+    std::fs::create_dir_all(previous_binary.parent().unwrap()).unwrap();
+    std::fs::create_dir_all(&toolchain_dir).unwrap();
+    std::fs::write(&previous_binary, "#!/bin/sh\n").unwrap();
+    midenup::utils::fs::symlink(
+        &toolchain_dir.join("0.16.0"),
+        std::path::Path::new("../installed_toolchains/0.16.0-previous"),
+    )
+    .unwrap();
+
+    let local_manifest_path = test_env.midenup_home.join("manifest.json");
+    std::fs::copy(
+        full_path!("tests/data/integration_update_rollback_test/channel-manifest-1.json"),
+        &local_manifest_path,
+    )
+    .unwrap();
+    let previous_local_manifest = std::fs::read_to_string(&local_manifest_path).unwrap();
+
+    // This manifest bumps the version of the only component of 0.16.0.
+    let manifest: &str =
+        full_path_manifest!("tests/data/integration_update_rollback_test/channel-manifest-2.json");
+    let (mut local_manifest, config) = test_setup(&test_env, manifest);
+
+    let command = Midenup::try_parse_from(["midenup", "update", "0.16.0"]).unwrap();
+    command
+        .execute_with_manifest(&config, &mut local_manifest)
+        .expect_err("update should have failed");
+
+    // The toolchain still points to the previous install, which still has its binary.
+    assert_eq!(
+        toolchain_dir.join("0.16.0").canonicalize().unwrap(),
+        previous_install.canonicalize().unwrap()
+    );
+    assert!(toolchain_dir.join("0.16.0").join("bin").join("fake").exists());
+
+    // And the local manifest still describes the previous install.
+    assert_eq!(std::fs::read_to_string(&local_manifest_path).unwrap(), previous_local_manifest);
+}
+
+/// Checks that `midenup update --dry-run` prints the components it would reinstall, without
+/// changing the local manifest or the installed toolchain.
+#[test]
+fn update_dry_run_changes_nothing() {
+    let test_name = "update_dry_run_changes_nothing";
+    let test_env = environment_setup(test_name);
+
+    const FILE: &str = full_path_manifest!("tests/data/list_test/channel-manifest.json");
+
+    // Pretend that an older version of the toolchain's only component is installed.
+    let (_, config) = test_setup(&test_env, FILE);
+    let mut installed_channel = config
+        .manifest
+        .get_channel(&channel::UserChannel::Version(semver::Version::new(0, 16, 0)))
+        .unwrap()
+        .clone();
+    installed_channel.components[0].version = version::Authority::Cargo {
+        package: Some(String::from("midenup-nonexistent-test-component")),
+        version: semver::Version::new(0, 1, 0),
+    };
+    let mut local_manifest = LocalManifest::default();
+    local_manifest.add_channel(installed_channel);
+    let local_manifest_contents = serde_json::to_string_pretty(&local_manifest).unwrap();
+    let local_manifest_path = test_env.midenup_home.join("manifest.json");
+    std::fs::create_dir_all(&test_env.midenup_home).unwrap();
+    std::fs::write(&local_manifest_path, &local_manifest_contents).unwrap();
+
+    // The update reuses the previous install, so the outdated component is removed from it.
+    let toolchain_dir = test_env.midenup_home.join("toolchains");
+    let previous_install =
+        test_env.midenup_home.join("installed_toolchains").join("0.16.0-previous");
+    std::fs::create_dir_all(previous_install.join("bin")).unwrap();
+    std::fs::create_dir_all(&toolchain_dir).unwrap();
+    std::fs::write(previous_install.join("bin").join("fake"), "#!/bin/sh\n").unwrap();
+    midenup::utils::fs::symlink(
+        &toolchain_dir.join("0.16.0"),
+        std::path::Path::new("../installed_toolchains/0.16.0-previous"),
+    )
+    .unwrap();
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_midenup"))
+        .args(["update", "0.16.0", "--dry-run"])
+        .current_dir(&test_env.present_working_dir)
+        .env("MIDENUP_HOME", &test_env.midenup_home)
+        .env("CARGO_HOME", &test_env.cargo_home)
+        .env("MIDENUP_MANIFEST_URI", FILE)
+        .output()
+        .expect("failed to run midenup");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(output.status.success(), "{stdout}{}", String::from_utf8_lossy(&output.stderr));
+
+    assert!(stdout.contains("remove"), "{stdout}");
+    assert!(
+        stdout.contains("from crates.io (midenup-nonexistent-test-component 0.2.0)"),
+        "{stdout}"
+    );
+    assert!(stdout.contains("update toolchain 0.16.0 in the local manifest"), "{stdout}");
+
+    assert_eq!(std::fs::read_to_string(&local_manifest_path).unwrap(), local_manifest_contents);
+    assert_eq!(
+        std::fs::read_link(toolchain_dir.join("0.16.0")).unwrap(),
+        std::path::Path::new("../installed_toolchains/0.16.0-previous")
+    );
+    assert!(previous_install.join("bin").join("fake").exists());
+    let installed_toolchains =
+        std::fs::read_dir(test_env.midenup_home.join("installed_toolchains"))
+            .unwrap()
+            .count();
+    assert_eq!(installed_toolchains, 1);
+}
+
+/// Checks that `midenup update --check` reports the available updates without installing them,
+/// and exits with a distinct code only if there are any.
+#[test]
+fn update_check_reports_without_installing() {
+    let test_name = "update_check_reports_without_installing";
+    let test_env = environment_setup(test_name);
+
+    const FILE: &str = full_path_manifest!("tests/data/list_test/channel-manifest.json");
+
+    let (_, config) = test_setup(&test_env, FILE);
+    let upstream_channel = |version| {
+        config
+            .manifest
+            .get_channel(&channel::UserChannel::Version(version))
+            .unwrap()
+            .clone()
+    };
+    let local_manifest_path = test_env.midenup_home.join("manifest.json");
+    std::fs::create_dir_all(&test_env.midenup_home).unwrap();
+    let midenup = |args: &[&str]| {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_midenup"))
+            .args(args)
+            .current_dir(&test_env.present_working_dir)
+            .env("MIDENUP_HOME", &test_env.midenup_home)
+            .env("CARGO_HOME", &test_env.cargo_home)
+            .env("MIDENUP_MANIFEST_URI", FILE)
+            .output()
+            .expect("failed to run midenup");
+        (output.status.code(), String::from_utf8(output.stdout).unwrap())
+    };
+
+    // Pretend that an older version of 0.16.0's only component is installed. 0.16.0 is also the
+    // latest stable release, so stable has nothing to update.
+    let mut installed_channel = upstream_channel(semver::Version::new(0, 16, 0));
+    installed_channel.components[0].version = version::Authority::Cargo {
+        package: Some(String::from("midenup-nonexistent-test-component")),
+        version: semver::Version::new(0, 1, 0),
+    };
+    let mut local_manifest = LocalManifest::default();
+    local_manifest.add_channel(installed_channel);
+    let local_manifest_contents = serde_json::to_string_pretty(&local_manifest).unwrap();
+    std::fs::write(&local_manifest_path, &local_manifest_contents).unwrap();
+
+    let (code, stdout) = midenup(&["update", "--check"]);
+    assert_eq!(code, Some(100), "{stdout}");
+    assert!(stdout.contains("0.16.0 can be updated:\n- fake: 0.1.0 -> 0.2.0"), "{stdout}");
+    let (code, stdout) = midenup(&["update", "stable", "--check"]);
+    assert_eq!(code, Some(0), "{stdout}");
+
+    assert_eq!(std::fs::read_to_string(&local_manifest_path).unwrap(), local_manifest_contents);
+    assert!(!test_env.midenup_home.join("toolchains").exists());
+
+    // An up to date 0.15.0 is the only stable toolchain installed.
+    let mut local_manifest = LocalManifest::default();
+    local_manifest.add_channel(upstream_channel(semver::Version::new(0, 15, 0)));
+    std::fs::write(&local_manifest_path, serde_json::to_string_pretty(&local_manifest).unwrap())
+        .unwrap();
+
+    let (code, stdout) = midenup(&["update", "--check"]);
+    assert_eq!(code, Some(0), "{stdout}");
+    let (code, stdout) = midenup(&["update", "stable", "--check"]);
+    assert_eq!(code, Some(100), "{stdout}");
+    assert!(stdout.contains("stable can be updated from 0.15.0 to 0.16.0"), "{stdout}");
+    assert!(!test_env.midenup_home.join("toolchains").exists());
+}
+
+/// Checks that `midenup update --keep-going` keeps updating the remaining toolchains after one
+/// fails, and reports the failure at the end, while plain `midenup update` stops at the first one.
+#[test]
+fn update_keep_going_reports_failures() {
+    let test_name = "update_keep_going_reports_failures";
+    let test_env = environment_setup(test_name);
+
+    // The version requirement of 0.15.0's only component can't be resolved offline, while 0.16.0
+    // is up to date.
+    const FILE: &str =
+        full_path_manifest!("tests/data/update_keep_going_test/channel-manifest.json");
+
+    let fake = |version| {
+        channel::Component::new(
+            "fake",
+            version::Authority::Cargo {
+                package: Some(String::from("midenup-nonexistent-test-component")),
+                version,
+            },
+        )
+    };
+    let mut local_manifest = LocalManifest::default();
+    for (name, version) in [
+        (semver::Version::new(0, 15, 0), semver::Version::new(0, 1, 0)),
+        (semver::Version::new(0, 16, 0), semver::Version::new(0, 2, 0)),
+    ] {
+        local_manifest.add_channel(channel::Channel::new(name, None, vec![fake(version)], vec![]));
+    }
+    std::fs::create_dir_all(&test_env.midenup_home).unwrap();
+    std::fs::write(
+        test_env.midenup_home.join("manifest.json"),
+        serde_json::to_string_pretty(&local_manifest).unwrap(),
+    )
+    .unwrap();
+
+    let update = |args: &[&str]| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_midenup"))
+            .args(["--offline", "update"])
+            .args(args)
+            .current_dir(&test_env.present_working_dir)
+            .env("MIDENUP_HOME", &test_env.midenup_home)
+            .env("CARGO_HOME", &test_env.cargo_home)
+            .env("MIDENUP_MANIFEST_URI", FILE)
+            .output()
+            .expect("failed to run midenup")
+    };
+
+    let output = update(&[]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(!output.status.success(), "{stdout}{stderr}");
+    assert!(!stdout.contains("syncing channel updates for 0.16.0"), "{stdout}");
+
+    let output = update(&["--keep-going"]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(!output.status.success(), "{stdout}{stderr}");
+    assert!(stdout.contains("syncing channel updates for 0.16.0"), "{stdout}");
+    assert!(stderr.contains("failed to update 1 out of 2 toolchains"), "{stderr}");
+    assert!(stderr.contains("  0.15.0: cannot look up the published versions"), "{stderr}");
+}
+
+/// Checks that `midenup update <toolchain> --path-update=all` only reinstalls the components
+/// installed from a path in that toolchain.
+#[test]
+fn update_path_components_of_one_toolchain() {
+    let test_name = "update_path_components_of_one_toolchain";
+    let test_env = environment_setup(test_name);
+
+    const FILE: &str =
+        full_path_manifest!("tests/data/update_keep_going_test/channel-manifest.json");
+
+    // Both toolchains have `fake` installed from a directory that changed since then.
+    let source_dir = test_env.present_working_dir.join("fake");
+    std::fs::create_dir_all(&source_dir).unwrap();
+    std::fs::write(source_dir.join("lib.rs"), "").unwrap();
+    let fake = channel::Component::new(
+        "fake",
+        version::Authority::Path {
+            path: source_dir.clone(),
+            crate_name: String::from("midenup-nonexistent-test-component"),
+            last_modification: Some(std::time::SystemTime::UNIX_EPOCH),
+        },
+    );
+    let mut local_manifest = LocalManifest::default();
+    for name in [semver::Version::new(0, 15, 0), semver::Version::new(0, 16, 0)] {
+        local_manifest.add_channel(channel::Channel::new(name, None, vec![fake.clone()], vec![]));
+    }
+    std::fs::create_dir_all(&test_env.midenup_home).unwrap();
+    std::fs::write(
+        test_env.midenup_home.join("manifest.json"),
+        serde_json::to_string_pretty(&local_manifest).unwrap(),
+    )
+    .unwrap();
+
+    let update = |args: &[&str]| {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_midenup"))
+            .args(["--offline", "update", "0.16.0", "--dry-run"])
+            .args(args)
+            .current_dir(&test_env.present_working_dir)
+            .env("MIDENUP_HOME", &test_env.midenup_home)
+            .env("CARGO_HOME", &test_env.cargo_home)
+            .env("MIDENUP_MANIFEST_URI", FILE)
+            .output()
+            .expect("failed to run midenup");
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert!(output.status.success(), "{stdout}{}", String::from_utf8_lossy(&output.stderr));
+        stdout
+    };
+
+    // Path components are left alone by default.
+    let stdout = update(&[]);
+    assert!(stdout.contains("Toolchain 0.16.0 is up to date"), "{stdout}");
+
+    let stdout = update(&["--path-update=all"]);
+    assert!(stdout.contains(&format!("from {}", source_dir.display())), "{stdout}");
+    assert!(stdout.contains("update toolchain 0.16.0 in the local manifest"), "{stdout}");
+    assert!(!stdout.contains("0.15.0"), "{stdout}");
+}
+
+/// Checks that `midenup update nightly` updates the latest installed nightly toolchain.
+#[test]
+fn update_nightly() {
+    let test_name = "update_nightly";
+    let test_env = environment_setup(test_name);
+
+    const FILE: &str = full_path_manifest!("tests/data/list_test/channel-manifest.json");
+
+    let update = || {
+        std::process::Command::new(env!("CARGO_BIN_EXE_midenup"))
+            .args(["--offline", "update", "nightly", "--dry-run"])
+            .current_dir(&test_env.present_working_dir)
+            .env("MIDENUP_HOME", &test_env.midenup_home)
+            .env("CARGO_HOME", &test_env.cargo_home)
+            .env("MIDENUP_MANIFEST_URI", FILE)
+            .output()
+            .expect("failed to run midenup")
+    };
+
+    let output = update();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(!output.status.success());
+    assert!(stderr.contains("No nightly toolchain is installed"), "{stderr}");
+
+    let nightly = channel::Channel::new(
+        semver::Version::parse("0.17.0-nightly").unwrap(),
+        Some(channel::ChannelAlias::Nightly(None)),
+        vec![channel::Component::new(
+            "fake",
+            version::Authority::Cargo {
+                package: Some(String::from("midenup-nonexistent-test-component")),
+                version: semver::Version::new(0, 3, 0),
+            },
+        )],
+        vec![],
+    );
+    let mut local_manifest = LocalManifest::default();
+    local_manifest.add_channel(nightly);
+    std::fs::create_dir_all(&test_env.midenup_home).unwrap();
+    std::fs::write(
+        test_env.midenup_home.join("manifest.json"),
+        serde_json::to_string_pretty(&local_manifest).unwrap(),
+    )
+    .unwrap();
+
+    let output = update();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(output.status.success(), "{stdout}{}", String::from_utf8_lossy(&output.stderr));
+    assert!(stdout.contains("syncing channel updates for 0.17.0-nightly"), "{stdout}");
+    assert!(stdout.contains("is up to date"), "{stdout}");
+}
@@ -0,0 +1,98 @@
+mod common;
+
+use common::*;
+
+/// Checks that `midenup which` prints the commands that `miden` runs for the components and
+/// aliases of the active toolchain.
+#[test]
+fn which_resolves_components_and_aliases() {
+    let test_name = "which_resolves_components_and_aliases";
+    let test_env = environment_setup(test_name);
+
+    const FILE: &str = full_path_manifest!("tests/data/list_test/channel-manifest.json");
+
+    std::fs::create_dir_all(&test_env.midenup_home).unwrap();
+    std::fs::write(
+        test_env.midenup_home.join("manifest.json"),
+        r#"{
+            "manifest_version": "1.0.1",
+            "date": 1745931671,
+            "channels": [
+                {
+                    "name": "0.16.0",
+                    "components": [
+                        {
+                            "name": "vm",
+                            "package": "miden-vm",
+                            "version": "0.16.0",
+                            "installed_executable": "miden-vm"
+                        },
+                        {
+                            "name": "midenc",
+                            "package": "midenc",
+                            "version": "0.1.0",
+                            "call_format": ["executable", "-L", "lib_path"],
+                            "aliases": {
+                                "compile": ["executable", "compile"]
+                            }
+                        },
+                        {
+                            "name": "std",
+                            "package": "miden-stdlib",
+                            "version": "0.16.0",
+                            "installed_library": "std.masp",
+                            "library_struct": "miden_stdlib::StdLibrary"
+                        }
+                    ]
+                }
+            ]
+        }"#,
+    )
+    .unwrap();
+
+    let midenup = |args: &[&str]| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_midenup"))
+            .args(args)
+            .current_dir(&test_env.present_working_dir)
+            .env("MIDENUP_HOME", &test_env.midenup_home)
+            .env("CARGO_HOME", &test_env.cargo_home)
+            .env("MIDENUP_MANIFEST_URI", FILE)
+            .output()
+            .expect("failed to run midenup")
+    };
+    let toolchain_dir = test_env.midenup_home.join("toolchains").join("0.16.0");
+    let midenc = toolchain_dir.join("bin").join("midenc");
+
+    let output = midenup(&["which", "vm"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        format!("{}\n", toolchain_dir.join("bin").join("miden-vm").display())
+    );
+
+    let output = midenup(&["which", "--all"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        format!(
+            "midenc: {} -L {}\nvm: {}\ncompile: {} compile\n",
+            midenc.display(),
+            toolchain_dir.join("lib").display(),
+            toolchain_dir.join("bin").join("miden-vm").display(),
+            midenc.display(),
+        )
+    );
+
+    let output = midenup(&["which", "compile", "--json"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let dispatch: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(dispatch["kind"], "alias");
+    assert_eq!(dispatch["component"], "midenc");
+    assert_eq!(dispatch["program"], midenc.to_str().unwrap());
+    assert_eq!(dispatch["args"], serde_json::json!(["compile"]));
+
+    let output = midenup(&["which", "std"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("'std' is neither an alias nor a component"), "{stderr}");
+}
@@ -34,6 +34,8 @@ enum Command {
     Format,
     /// Updates the timestamp of the manifest to the current time in UTC
     Touch,
+    /// Bump every component installed from crates.io to the latest semver-compatible version
+    Update,
     /// Clone the a toolchain to a new toolchain for further modification
     CloneToolchain {
         /// The channel to clone
@@ -135,6 +137,24 @@ impl Cli {
                 manifest.update_last_modified();
                 write_manifest(&manifest, &self.manifest_path)
             },
+            Command::Update => {
+                let result = manifest.update()?;
+                if result.changed_packages.is_empty() {
+                    println!("all components are up to date");
+                    return Ok(());
+                }
+                for package in &result.changed_packages {
+                    println!("updated {package}");
+                }
+                for package in &result.dropped_checksums {
+                    eprintln!(
+                        "warning: dropped the sha256 of the artifacts of {package}, which were \
+                         those of its previous version; add the new ones by hand"
+                    );
+                }
+                manifest.update_last_modified();
+                write_manifest(&manifest, &self.manifest_path)
+            },
             Command::CloneToolchain { from, to } => {
                 let Some(mut from) = manifest.get_channel(from).cloned() else {
                     bail!("unknown source toolchain '{from}'")